@@ -10,21 +10,31 @@ use logid::{log, logging::event_entry::AddonKind, pipe};
 
 use unimarkup_core::{
     commons::config::{output::OutputFormatKind, Config},
+    render::html::load_style,
     Unimarkup,
 };
 
-use crate::log_id::{GeneralError, GeneralInfo};
+use crate::{
+    log_handler,
+    log_id::{GeneralError, GeneralInfo},
+};
 
 /// Compiles a Unimarkup document.
 ///
 /// # Arguments
 ///
 /// * `config` - Unimarkup configuration constructed from command-line arguments passed to *unimarkup*.
+///   Project configuration files are merged into it with lower precedence.
 ///
 /// # Errors
 ///
 /// Returns a [`GeneralError`] if error occurs during compilation.
-pub fn compile(config: Config) -> Result<(), GeneralError> {
+pub fn compile(mut config: Config) -> Result<(), GeneralError> {
+    config
+        .merge_project_configs()
+        .map_err(GeneralError::ProjectConfig)?;
+    log_handler::set_ignore_cfg(&config.preamble.render);
+
     let source: String = match config.input.extension().and_then(OsStr::to_str) {
         Some("umi") => unsafe { String::from_utf8_unchecked(fs::read(&config.input).unwrap()) },
         _ => fs::read_to_string(&config.input).map_err(|error| {
@@ -37,24 +47,59 @@ pub fn compile(config: Config) -> Result<(), GeneralError> {
     };
 
     let out_path = {
-        if let Some(ref out_file) = config.output.file {
-            out_file.clone()
-        } else {
-            let mut in_file = config.input.clone();
-            in_file.set_extension("");
+        let mut out_file = match config.output.file {
+            Some(ref out_file) => out_file.clone(),
+            None => config.input.clone(),
+        };
+        // Note: Extension is set per output format
+        out_file.set_extension("");
 
-            in_file
-        }
+        out_file
     };
 
-    let um = Unimarkup::parse(&source, config);
+    let um = Unimarkup::parse(&source, config.clone());
+    // Note: Ignore entries may also be set in the preamble.
+    log_handler::set_ignore_cfg(&um.get_document().config.preamble.render);
+
+    // Note: The style may also be set in the preamble, so it is only known after parsing.
+    if let Some(style) = &um.get_document().config.preamble.cite.style {
+        load_style(style).map_err(|_| GeneralError::CitationStyle)?;
+    }
+    let output_langs = um.get_output_langs();
+
+    if output_langs.is_empty() {
+        return render_formats(&um, &out_path);
+    }
+
+    for lang in output_langs {
+        let localised_um = Unimarkup::parse_for_lang(&source, config.clone(), lang.clone())
+            .map_err(|error| {
+                pipe!(
+                    GeneralError::FileRead,
+                    format!("Could not parse file: '{:?}'", &config.input),
+                    add: AddonKind::Info(format!("Cause: {}", error))
+                )
+            })?;
+
+        let mut localised_out_path = out_path.clone().into_os_string();
+        localised_out_path.push(format!(".{}", lang));
+
+        render_formats(&localised_um, Path::new(&localised_out_path))?;
+    }
+
+    Ok(())
+}
+
+/// Renders the given [`Unimarkup`] to all configured output formats.
+/// The extension of the output format is appended to the given output path.
+fn render_formats(um: &Unimarkup, out_path: &Path) -> Result<(), GeneralError> {
     for format in um.get_formats() {
         match format {
             OutputFormatKind::Html => write_file(
                 &um.render_html(false)
                     .map_err(|_| GeneralError::Render)?
                     .to_string(),
-                &out_path,
+                out_path,
                 format.extension(),
             )?,
             OutputFormatKind::Pdf => write_raw_file(
@@ -62,7 +107,7 @@ pub fn compile(config: Config) -> Result<(), GeneralError> {
                     log!(err);
                     GeneralError::Render
                 })?,
-                &out_path,
+                out_path,
                 format.extension(),
             )?,
             OutputFormatKind::Umi => write_file(
@@ -70,9 +115,23 @@ pub fn compile(config: Config) -> Result<(), GeneralError> {
                     .map_err(|_| GeneralError::Render)?
                     .create_workbook()
                     .to_string(),
-                &out_path,
+                out_path,
                 OutputFormatKind::Umi.extension(),
             )?,
+            OutputFormatKind::Markdown => write_file(
+                &um.render_markdown()
+                    .map_err(|_| GeneralError::Render)?
+                    .to_string(),
+                out_path,
+                format.extension(),
+            )?,
+            OutputFormatKind::Latex => write_file(
+                &um.render_latex()
+                    .map_err(|_| GeneralError::Render)?
+                    .to_string(),
+                out_path,
+                format.extension(),
+            )?,
         }
     }
 
@@ -84,8 +143,9 @@ fn write_raw_file(
     out_path: impl AsRef<Path>,
     extension: &str,
 ) -> Result<(), GeneralError> {
-    let mut full_out_path: PathBuf = out_path.as_ref().into();
-    full_out_path.set_extension(extension);
+    let mut full_out_path = out_path.as_ref().as_os_str().to_owned();
+    full_out_path.push(format!(".{}", extension));
+    let full_out_path = PathBuf::from(full_out_path);
 
     log!(
         GeneralInfo::WritingToFile,
@@ -0,0 +1,150 @@
+//! Contains the handler writing log events to stderr, which applies the ignore entries of the render config.
+
+use std::{
+    io::Write,
+    sync::{Arc, RwLock},
+};
+
+use colored::{Color, Colorize};
+use logid::{
+    evident::event::Event,
+    log_id::{LogId, LogLevel},
+    logging::{event_entry::LogEventEntry, msg::LogMsg},
+};
+use unimarkup_core::commons::config::preamble::RenderConfig;
+
+/// Render config whose ignore entries are applied to all written log events.
+static IGNORE_CFG: RwLock<Option<RenderConfig>> = RwLock::new(None);
+
+/// Length of the level prefix set before the message of a log event.
+const LEVEL_PREFIX_LEN: usize = 6;
+
+/// Sets the render config whose ignore entries are applied to all log events that are written afterwards.
+///
+/// **Note:** Ignore entries are applied here instead of where events are logged,
+/// so new diagnostics are ignored without additional checks.
+pub fn set_ignore_cfg(render_cfg: &RenderConfig) {
+    if let Ok(mut ignore_cfg) = IGNORE_CFG.write() {
+        *ignore_cfg = Some(render_cfg.clone());
+    }
+}
+
+/// Writes the given log event to stderr, unless it is ignored by the set render config.
+/// The output follows the layout of the stderr writer of [`logid`].
+pub fn stderr_writer(log_event: Arc<Event<LogId, LogMsg, LogEventEntry>>) {
+    let id = log_event.get_event_id();
+    let entry = log_event.get_entry();
+
+    let ignored = IGNORE_CFG
+        .read()
+        .ok()
+        .and_then(|ignore_cfg| {
+            ignore_cfg
+                .as_ref()
+                .map(|cfg| cfg.ignores_event(id, entry.get_infos()))
+        })
+        .unwrap_or(false);
+    if ignored {
+        return;
+    }
+
+    let level = id.get_log_level();
+    let color = level_color(level);
+    let msg = match log_event.get_msg() {
+        Some(msg) => msg.to_string(),
+        None => format!("{}, entry='{}'", id, log_event.get_entry_id()),
+    };
+
+    let mut lines = Vec::new();
+    push_lines(
+        &mut lines,
+        format!(
+            "{}{}",
+            level.to_string().bold().color(color),
+            " ".repeat(LEVEL_PREFIX_LEN.saturating_sub(level.to_string().len()))
+        ),
+        LEVEL_PREFIX_LEN,
+        &msg,
+        color,
+    );
+
+    let mut addons: Vec<(&str, Color, String)> = entry
+        .get_related()
+        .iter()
+        .map(|related| {
+            let related_id = related.get_event_id();
+            let related_level = related_id.get_log_level();
+            let content = format!(
+                "lvl='{}', {}, entry='{}'",
+                related_level
+                    .to_string()
+                    .bold()
+                    .color(level_color(related_level)),
+                related_id,
+                related.get_entry_id()
+            );
+            ("Related", color, content)
+        })
+        .collect();
+    addons.extend(
+        entry
+            .get_infos()
+            .iter()
+            .map(|info| ("Info", level_color(LogLevel::Info), info.clone())),
+    );
+
+    let addon_count = addons.len();
+    for (i, (kind, kind_color, content)) in addons.into_iter().enumerate() {
+        let cross = if i + 1 == addon_count { "╰" } else { "├" };
+        let prefix = format!(
+            "{}{} {}: ",
+            cross.color(color),
+            "───>".color(color),
+            kind.bold().color(kind_color)
+        );
+        push_lines(
+            &mut lines,
+            prefix,
+            "|---> : ".len() + kind.len(),
+            &content,
+            color,
+        );
+    }
+
+    let mut stderr = std::io::stderr().lock();
+    for line in lines {
+        let _ = writeln!(stderr, "{}", line);
+    }
+    let _ = stderr.flush();
+}
+
+/// Pushes the lines of the given content, with the prefix set before the first line.
+/// Following lines are indented by the length of the prefix.
+fn push_lines(
+    lines: &mut Vec<String>,
+    prefix: String,
+    prefix_len: usize,
+    content: &str,
+    color: Color,
+) {
+    let indent = " ".repeat(prefix_len.saturating_sub(1));
+    let mut content_lines = content.lines();
+
+    lines.push(format!(
+        "{}{}",
+        prefix,
+        content_lines.next().unwrap_or_default()
+    ));
+    lines.extend(content_lines.map(|line| format!("{}{}{}", "│".color(color), indent, line)));
+}
+
+/// Returns the color of the given log level.
+fn level_color(level: LogLevel) -> Color {
+    match level {
+        LogLevel::Error => Color::Red,
+        LogLevel::Warn => Color::Yellow,
+        LogLevel::Info => Color::Green,
+        LogLevel::Debug => Color::Blue,
+        LogLevel::Trace => Color::Cyan,
+    }
+}
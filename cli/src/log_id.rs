@@ -1,7 +1,10 @@
 //! Defines log-ids for the cli crate
 
+use std::path::PathBuf;
+
 use logid::{ErrLogId, InfoLogId};
 use thiserror::Error;
+use unimarkup_core::commons::config::log_id::ConfigErr;
 
 /// General error log-ids for the cli crate
 #[derive(Debug, Clone, Error, ErrLogId)]
@@ -25,6 +28,14 @@ pub enum GeneralError {
     /// Log-id denoting that rendering failed
     #[error("Failed rendering given input.")]
     Render,
+
+    /// Log-id denoting that the citation style could not be loaded
+    #[error("Failed loading the citation style.")]
+    CitationStyle,
+
+    /// Log-id denoting that a project configuration file could not be merged
+    #[error("Failed merging the project config file {:?}: {}", (.0).0, (.0).1)]
+    ProjectConfig((PathBuf, ConfigErr)),
 }
 
 #[derive(Debug, Clone, InfoLogId)]
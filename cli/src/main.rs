@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use logid::{
     event_handler::builder::LogEventHandlerBuilder,
     log,
@@ -8,13 +8,42 @@ use logid::{
         filter::{AddonFilter, FilterConfigBuilder},
     },
 };
-use unimarkup_core::commons::config::Config;
+use unimarkup_core::{
+    commons::config::{Config, ABOUT, HELP_TEMPLATE, UNIMARKUP_NAME},
+    render::html::bundled_style_ids,
+};
 
 use crate::log_id::{GeneralError, GeneralInfo};
 
 mod compiler;
+mod log_handler;
 mod log_id;
 
+/// Arguments of the CLI, which either compiles the given input or runs a subcommand.
+#[derive(Parser, Debug)]
+#[command(
+    name = UNIMARKUP_NAME,
+    help_template = HELP_TEMPLATE,
+    author,
+    version,
+    about = ABOUT,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    config: Config,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Lists the ids of the bundled citation styles that may be set as `--citation-style`.
+    CitationStyles,
+}
+
 fn main() {
     let _ = logid::logging::filter::set_filter(
         FilterConfigBuilder::new(LogLevel::Info)
@@ -22,13 +51,21 @@ fn main() {
             .build(),
     );
 
+    // Note: Ignore entries are applied by the handler, because logid filters may only allow log-ids.
     let _handler = LogEventHandlerBuilder::new()
-        .to_stderr()
+        .add_handler(log_handler::stderr_writer)
         .all_log_events()
         .build();
 
-    let config = Config::parse();
-    match compiler::compile(config) {
+    let cli = Cli::parse();
+    if let Some(Command::CitationStyles) = cli.command {
+        for id in bundled_style_ids() {
+            println!("{}", id);
+        }
+        return;
+    }
+
+    match compiler::compile(cli.config) {
         Ok(_) => {
             log!(GeneralInfo::FinishedCompiling);
         }
@@ -13,10 +13,14 @@ pub mod locale;
 pub mod log_id;
 pub mod output;
 pub mod preamble;
-
-const UNIMARKUP_NAME: &str = "unimarkup";
-const ABOUT: &str = "The official compiler for Unimarkup.";
-const HELP_TEMPLATE: &str = r#"
+pub mod project;
+
+/// Name of the compiler shown in the CLI help.
+pub const UNIMARKUP_NAME: &str = "unimarkup";
+/// Description of the compiler shown in the CLI help.
+pub const ABOUT: &str = "The official compiler for Unimarkup.";
+/// Template of the CLI help.
+pub const HELP_TEMPLATE: &str = r#"
 {before-help}{name} {version} - {about-with-newline}
 Written by: {author-with-newline}
 {usage-heading} {usage}
@@ -70,6 +74,15 @@ impl ConfigFns for Config {
         self.output.validate()?;
         self.merging.validate()?;
 
+        if let Some(config_file) = &self.merging.config_file {
+            if !config_file.exists() {
+                return err!(
+                    ConfigErr::InvalidFile,
+                    format!("Project config file not found: {:?}", config_file)
+                );
+            }
+        }
+
         if !self.input.exists() {
             return err!(
                 ConfigErr::InvalidFile,
@@ -84,7 +97,12 @@ impl ConfigFns for Config {
 #[derive(Args, Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct MergingConfig {
     #[arg(long)]
+    #[serde(default)]
     pub ignore_preamble: bool,
+    /// Project configuration file to use instead of discovering `unimarkup.yml` files.
+    #[arg(long = "config")]
+    #[serde(skip)]
+    pub config_file: Option<PathBuf>,
 }
 
 impl ConfigFns for MergingConfig {
@@ -163,8 +181,8 @@ mod tests {
         let cfg: Config = Config::parse_from(vec![
             "unimarkup",
             "--output-formats=html",
-            //invalid attribute "shouldfail" on purpose
-            "--citation-style=shouldfail",
+            //invalid attribute "shouldfail.csl" on purpose
+            "--citation-style=shouldfail.csl",
             path.to_str().unwrap(),
         ]);
 
@@ -189,4 +207,128 @@ mod tests {
 
         cfg.validate().unwrap();
     }
+
+    #[test]
+    fn config_with_ignore_file() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .canonicalize()
+            .unwrap();
+        path.push("tests/sample_files/empty.um");
+        let mut ignore_path = path.clone();
+        ignore_path.set_file_name("unimarkup.ignore");
+
+        let cfg: Config = Config::parse_from(vec![
+            "unimarkup",
+            "--formats=html",
+            &format!("--ignore-file={}", ignore_path.to_str().unwrap()),
+            path.to_str().unwrap(),
+        ]);
+
+        cfg.validate().unwrap();
+
+        let render_cfg = &cfg.preamble.render;
+        assert_eq!(
+            render_cfg.ignore.len(),
+            2,
+            "Comments or blank lines were not skipped."
+        );
+        assert!(render_cfg.ignores_kind("Heading"));
+        assert!(!render_cfg.ignores_kind("Paragraph"));
+        assert!(render_cfg.ignores_log_id(&logid::log_id::LogId::new(
+            "unimarkup_render::log_id",
+            "RenderError::Unimplemented",
+            logid::log_id::LogLevel::Error,
+        )));
+        assert!(render_cfg.ignores_event(
+            &logid::log_id::LogId::new(
+                "unimarkup_render::log_id",
+                "GeneralWarning::UnsafeUrl",
+                logid::log_id::LogLevel::Warn,
+            ),
+            &["Element: Heading".to_string()],
+        ));
+    }
+
+    #[test]
+    fn lang_without_cli_argument() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .canonicalize()
+            .unwrap();
+        path.push("tests/sample_files/empty.um");
+
+        let mut cfg: Config = Config::parse_from(vec!["unimarkup", path.to_str().unwrap()]);
+        assert!(
+            cfg.preamble.i18n.lang.is_none(),
+            "CLI must not set a default language."
+        );
+
+        let mut preamble = Preamble::default();
+        preamble.i18n.lang = Some(icu_locid::locale!("de-AT"));
+        cfg.preamble.merge(preamble);
+
+        assert_eq!(cfg.preamble.i18n.lang, Some(icu_locid::locale!("de-AT")));
+    }
+
+    #[test]
+    fn numbering_config() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .canonicalize()
+            .unwrap();
+        path.push("tests/sample_files/empty.um");
+
+        let cfg: Config = Config::parse_from(vec![
+            "unimarkup",
+            "--numbering-depth=3",
+            "--numbering-style=roman",
+            path.to_str().unwrap(),
+        ]);
+        cfg.validate().unwrap();
+
+        let numbering = &cfg.preamble.numbering;
+        assert_eq!(numbering.numbering_depth, Some(3));
+        assert_eq!(numbering.style().format(14), "XIV");
+        assert_eq!(preamble::NumberingStyle::Alpha.format(27), "AA");
+
+        let mut cfg: Config = Config::parse_from(vec![
+            "unimarkup",
+            "--numbering-depth=7",
+            path.to_str().unwrap(),
+        ]);
+        assert!(
+            cfg.validate().is_err(),
+            "Invalid numbering depth was not detected."
+        );
+
+        let mut preamble = Preamble::default();
+        preamble.numbering.numbering_style = Some(preamble::NumberingStyle::Alpha);
+        cfg.preamble.merge(preamble);
+        assert_eq!(
+            cfg.preamble.numbering.style(),
+            preamble::NumberingStyle::Alpha,
+            "Numbering style of the preamble was not used."
+        );
+    }
+
+    #[test]
+    fn latex_listings_from_preamble() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .canonicalize()
+            .unwrap();
+        path.push("tests/sample_files/empty.um");
+
+        let mut cfg: Config = Config::parse_from(vec!["unimarkup", path.to_str().unwrap()]);
+        assert!(
+            cfg.preamble.latex.latex_listings.is_none(),
+            "CLI must not set a default listings package."
+        );
+
+        let mut preamble = Preamble::default();
+        preamble.latex.latex_listings = Some(preamble::LatexListings::Minted);
+        cfg.preamble.merge(preamble);
+
+        assert_eq!(
+            cfg.preamble.latex.latex_listings,
+            Some(preamble::LatexListings::Minted)
+        );
+    }
 }
@@ -13,18 +13,26 @@ pub struct Output {
     /// Defines the output format to render to.
     /// If this option is not set, the input is rendered to all supported formats.
     ///
-    /// **Supported formats:** `html`, `pdf`
+    /// **Supported formats:** `html`, `pdf`, `markdown`, `latex`
     #[arg(long, alias = "output-formats", value_parser = parse_to_hashset::<OutputFormatKind>, required = false, default_value = "html")]
+    #[serde(default)]
     pub formats: HashSet<OutputFormatKind>,
     /// `true` overwrites existing output files
     #[arg(long, alias = "overwrite-out-files")]
+    #[serde(default)]
     pub overwrite: bool,
+    /// Layout of the written HTML.
+    #[arg(long, value_enum, default_value_t)]
+    #[serde(default)]
+    pub html_format: HtmlFormat,
 }
 
 impl ConfigFns for Output {
     fn merge(&mut self, other: Self) {
         self.file.replace_none(other.file);
         self.formats.extend(other.formats.iter());
+
+        // Note: `html_format` has a default value, so the format of the main config is kept.
     }
 
     fn validate(&self) -> Result<(), ConfigErr> {
@@ -53,6 +61,20 @@ impl ConfigFns for Output {
     }
 }
 
+/// Layouts of the written HTML.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HtmlFormat {
+    /// All elements are written without whitespace between them.
+    #[default]
+    Compact,
+    /// Block elements are written on their own lines and indented by nesting depth.
+    /// Attributes are sorted by name, so the output diffs well.
+    Pretty,
+    /// Like `compact`, but inlined styles and scripts are minified as well.
+    Minified,
+}
+
 #[derive(
     Default,
     Debug,
@@ -69,9 +91,16 @@ impl ConfigFns for Output {
 )]
 pub enum OutputFormatKind {
     #[default]
+    #[serde(alias = "html")]
     Html,
+    #[serde(alias = "umi")]
     Umi,
+    #[serde(alias = "pdf")]
     Pdf,
+    #[serde(alias = "markdown", alias = "md")]
+    Markdown,
+    #[serde(alias = "latex", alias = "tex")]
+    Latex,
 }
 
 impl OutputFormatKind {
@@ -81,6 +110,8 @@ impl OutputFormatKind {
             OutputFormatKind::Html => "html",
             OutputFormatKind::Umi => "umi",
             OutputFormatKind::Pdf => "pdf",
+            OutputFormatKind::Markdown => "md",
+            OutputFormatKind::Latex => "tex",
         }
     }
 }
@@ -93,6 +124,8 @@ impl FromStr for OutputFormatKind {
             "html" => Ok(OutputFormatKind::Html),
             "umi" => Ok(OutputFormatKind::Umi),
             "pdf" => Ok(OutputFormatKind::Pdf),
+            "markdown" | "md" => Ok(OutputFormatKind::Markdown),
+            "latex" | "tex" => Ok(OutputFormatKind::Latex),
             o => Err(format!("Bad output format: {}", o)),
         }
     }
@@ -3,9 +3,9 @@ use std::{
     path::PathBuf,
 };
 
-use clap::Args;
+use clap::{Args, ValueEnum};
 use icu_locid::Locale;
-use logid::{err, log};
+use logid::{err, log, log_id::LogId, logging::event_entry::AddonKind};
 use serde::{Deserialize, Serialize};
 
 use super::{locale, log_id::ConfigErr, parse_to_hashset, ConfigFns, ReplaceIfNone};
@@ -24,6 +24,21 @@ pub struct Preamble {
     #[command(flatten)]
     #[serde(flatten)]
     pub i18n: I18n,
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub numbering: Numbering,
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub toc: TocConfig,
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub slugs: SlugConfig,
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub styling: StyleConfig,
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub latex: LatexConfig,
 }
 
 impl ConfigFns for Preamble {
@@ -32,13 +47,23 @@ impl ConfigFns for Preamble {
         self.cite.merge(other.cite);
         self.render.merge(other.render);
         self.i18n.merge(other.i18n);
+        self.numbering.merge(other.numbering);
+        self.toc.merge(other.toc);
+        self.slugs.merge(other.slugs);
+        self.styling.merge(other.styling);
+        self.latex.merge(other.latex);
     }
 
     fn validate(&self) -> Result<(), ConfigErr> {
         self.metadata.validate()?;
         self.cite.validate()?;
         self.render.validate()?;
-        self.i18n.validate()
+        self.i18n.validate()?;
+        self.numbering.validate()?;
+        self.toc.validate()?;
+        self.slugs.validate()?;
+        self.styling.validate()?;
+        self.latex.validate()
     }
 }
 
@@ -48,8 +73,13 @@ pub fn default_locale() -> Locale {
 
 #[derive(Args, Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct I18n {
-    #[arg(long, value_parser = locale::clap::parse_locale, default_value = "en")]
-    #[serde(with = "locale::serde::optional")]
+    /// Main language of the document.
+    /// If no language is set in the CLI, preamble or project configuration, `en` is used.
+    ///
+    /// **Note:** There is no CLI default value, because configs are merged with `replace_none`.
+    /// A default would always be set, and languages of preambles or project configs would never be used.
+    #[arg(long, value_parser = locale::clap::parse_locale)]
+    #[serde(with = "locale::serde::optional", default)]
     pub lang: Option<Locale>,
 
     #[arg(long, value_parser = parse_to_hashset::<Locale>, required = false, default_value = "")]
@@ -71,6 +101,297 @@ impl ConfigFns for I18n {
     }
 }
 
+/// Numbering of headings.
+#[derive(Args, Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Numbering {
+    /// Deepest heading level that is numbered in the output, e.g. `3` numbers headings up to `### `.
+    /// Headings are not numbered in the output if no depth is set,
+    /// but internal references still use the numbers of all heading levels.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub numbering_depth: Option<u8>,
+    /// Style of the numbers.
+    /// Arabic numbers are used if no style is set.
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub numbering_style: Option<NumberingStyle>,
+}
+
+impl Numbering {
+    /// Returns the set numbering style, or the default style if none is set.
+    pub fn style(&self) -> NumberingStyle {
+        self.numbering_style.unwrap_or_default()
+    }
+}
+
+impl ConfigFns for Numbering {
+    fn merge(&mut self, other: Self) {
+        self.numbering_depth.replace_none(other.numbering_depth);
+        self.numbering_style.replace_none(other.numbering_style);
+    }
+
+    fn validate(&self) -> Result<(), ConfigErr> {
+        if self.numbering_depth.is_some_and(|depth| depth > 6) {
+            return err!(
+                ConfigErr::InvalidConfig,
+                "'numbering_depth' must be a heading level from 0 to 6."
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Table of contents of the document.
+#[derive(Args, Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TocConfig {
+    /// `true` adds a table of contents at the beginning of the document,
+    /// unless a table of contents is placed with `{$um.toc}`.
+    #[arg(long)]
+    #[serde(default)]
+    pub toc: bool,
+    /// Deepest heading level that is listed in tables of contents.
+    /// All levels are listed if no depth is set.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub toc_depth: Option<u8>,
+}
+
+impl ConfigFns for TocConfig {
+    fn merge(&mut self, other: Self) {
+        self.toc |= other.toc;
+        self.toc_depth.replace_none(other.toc_depth);
+    }
+
+    fn validate(&self) -> Result<(), ConfigErr> {
+        if self.toc_depth.is_some_and(|depth| depth > 6) {
+            return err!(
+                ConfigErr::InvalidConfig,
+                "'toc_depth' must be a heading level from 0 to 6."
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Rules to derive heading ids from the heading content.
+#[derive(Args, Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlugConfig {
+    /// `true` transliterates letters to ASCII, e.g. `ä` to `ae`, and removes other non-ASCII characters.
+    #[arg(long)]
+    #[serde(default)]
+    pub slug_ascii: bool,
+    /// `true` keeps the case of the heading content instead of lowercasing it.
+    #[arg(long)]
+    #[serde(default)]
+    pub slug_keep_case: bool,
+}
+
+impl ConfigFns for SlugConfig {
+    fn merge(&mut self, other: Self) {
+        self.slug_ascii |= other.slug_ascii;
+        self.slug_keep_case |= other.slug_keep_case;
+    }
+
+    fn validate(&self) -> Result<(), ConfigErr> {
+        Ok(())
+    }
+}
+
+/// Stylesheets and themes of the HTML output.
+#[derive(Args, Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StyleConfig {
+    /// Built-in theme that styles the document.
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub theme: Option<HtmlTheme>,
+    /// CSS files that are added after the theme, so their rules take precedence.
+    #[arg(long = "stylesheet", required = false)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub stylesheets: Vec<PathBuf>,
+    /// `true` links stylesheets in the output instead of inlining them.
+    #[arg(long)]
+    #[serde(default)]
+    pub link_stylesheets: bool,
+    /// Theme for syntax highlighting.
+    /// Either the name of a theme bundled with syntect (e.g. `InspiredGitHub`), or a `.tmTheme` file.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub highlight_theme: Option<String>,
+}
+
+impl ConfigFns for StyleConfig {
+    fn merge(&mut self, other: Self) {
+        self.theme.replace_none(other.theme);
+        for stylesheet in other.stylesheets {
+            if !self.stylesheets.contains(&stylesheet) {
+                self.stylesheets.push(stylesheet);
+            }
+        }
+        self.link_stylesheets |= other.link_stylesheets;
+        self.highlight_theme.replace_none(other.highlight_theme);
+    }
+
+    fn validate(&self) -> Result<(), ConfigErr> {
+        for stylesheet in &self.stylesheets {
+            if !stylesheet.exists() {
+                return err!(
+                    ConfigErr::InvalidFile,
+                    format!("Stylesheet not found: {:?}", stylesheet)
+                );
+            }
+        }
+
+        if let Some(theme) = &self.highlight_theme {
+            if theme.ends_with(".tmTheme") && !PathBuf::from(theme).exists() {
+                return err!(
+                    ConfigErr::InvalidFile,
+                    format!("Highlight theme file not found: '{}'", theme)
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Built-in themes of the HTML output.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HtmlTheme {
+    /// Single column layout for short documents.
+    Article,
+    /// Layout for longer documents with emphasized chapter headings.
+    Report,
+    /// Every top-level section is shown as one slide.
+    Slides,
+}
+
+/// Document class and preamble of the LaTeX output.
+#[derive(Args, Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatexConfig {
+    /// LaTeX document class, e.g. `report` or a class provided by a venue.
+    /// Falls back to `article` if no class is set.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub latex_class: Option<String>,
+    /// File with LaTeX commands that are added after the default preamble,
+    /// e.g. to load packages or define commands required by a venue.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub latex_preamble: Option<PathBuf>,
+    /// Package that typesets verbatim blocks.
+    /// Falls back to `listings` if no package is set.
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub latex_listings: Option<LatexListings>,
+}
+
+impl ConfigFns for LatexConfig {
+    fn merge(&mut self, other: Self) {
+        self.latex_class.replace_none(other.latex_class);
+        self.latex_preamble.replace_none(other.latex_preamble);
+        self.latex_listings.replace_none(other.latex_listings);
+    }
+
+    fn validate(&self) -> Result<(), ConfigErr> {
+        if let Some(preamble) = &self.latex_preamble {
+            if !preamble.exists() {
+                return err!(
+                    ConfigErr::InvalidFile,
+                    format!("LaTeX preamble file not found: {:?}", preamble)
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Packages that typeset verbatim blocks in the LaTeX output.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LatexListings {
+    /// The `listings` package, which needs no external tools.
+    #[default]
+    Listings,
+    /// The `minted` package, which highlights code with Pygments.
+    /// LaTeX must be run with `-shell-escape` to compile the output.
+    Minted,
+}
+
+/// Styles of the numbers of headings.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NumberingStyle {
+    /// Arabic numbers, e.g. `2.1`.
+    #[default]
+    Arabic,
+    /// Uppercase roman numbers, e.g. `II.I`.
+    Roman,
+    /// Uppercase letters, e.g. `B.A`.
+    Alpha,
+}
+
+impl NumberingStyle {
+    /// Formats the given number in this style.
+    /// `0` is formatted as arabic number, because neither roman numbers nor letters can represent it.
+    pub fn format(&self, number: usize) -> String {
+        match self {
+            NumberingStyle::Arabic => number.to_string(),
+            _ if number == 0 => number.to_string(),
+            NumberingStyle::Roman => {
+                const NUMERALS: [(usize, &str); 13] = [
+                    (1000, "M"),
+                    (900, "CM"),
+                    (500, "D"),
+                    (400, "CD"),
+                    (100, "C"),
+                    (90, "XC"),
+                    (50, "L"),
+                    (40, "XL"),
+                    (10, "X"),
+                    (9, "IX"),
+                    (5, "V"),
+                    (4, "IV"),
+                    (1, "I"),
+                ];
+
+                let mut remaining = number;
+                let mut roman = String::new();
+                for (value, numeral) in NUMERALS {
+                    while remaining >= value {
+                        roman.push_str(numeral);
+                        remaining -= value;
+                    }
+                }
+                roman
+            }
+            NumberingStyle::Alpha => {
+                // Bijective base-26: 1 => A, 26 => Z, 27 => AA
+                let mut remaining = number;
+                let mut letters = Vec::new();
+                while remaining > 0 {
+                    remaining -= 1;
+                    letters.push((b'A' + (remaining % 26) as u8) as char);
+                    remaining /= 26;
+                }
+                letters.iter().rev().collect()
+            }
+        }
+    }
+}
+
 #[derive(Args, Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RenderConfig {
     #[arg(long = "ignore-file", value_parser = parse_ignore_file, required = false, default_value = "")]
@@ -87,6 +408,16 @@ pub struct RenderConfig {
     #[arg(long)]
     #[serde(default)]
     pub allow_unsafe: bool,
+    /// `true` adds a self-link anchor next to every heading in HTML output.
+    #[arg(long)]
+    #[serde(default)]
+    pub heading_anchors: bool,
+    /// HTML template file that replaces the default document shell.
+    /// See `unimarkup_render::html::template` for the available placeholders.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub html_template: Option<PathBuf>,
 }
 
 impl ConfigFns for RenderConfig {
@@ -95,19 +426,80 @@ impl ConfigFns for RenderConfig {
         self.parameter.extend(other.parameter);
         self.keep_comments |= other.keep_comments;
         self.allow_unsafe |= other.allow_unsafe;
+        self.heading_anchors |= other.heading_anchors;
+        self.html_template.replace_none(other.html_template);
     }
 
     fn validate(&self) -> Result<(), ConfigErr> {
-        // TODO: validate ignore and parameter syntax
+        if let Some(template) = &self.html_template {
+            if !template.exists() {
+                return err!(
+                    ConfigErr::InvalidFile,
+                    format!("HTML template file not found: {:?}", template)
+                );
+            }
+        }
+
+        for entry in &self.ignore {
+            if entry.split("::").any(|part| part.trim().is_empty()) {
+                return err!(
+                    ConfigErr::InvalidConfig,
+                    format!("Invalid ignore entry: '{}'", entry)
+                );
+            }
+        }
+
+        // TODO: validate parameter syntax
         Ok(())
     }
 }
 
+impl RenderConfig {
+    /// Returns `true` if diagnostics with the given log-id are ignored.
+    ///
+    /// Ignore entries may either be the log-id identifier (e.g. `RenderError::Unimplemented`),
+    /// or the identifier prefixed with its module path.
+    pub fn ignores_log_id(&self, log_id: &LogId) -> bool {
+        let identifier = log_id.get_identifier();
+
+        self.ignore.contains(identifier)
+            || self
+                .ignore
+                .contains(&format!("{}::{}", log_id.get_module_path(), identifier))
+    }
+
+    /// Returns `true` if diagnostics for the given element kind (e.g. `Heading`) are ignored.
+    pub fn ignores_kind(&self, kind: &str) -> bool {
+        self.ignore.contains(kind)
+    }
+
+    /// Returns `true` if a diagnostic with the given log-id and info addons is ignored.
+    /// The element kind of a diagnostic is taken from its [`element_kind_info`] addon.
+    pub fn ignores_event(&self, log_id: &LogId, infos: &[String]) -> bool {
+        self.ignores_log_id(log_id)
+            || infos
+                .iter()
+                .filter_map(|info| info.strip_prefix(ELEMENT_KIND_INFO))
+                .any(|kind| self.ignores_kind(kind))
+    }
+}
+
+/// Prefix of the info addon that names the element kind a diagnostic belongs to.
+pub const ELEMENT_KIND_INFO: &str = "Element: ";
+
+/// Returns the info addon naming the element kind a diagnostic belongs to,
+/// so that the diagnostic may be ignored by kind (see [`RenderConfig::ignores_event`]).
+pub fn element_kind_info(kind: &str) -> AddonKind {
+    AddonKind::Info(format!("{}{}", ELEMENT_KIND_INFO, kind))
+}
+
 // TODO: Instead of PathBufs, file contents should be parsed on deserialization.
 // This makes it easier to access the parsed data without creating another config struct.
 // It also makes compiling faster for bad inputs, since it would break before parsing starts.
 #[derive(Args, Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Citedata {
+    /// CSL style used to render citations and the bibliography.
+    /// The LaTeX output ignores the style, because its bibliography is typeset by BibTeX.
     #[arg(long = "citation-style")]
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
@@ -124,6 +516,11 @@ pub struct Citedata {
     #[arg(long = "csl-locale", value_parser = parse_locale_path_buf, required = false, default_value = "")]
     #[serde(skip)]
     pub csl_locales: Vec<(Locale, PathBuf)>,
+
+    /// Warn about references that are never cited in the document.
+    #[arg(long)]
+    #[serde(default)]
+    pub warn_unused_references: bool,
 }
 
 fn parse_locale_path_buf(s: &str) -> Result<(Locale, PathBuf), clap::Error> {
@@ -161,15 +558,21 @@ impl ConfigFns for Citedata {
             self.citation_locales.insert(locale, pathbuf);
         }
         self.citation_locales.extend(other.citation_locales);
+        self.warn_unused_references |= other.warn_unused_references;
     }
 
     fn validate(&self) -> Result<(), ConfigErr> {
-        if let Some(file) = &self.style {
-            if !file.exists() {
-                // TODO: check for included styles
+        if let Some(style) = &self.style {
+            // Note: Styles that are neither `.csl` files nor paths are ids of bundled styles,
+            // which are validated together with the style content by the renderer.
+            let is_path = style
+                .extension()
+                .is_some_and(|extension| extension == "csl")
+                || style.components().count() > 1;
+            if is_path && !style.exists() {
                 return err!(
                     ConfigErr::InvalidFile,
-                    format!("Citation Style Language file not found: {:?}", file)
+                    format!("Citation Style Language file not found: {:?}", style)
                 );
             }
         }
@@ -211,6 +614,10 @@ pub struct Metadata {
     #[serde(skip_serializing_if = "HashSet::is_empty")]
     #[serde(default)]
     pub fonts: HashSet<PathBuf>,
+    /// `true` links font files in the output instead of embedding them.
+    #[arg(long)]
+    #[serde(default)]
+    pub link_fonts: bool,
 }
 
 impl ConfigFns for Metadata {
@@ -218,6 +625,7 @@ impl ConfigFns for Metadata {
         self.title.replace_none(other.title);
         self.authors.extend(other.authors);
         self.fonts.extend(other.fonts);
+        self.link_fonts |= other.link_fonts;
 
         // Note: `base` and `description` must not be merged with sub-configs according to specification.
     }
@@ -274,8 +682,36 @@ impl ConfigFns for HtmlSpecificParameter {
     }
 }
 
-pub fn parse_ignore_file(_s: &str) -> Result<HashSet<String>, clap::Error> {
-    //TODO: Implement once ignore file parser is implemented
+/// Reads the ignore file at the given path.
+///
+/// An ignore file lists one entry per line. Entries are either log-ids (e.g. `RenderError::Unimplemented`,
+/// optionally prefixed with the module path like `unimarkup_render::log_id::RenderError::Unimplemented`),
+/// or element kinds (e.g. `Heading`, `Bold`) to suppress diagnostics for.
+/// Empty lines and lines starting with `#` are skipped.
+///
+/// An empty path results in an empty set, because the ignore file is optional.
+pub fn parse_ignore_file(s: &str) -> Result<HashSet<String>, clap::Error> {
+    if s.trim().is_empty() {
+        return Ok(HashSet::default());
+    }
+
+    let content = std::fs::read_to_string(s).map_err(|err| {
+        clap::Error::raw(
+            clap::error::ErrorKind::Io,
+            format!("Could not read ignore file '{}': {}\n", s, err),
+        )
+    })?;
+
+    Ok(parse_ignore_entries(&content))
+}
 
-    Ok(HashSet::default())
+/// Parses the content of an ignore file into a set of entries.
+/// See [`parse_ignore_file`] for the format.
+pub fn parse_ignore_entries(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
 }
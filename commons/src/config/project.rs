@@ -0,0 +1,273 @@
+//! Contains the project configuration file (`unimarkup.yml`) that is discovered upwards from the input file.
+//!
+//! Precedence of configurations from highest to lowest:
+//!
+//! 1. CLI arguments
+//! 2. The file given with `--config`, or discovered project files starting with the one closest to the input file
+//! 3. Preambles inside Unimarkup documents
+//!
+//! A project file in a sub-directory therefore overrides values set by project files in parent directories.
+//! Discovery stops at a project file setting `root: true`, or at the root directory of a git repository.
+
+use std::path::{Path, PathBuf};
+
+use logid::err;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    log_id::ConfigErr, output::Output, preamble::Preamble, Config, ConfigFns, MergingConfig,
+    ReplaceIfNone,
+};
+
+/// Name of the project configuration file.
+pub const PROJECT_CONFIG_FILE: &str = "unimarkup.yml";
+
+/// Configuration that may be set in a project configuration file.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectConfig {
+    #[serde(flatten)]
+    pub preamble: Preamble,
+    #[serde(default)]
+    pub output: Output,
+    #[serde(default)]
+    pub merging: MergingConfig,
+    /// `true` stops the discovery of project files in parent directories.
+    #[serde(default)]
+    pub root: bool,
+}
+
+impl ProjectConfig {
+    /// Reads the project configuration file at the given path.
+    /// Relative paths inside the file are resolved relative to the directory of the file.
+    pub fn from_file(path: &Path) -> Result<Self, ConfigErr> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) => {
+                return err!(
+                    ConfigErr::InvalidFile,
+                    format!("Could not read project config file {:?}: {}", path, error)
+                )
+            }
+        };
+
+        let mut project_cfg: ProjectConfig = match serde_yaml::from_str(&content) {
+            Ok(cfg) => cfg,
+            Err(error) => {
+                return err!(
+                    ConfigErr::InvalidConfig,
+                    format!("Invalid project config file {:?}: {}", path, error)
+                )
+            }
+        };
+
+        if let Some(base_dir) = path.parent() {
+            project_cfg.resolve_paths(base_dir);
+        }
+
+        Ok(project_cfg)
+    }
+
+    /// Makes all relative paths in this configuration relative to the given base directory.
+    fn resolve_paths(&mut self, base_dir: &Path) {
+        let resolve = |path: &PathBuf| -> PathBuf {
+            if path.is_relative() {
+                base_dir.join(path)
+            } else {
+                path.clone()
+            }
+        };
+
+        let cite = &mut self.preamble.cite;
+        if let Some(style) = &cite.style {
+            // Note: Style may also be the name of a bundled style, so it is only resolved if the file exists.
+            let resolved = resolve(style);
+            if resolved.exists() {
+                cite.style = Some(resolved);
+            }
+        }
+        cite.references = cite.references.iter().map(resolve).collect();
+        cite.citation_locales = cite
+            .citation_locales
+            .iter()
+            .map(|(locale, path)| (locale.clone(), resolve(path)))
+            .collect();
+
+        let metadata = &mut self.preamble.metadata;
+        metadata.fonts = metadata.fonts.iter().map(resolve).collect();
+        metadata.base = metadata.base.as_ref().map(resolve);
+
+        self.output.file = self.output.file.as_ref().map(resolve);
+    }
+}
+
+/// Returns all project configuration files found in the directory of the given input file and its parent directories.
+/// The file closest to the input comes first.
+///
+/// Parent directories are searched up to the first project file setting `root: true`,
+/// or up to the root directory of the git repository containing the input.
+pub fn discover_project_configs(input: &Path) -> Vec<PathBuf> {
+    let input = input.canonicalize().unwrap_or_else(|_| input.to_path_buf());
+    let mut files = Vec::new();
+
+    for dir in input.ancestors().skip(1) {
+        let file = dir.join(PROJECT_CONFIG_FILE);
+        if file.is_file() {
+            let is_root = is_root_config(&file);
+            files.push(file);
+
+            if is_root {
+                break;
+            }
+        }
+
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+
+    files
+}
+
+/// Returns `true` if the given project configuration file sets `root: true`.
+///
+/// **Note:** Invalid files are not reported here, because they are reported once they are merged.
+fn is_root_config(file: &Path) -> bool {
+    std::fs::read_to_string(file)
+        .ok()
+        .and_then(|content| serde_yaml::from_str::<ProjectConfig>(&content).ok())
+        .is_some_and(|cfg| cfg.root)
+}
+
+impl Config {
+    /// Merges project configuration files into this configuration.
+    ///
+    /// If `--config` is set, only the given file is used.
+    /// Otherwise, project files are discovered upwards from the input file (see [`discover_project_configs`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns the path of the first file that could not be read, together with the [`ConfigErr`] cause.
+    pub fn merge_project_configs(&mut self) -> Result<(), (PathBuf, ConfigErr)> {
+        let files = match &self.merging.config_file {
+            Some(file) => vec![file.clone()],
+            None => discover_project_configs(&self.input),
+        };
+
+        for file in files {
+            let project_cfg = ProjectConfig::from_file(&file).map_err(|err| (file, err))?;
+            self.merge_project_config(project_cfg);
+        }
+
+        Ok(())
+    }
+
+    /// Merges the given project configuration into this configuration.
+    /// Values already set in **self** take precedence.
+    pub fn merge_project_config(&mut self, other: ProjectConfig) {
+        let metadata = &mut self.preamble.metadata;
+        // Note: Project configs are part of the main config, so `base` and `description` are also taken.
+        metadata
+            .description
+            .replace_none(other.preamble.metadata.description.clone());
+        metadata
+            .base
+            .replace_none(other.preamble.metadata.base.clone());
+
+        self.preamble.merge(other.preamble);
+        self.output.merge(other.output);
+
+        self.merging.ignore_preamble |= other.merging.ignore_preamble;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use clap::Parser;
+
+    use super::*;
+    use crate::config::{icu_locid, output::OutputFormatKind};
+
+    fn sample_path(path: &str) -> PathBuf {
+        let mut sample = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .canonicalize()
+            .unwrap();
+        sample.push("tests/sample_files");
+        sample.push(path);
+        sample
+    }
+
+    #[test]
+    fn discover_nested_configs() {
+        let files = discover_project_configs(&sample_path("project/chapter/chapter.um"));
+
+        assert_eq!(
+            files,
+            vec![
+                sample_path("project/chapter/unimarkup.yml"),
+                sample_path("project/unimarkup.yml")
+            ],
+            "Discovery did not stop at the root project file."
+        );
+    }
+
+    #[test]
+    fn closer_config_takes_precedence() {
+        let input = sample_path("project/chapter/chapter.um");
+        let mut cfg = Config::parse_from(vec!["unimarkup", input.to_str().unwrap()]);
+
+        cfg.merge_project_configs().unwrap();
+
+        assert_eq!(
+            cfg.preamble.metadata.title.as_deref(),
+            Some("Chapter title")
+        );
+        assert_eq!(
+            cfg.preamble.metadata.description.as_deref(),
+            Some("Project description")
+        );
+        assert_eq!(cfg.preamble.i18n.lang, Some(icu_locid::locale!("de-AT")));
+        assert!(cfg.output.formats.contains(&OutputFormatKind::Pdf));
+        assert!(
+            cfg.preamble
+                .cite
+                .references
+                .contains(&sample_path("project/references.json")),
+            "Reference path was not resolved relative to the config file."
+        );
+        assert!(cfg.validate().is_ok(), "Merged config is invalid.");
+    }
+
+    #[test]
+    fn cli_takes_precedence() {
+        let input = sample_path("project/chapter/chapter.um");
+        let mut cfg = Config::parse_from(vec![
+            "unimarkup",
+            "--title=CLI title",
+            input.to_str().unwrap(),
+        ]);
+
+        cfg.merge_project_configs().unwrap();
+
+        assert_eq!(cfg.preamble.metadata.title.as_deref(), Some("CLI title"));
+    }
+
+    #[test]
+    fn explicit_config_file() {
+        let input = sample_path("project/chapter/chapter.um");
+        let config_file = sample_path("project/unimarkup.yml");
+        let mut cfg = Config::parse_from(vec![
+            "unimarkup",
+            &format!("--config={}", config_file.to_str().unwrap()),
+            input.to_str().unwrap(),
+        ]);
+
+        cfg.merge_project_configs().unwrap();
+
+        assert_eq!(
+            cfg.preamble.metadata.title.as_deref(),
+            Some("Project title")
+        );
+    }
+}
@@ -59,6 +59,8 @@ pub enum SymbolKind {
     Ampersand,
     /// A comma literal (`,`)
     Comma,
+    /// A semicolon literal (`;`) is used for JSON preambles.
+    Semicolon,
     /// The open parentheses (`(`) literal is used for additional data to text group elements (e.g.
     /// image insert).
     OpenParenthesis,
@@ -252,6 +254,7 @@ impl From<&str> for SymbolKind {
             "." => SymbolKind::Dot,
             "&" => SymbolKind::Ampersand,
             "," => SymbolKind::Comma,
+            ";" => SymbolKind::Semicolon,
             symbol
                 if symbol != "\n"
                     && symbol != "\r\n"
@@ -313,6 +316,7 @@ impl SymbolKind {
             SymbolKind::Dot => ".",
             SymbolKind::Ampersand => "&",
             SymbolKind::Comma => ",",
+            SymbolKind::Semicolon => ";",
         }
     }
 }
@@ -24,6 +24,7 @@ pub enum TokenKind {
     Dot(usize),
     Ampersand(usize),
     Comma(usize),
+    Semicolon(usize),
 
     // parenthesis
     OpenParenthesis,
@@ -142,6 +143,7 @@ impl From<TokenKind> for String {
             TokenKind::Dot(len) => SymbolKind::Dot.as_str().repeat(len),
             TokenKind::Ampersand(len) => SymbolKind::Ampersand.as_str().repeat(len),
             TokenKind::Comma(len) => SymbolKind::Comma.as_str().repeat(len),
+            TokenKind::Semicolon(len) => SymbolKind::Semicolon.as_str().repeat(len),
             TokenKind::OpenParenthesis => {
                 let mut s = String::with_capacity(SymbolKind::OpenParenthesis.as_str().len());
                 s.push_str(SymbolKind::OpenParenthesis.as_str());
@@ -233,6 +235,7 @@ impl From<SymbolKind> for TokenKind {
             SymbolKind::Dot => TokenKind::Colon(1),
             SymbolKind::Ampersand => TokenKind::Ampersand(1),
             SymbolKind::Comma => TokenKind::Comma(1),
+            SymbolKind::Semicolon => TokenKind::Semicolon(1),
             SymbolKind::OpenParenthesis => TokenKind::OpenParenthesis,
             SymbolKind::CloseParenthesis => TokenKind::CloseParenthesis,
             SymbolKind::OpenBracket => TokenKind::OpenBracket,
@@ -270,6 +273,7 @@ impl From<(SymbolKind, usize)> for TokenKind {
             SymbolKind::Dot => TokenKind::Dot(len),
             SymbolKind::Ampersand => TokenKind::Ampersand(len),
             SymbolKind::Comma => TokenKind::Comma(len),
+            SymbolKind::Semicolon => TokenKind::Semicolon(len),
             SymbolKind::OpenParenthesis => TokenKind::OpenParenthesis,
             SymbolKind::CloseParenthesis => TokenKind::CloseParenthesis,
             SymbolKind::OpenBracket => TokenKind::OpenBracket,
@@ -7,12 +7,16 @@ pub use unimarkup_render as render;
 use unimarkup_render::pdf::render::render_pdf;
 
 use crate::commons::config::output::OutputFormatKind;
-use crate::commons::config::Config;
+use crate::commons::config::{icu_locid::Locale, Config};
 use crate::parser::document::Document;
 use crate::render::html::render::HtmlRenderer;
-use crate::render::html::Html;
-use crate::render::log_id::RenderError;
-use crate::render::render::{OutputFormat, Renderer};
+use crate::render::html::{Html, HtmlFragment};
+use crate::render::latex::render::LatexRenderer;
+use crate::render::latex::Latex;
+use crate::render::log_id::{RenderError, UmiParserError};
+use crate::render::markdown::render::MarkdownRenderer;
+use crate::render::markdown::Markdown;
+use crate::render::render::{log_document_issues, OutputFormat, Renderer};
 use crate::render::umi::render::UmiRenderer;
 use crate::render::umi::Umi;
 
@@ -23,18 +27,52 @@ pub struct Unimarkup {
 impl Unimarkup {
     /// Parses Unimarkup content, and returns a [`Unimarkup`] struct to render the content to supported formats.
     ///
+    /// Unknown citation ids, unused references and dead internal references are logged once after parsing.
+    ///
     /// # Arguments
     ///
     /// * `um_content` - String containing Unimarkup elements.
     /// * `config` - Unimarkup configuration to be used on top of preambles.
     pub fn parse(um_content: &str, mut config: Config) -> Self {
+        let doc = match config.input.extension().and_then(OsStr::to_str) {
+            Some("umi") => Umi::create_um(um_content, &mut config).unwrap(),
+            _ => parser::parse_unimarkup(um_content, config),
+        };
+        log_document_issues(&doc);
+
+        Unimarkup { doc }
+    }
+
+    /// Parses Unimarkup content for the given output locale, and returns a [`Unimarkup`] struct to render the content to supported formats.
+    ///
+    /// Localised content columns are used for `.umi` files,
+    /// and blocks with a `lang` attribute not matching the locale are skipped for Unimarkup files.
+    /// Issues of the content are not logged, because they are already logged by [`Unimarkup::parse`].
+    ///
+    /// Returns an error if the content of a `.umi` file cannot be parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `um_content` - String containing Unimarkup elements.
+    /// * `config` - Unimarkup configuration to be used on top of preambles.
+    /// * `lang` - Locale of the output.
+    pub fn parse_for_lang(
+        um_content: &str,
+        mut config: Config,
+        lang: Locale,
+    ) -> Result<Self, UmiParserError> {
         match config.input.extension().and_then(OsStr::to_str) {
-            Some("umi") => Unimarkup {
-                doc: Umi::create_um(um_content, &mut config).unwrap(),
-            },
-            _ => Unimarkup {
-                doc: parser::parse_unimarkup(um_content, config),
-            },
+            Some("umi") => {
+                config.preamble.i18n.lang = Some(lang.clone());
+                let mut doc = Umi::create_um(um_content, &mut config)?;
+                doc.blocks.retain(|block| block.matches_lang(&lang));
+                doc.config.preamble.i18n.lang = Some(lang);
+
+                Ok(Unimarkup { doc })
+            }
+            _ => Ok(Unimarkup {
+                doc: parser::parse_unimarkup_for_lang(um_content, config, lang),
+            }),
         }
     }
 
@@ -46,6 +84,22 @@ impl Unimarkup {
         self.doc.output_formats()
     }
 
+    /// Returns the configured output locales sorted by their string representation.
+    /// If no output locales are set, only one output for the main language must be created.
+    pub fn get_output_langs(&self) -> Vec<Locale> {
+        let mut langs: Vec<Locale> = self
+            .doc
+            .config
+            .preamble
+            .i18n
+            .output_langs
+            .iter()
+            .cloned()
+            .collect();
+        langs.sort_by_key(|lang| lang.to_string());
+        langs
+    }
+
     pub fn render<T: OutputFormat>(
         &self,
         format: OutputFormatKind,
@@ -58,10 +112,27 @@ impl Unimarkup {
         self.render(OutputFormatKind::Html, HtmlRenderer::new(use_paged_js))
     }
 
+    /// Renders the document to HTML without the `<html>`, `<head>` and `<body>` wrapper.
+    /// Styles and scripts the content needs are returned as [`HtmlAssets`](crate::render::html::HtmlAssets) of the fragment.
+    pub fn render_html_fragment(&self) -> Result<HtmlFragment, RenderError> {
+        Ok(self.render_html(false)?.fragment())
+    }
+
     pub fn render_umi(&self) -> Result<Umi, RenderError> {
         self.render(OutputFormatKind::Umi, UmiRenderer::default())
     }
 
+    /// Renders the document to Markdown following CommonMark with GitHub Flavored Markdown extensions.
+    pub fn render_markdown(&self) -> Result<Markdown, RenderError> {
+        self.render(OutputFormatKind::Markdown, MarkdownRenderer::default())
+    }
+
+    /// Renders the document to a standalone LaTeX document.
+    /// The BibTeX database of cited references is embedded in the document and available as [`Latex::bibliography`].
+    pub fn render_latex(&self) -> Result<Latex, RenderError> {
+        self.render(OutputFormatKind::Latex, LatexRenderer::default())
+    }
+
     pub fn render_pdf(&self) -> Result<Vec<u8>, RenderError> {
         render_pdf(&self.render_html(true)?.to_string())
     }
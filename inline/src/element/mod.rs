@@ -13,7 +13,7 @@ use self::{
         Underline, Verbatim,
     },
     substitution::{named::NamedSubstitution, DirectUri, ImplicitSubstitution},
-    textbox::{citation::Citation, hyperlink::Hyperlink, TextBox},
+    textbox::{citation::Citation, hyperlink::Hyperlink, reference::InternalReference, TextBox},
 };
 
 mod helper;
@@ -93,9 +93,13 @@ pub enum Inline {
     /// Hyperlink element `[link text](url)`.
     Hyperlink(Hyperlink),
 
-    /// Citation element `[&&cite-id]`
+    /// Citation element `[&&cite-id]`, optionally with locator, prefix and suffix,
+    /// e.g. `[&&"see" smith2020, p. 12]`.
     Citation(Citation),
 
+    /// Internal reference `[#target-id]` to a labelled element of the document, e.g. a heading.
+    InternalReference(InternalReference),
+
     /// Named substitution ( i.e. `::heart::`).
     NamedSubstitution(NamedSubstitution),
 
@@ -136,6 +140,24 @@ impl Inline {
         matches!(self, Inline::Plain(_))
     }
 
+    /// Returns the nested inlines of this inline, if it may contain other inlines.
+    pub fn inner(&self) -> Option<&Vec<Inline>> {
+        match self {
+            Inline::Bold(inline) => Some(inline.inner()),
+            Inline::Italic(inline) => Some(inline.inner()),
+            Inline::Underline(inline) => Some(inline.inner()),
+            Inline::Subscript(inline) => Some(inline.inner()),
+            Inline::Superscript(inline) => Some(inline.inner()),
+            Inline::Overline(inline) => Some(inline.inner()),
+            Inline::Strikethrough(inline) => Some(inline.inner()),
+            Inline::Highlight(inline) => Some(inline.inner()),
+            Inline::Quote(inline) => Some(inline.inner()),
+            Inline::TextBox(inline) => Some(inline.inner()),
+            Inline::Hyperlink(inline) => Some(inline.inner()),
+            _ => None,
+        }
+    }
+
     pub fn variant_str(&self) -> &'static str {
         match self {
             Inline::Bold(_) => "Bold",
@@ -151,6 +173,7 @@ impl Inline {
             Inline::TextBox(_) => "TextBox",
             Inline::Hyperlink(_) => "Hyperlink",
             Inline::Citation(_) => "Citation",
+            Inline::InternalReference(_) => "InternalReference",
             Inline::Verbatim(_) => "Verbatim",
             Inline::Newline(_) => "Newline",
             Inline::ImplicitNewline(_) => "ImplicitNewline",
@@ -182,6 +205,7 @@ impl InlineElement for Inline {
             Inline::TextBox(inline) => inline.as_unimarkup(),
             Inline::Hyperlink(inline) => inline.as_unimarkup(),
             Inline::Citation(inline) => inline.as_unimarkup(),
+            Inline::InternalReference(inline) => inline.as_unimarkup(),
             Inline::Verbatim(inline) => inline.as_unimarkup(),
             Inline::Newline(inline) => inline.as_unimarkup(),
             Inline::ImplicitNewline(inline) => inline.as_unimarkup(),
@@ -212,6 +236,7 @@ impl InlineElement for Inline {
             Inline::TextBox(inline) => inline.start(),
             Inline::Hyperlink(inline) => inline.start(),
             Inline::Citation(inline) => inline.start(),
+            Inline::InternalReference(inline) => inline.start(),
             Inline::Verbatim(inline) => inline.start(),
             Inline::Newline(inline) => inline.start(),
             Inline::ImplicitNewline(inline) => inline.start(),
@@ -242,6 +267,7 @@ impl InlineElement for Inline {
             Inline::TextBox(inline) => inline.end(),
             Inline::Hyperlink(inline) => inline.end(),
             Inline::Citation(inline) => inline.end(),
+            Inline::InternalReference(inline) => inline.end(),
             Inline::Verbatim(inline) => inline.end(),
             Inline::Newline(inline) => inline.end(),
             Inline::ImplicitNewline(inline) => inline.end(),
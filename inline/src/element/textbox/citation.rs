@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{ops::Range, rc::Rc};
 
 use unimarkup_commons::lexer::{
     position::Position,
@@ -64,32 +64,24 @@ impl Citation {
             cite_token.kind
         );
 
-        let mut entries = Vec::new();
-        while scoped_parser.iter.peek().is_some() {
-            let (updated_parser, entry_opt) = CitationEntry::parse(scoped_parser);
-            scoped_parser = updated_parser;
+        let tokens = scoped_parser.iter.take_to_end();
+        let entries = match CitationEntry::parse_all(&tokens) {
+            Some(entries) if !entries.is_empty() && scoped_parser.iter.end_reached() => entries,
+            // One wrong citation entry invalidates the citation
+            _ => return (scoped_parser.unfold_scoped(outer_open_formats), None),
+        };
 
-            match entry_opt {
-                Some(entry) => entries.push(entry),
-                // One wrong citation entry invalidates the citation
-                None => return (scoped_parser.unfold_scoped(outer_open_formats), None),
-            }
-        }
-
-        if entries.is_empty() {
-            return (scoped_parser.unfold_scoped(outer_open_formats), None);
-        }
-
-        let end = entries.last().expect("At least one entry exists.").end;
-        let cited_ids = entries.iter().map(|entry| entry.id.clone()).collect_vec();
-        scoped_parser.context.citations.push(cited_ids);
+        let close_bracket = scoped_parser
+            .iter
+            .prev_token()
+            .expect("Closing bracket was consumed => previous token must exist.");
+        scoped_parser.context.citations.push(entries.clone());
 
         parser = scoped_parser.unfold_scoped(outer_open_formats);
-        parser.iter.next(); // Consume closing bracket
 
         (
             parser,
-            Some(Citation::new(entries, open_bracket.start, end).into()),
+            Some(Citation::new(entries, open_bracket.start, close_bracket.end).into()),
         )
     }
 }
@@ -107,7 +99,7 @@ impl InlineElement for Citation {
             self.entries
                 .iter()
                 .map(|entry| entry.as_unimarkup())
-                .join(", ")
+                .join("; ")
         )
     }
 
@@ -120,11 +112,62 @@ impl InlineElement for Citation {
     }
 }
 
+/// Locator abbreviations and the CSL locator types they stand for.
+const LOCATOR_LABELS: &[(&str, &str)] = &[
+    ("p.", "page"),
+    ("pp.", "page"),
+    ("page", "page"),
+    ("pages", "page"),
+    ("chap.", "chapter"),
+    ("ch.", "chapter"),
+    ("chapter", "chapter"),
+    ("sec.", "section"),
+    ("section", "section"),
+    ("§", "section"),
+    ("§§", "section"),
+    ("para.", "paragraph"),
+    ("¶", "paragraph"),
+    ("vol.", "volume"),
+    ("vols.", "volume"),
+    ("fig.", "figure"),
+    ("figs.", "figure"),
+    ("l.", "line"),
+    ("ll.", "line"),
+    ("n.", "note"),
+    ("nn.", "note"),
+    ("col.", "column"),
+    ("cols.", "column"),
+    ("no.", "number"),
+    ("pt.", "part"),
+    ("v.", "verse"),
+    ("vv.", "verse"),
+    ("bk.", "book"),
+    ("fol.", "folio"),
+    ("op.", "opus"),
+    ("s.v.", "sub verbo"),
+];
+
 /// Represents a citation entry.
+///
+/// An entry is written as `["prefix" ][-|!]id[, locator][ "suffix"]`, where `-` suppresses the
+/// author and `!` only shows the author. Entries are separated by `;` or `,`.
+///
+/// **Note:** A number after a comma is taken as page locator, so ids consisting only of digits
+/// must be separated by `;`, e.g. `[&&smith; 2020]`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CitationEntry {
     /// The citation ID.
     id: String,
+    /// Optional locator pointing into the cited work, e.g. `p. 12`.
+    locator: Option<Locator>,
+    /// Optional text shown before the cite.
+    prefix: Option<String>,
+    /// Optional text shown after the cite.
+    suffix: Option<String>,
+    /// `true` if the author is omitted from the cite, e.g. `[&&-smith2020]`.
+    suppress_author: bool,
+    /// `true` if only the author is shown, e.g. `[&&!smith2020]`.
+    author_only: bool,
     /// Optional attributes of the ciration entry.
     attributes: Vec<Inline>,
     /// The start of this entry in the original content.
@@ -133,68 +176,265 @@ pub struct CitationEntry {
     end: Position,
 }
 
+/// Locator of a citation entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locator {
+    /// The locator as written in the citation, e.g. `p. 12`.
+    text: String,
+    /// The CSL locator type, e.g. `page`.
+    label: String,
+    /// The locator without its label, e.g. `12`.
+    value: String,
+}
+
+impl Locator {
+    /// Parses a locator given as label followed by a value, or as bare page numbers, e.g. `12-14`.
+    ///
+    /// Values with other characters than digits, ranges and lists are no bare page numbers,
+    /// because they may be the id of the next entry, e.g. `2020smith`.
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (written_label, value) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let label = LOCATOR_LABELS
+            .iter()
+            .find(|(abbreviation, _)| abbreviation.eq_ignore_ascii_case(written_label))
+            .map(|(_, label)| label);
+
+        match label {
+            Some(label) if !value.trim().is_empty() => Some(Locator {
+                text: text.to_string(),
+                label: label.to_string(),
+                value: value.trim().to_string(),
+            }),
+            None if is_page_number(text) => Some(Locator {
+                text: text.to_string(),
+                label: "page".to_string(),
+                value: text.to_string(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the CSL locator type, e.g. `page` or `chapter`.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the locator without its label.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 impl CitationEntry {
+    /// Creates an entry citing the given ID without locator, prefix or suffix.
+    pub fn new(id: impl Into<String>) -> Self {
+        CitationEntry {
+            id: id.into(),
+            locator: None,
+            prefix: None,
+            suffix: None,
+            suppress_author: false,
+            author_only: false,
+            attributes: Vec::new(),
+            start: Position::default(),
+            end: Position::default(),
+        }
+    }
+
+    /// Returns the ID of the cited reference.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the [`Locator`] of this entry.
+    pub fn locator(&self) -> Option<&Locator> {
+        self.locator.as_ref()
+    }
+
+    /// Returns the text shown before the cite.
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix.as_deref()
+    }
+
+    /// Returns the text shown after the cite.
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_deref()
+    }
+
+    /// Returns `true` if the author is omitted from the cite.
+    pub fn suppress_author(&self) -> bool {
+        self.suppress_author
+    }
+
+    /// Returns `true` if only the author is shown.
+    pub fn author_only(&self) -> bool {
+        self.author_only
+    }
+
+    /// Returns the start of this entry in the original content.
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    /// Returns the end of this entry in the original content.
+    pub fn end(&self) -> Position {
+        self.end
+    }
+
     pub fn as_unimarkup(&self) -> String {
-        self.id.clone() // TODO: add attributes once implemented
+        let mut entry = String::new();
+        if let Some(prefix) = &self.prefix {
+            entry.push_str(&format!("\"{}\" ", prefix));
+        }
+        if self.suppress_author {
+            entry.push('-');
+        } else if self.author_only {
+            entry.push('!');
+        }
+        entry.push_str(&self.id);
+        if let Some(locator) = &self.locator {
+            entry.push_str(&format!(", {}", locator.text));
+        }
+        if let Some(suffix) = &self.suffix {
+            entry.push_str(&format!(" \"{}\"", suffix));
+        }
+        entry // TODO: add attributes once implemented
     }
 
-    pub(crate) fn parse<'slice, 'input>(
-        parser: InlineParser<'slice, 'input>,
-    ) -> (InlineParser<'slice, 'input>, Option<CitationEntry>) {
-        let mut id_parser = parser.nest(Some(Rc::new(|matcher: &mut dyn EndMatcher| {
-            matcher.consumed_matches(&[
-                InlineTokenKind::Comma.into(),
-                InlineTokenKind::Whitespace.into(),
-            ]) || matcher.consumed_matches(&[
-                InlineTokenKind::Comma.into(),
-                InlineTokenKind::Newline.into(),
-            ]) || matcher.matches(&[InlineTokenKind::Newline.into()])
-                || matcher.matches(&[InlineTokenKind::EscapedNewline.into()])
-                || matcher.matches(&[InlineTokenKind::Whitespace.into()])
-                || matcher.matches(&[InlineTokenKind::EscapedWhitespace.into()])
-                || matcher.matches(&[InlineTokenKind::Dot.into()])
-                || matcher.matches(&[InlineTokenKind::Cite.into()])
-                || matcher.outer_end() // TODO: match for attributes
-        })));
-
-        let id_parts = id_parser.iter.take_to_end();
-        match InlineToken::flatten(&id_parts) {
-            Some(id) => {
-                // Invalid entry ID
-                if id.is_empty()
-                    || matches!(
-                        id_parser.iter.peek_kind(),
-                        Some(InlineTokenKind::Newline)
-                            | Some(InlineTokenKind::EscapedNewline)
-                            | Some(InlineTokenKind::Whitespace)
-                            | Some(InlineTokenKind::EscapedWhitespace)
-                            | Some(InlineTokenKind::Dot)
-                            | Some(InlineTokenKind::Cite)
-                    )
-                {
-                    return (id_parser.into_inner(), None);
-                }
-
-                let start = id_parts
-                    .first()
-                    .expect("At least one token exists, because ID is not empty.")
-                    .start;
-                let end = id_parts
-                    .last()
-                    .expect("At least one token exists, because ID is not empty.")
-                    .end;
-
-                (
-                    id_parser.into_inner(),
-                    Some(CitationEntry {
-                        id: id.to_string(),
-                        attributes: Vec::new(),
-                        start,
-                        end,
-                    }),
-                )
+    /// Parses all entries of a citation from the tokens between `&&` and the closing bracket.
+    ///
+    /// Returns `None` if one of the entries is invalid.
+    pub(crate) fn parse_all(tokens: &[InlineToken<'_>]) -> Option<Vec<CitationEntry>> {
+        let content = InlineToken::flatten(tokens)?;
+        let base_offset = tokens.first()?.offset.start;
+
+        let entries = parse_entries(content)?;
+        Some(
+            entries
+                .into_iter()
+                .map(|(mut entry, range)| {
+                    entry.start = position_at(tokens, base_offset + range.start);
+                    entry.end = position_at(tokens, base_offset + range.end);
+                    entry
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Parses the entries of a citation, returning each entry with its byte range in the content.
+fn parse_entries(content: &str) -> Option<Vec<(CitationEntry, Range<usize>)>> {
+    let mut entries = Vec::new();
+    let mut rest = content;
+    let offset = |rest: &str| content.len() - rest.len();
+
+    loop {
+        rest = rest.trim_start();
+        let start = offset(rest);
+
+        let mut prefix = None;
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let (text, after) = quoted.split_once('"')?;
+            prefix = Some(text.to_string());
+            rest = after.trim_start();
+        }
+
+        let mut entry = CitationEntry::new(String::new());
+        entry.prefix = prefix;
+        if let Some(after) = rest.strip_prefix('-') {
+            entry.suppress_author = true;
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('!') {
+            entry.author_only = true;
+            rest = after;
+        }
+
+        let id_end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '"'))
+            .unwrap_or(rest.len());
+        if id_end == 0 {
+            return None;
+        }
+        entry.id = rest[..id_end].to_string();
+        rest = &rest[id_end..];
+        let mut end = offset(rest);
+
+        // A comma either starts the locator or separates this entry from the next one.
+        if let Some(after) = rest.trim_start().strip_prefix(',') {
+            let locator_text = locator_text(after);
+            if let Some(locator) = Locator::parse(locator_text) {
+                entry.locator = Some(locator);
+                rest = &after[locator_text.len()..];
+                end = offset(rest);
             }
-            None => return (id_parser.into_inner(), None),
         }
+
+        if let Some(quoted) = rest.trim_start().strip_prefix('"') {
+            let (text, after) = quoted.split_once('"')?;
+            entry.suffix = Some(text.to_string());
+            rest = after;
+            end = offset(rest);
+        }
+
+        entries.push((entry, start..end));
+
+        let rest_trimmed = rest.trim_start();
+        if rest_trimmed.is_empty() {
+            return Some(entries);
+        }
+        rest = rest_trimmed
+            .strip_prefix(';')
+            .or_else(|| rest_trimmed.strip_prefix(','))?;
+        if rest.trim().is_empty() {
+            return None;
+        }
+    }
+}
+
+/// Returns `true` if the given text consists of page numbers, ranges and lists, e.g. `12-14, 16`.
+fn is_page_number(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_digit())
+        && text
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_whitespace() || matches!(c, '-' | '–' | ','))
+}
+
+/// Text that may form a locator: everything up to a `;`, a quoted suffix, or a comma that is not
+/// followed by another number, e.g. `pp. 12, 14`.
+fn locator_text(text: &str) -> &str {
+    let mut end = text.len();
+    for (index, c) in text.char_indices() {
+        match c {
+            ';' | '"' => {
+                end = index;
+                break;
+            }
+            ',' if !text[index + 1..]
+                .trim_start()
+                .starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                end = index;
+                break;
+            }
+            _ => {}
+        }
+    }
+    let locator = &text[..end];
+    &locator[..locator.trim_end().len()]
+}
+
+/// Position of the given byte offset, based on the token that contains the offset.
+fn position_at(tokens: &[InlineToken<'_>], offset: usize) -> Position {
+    let token = tokens
+        .iter()
+        .find(|token| token.offset.start <= offset && offset <= token.offset.end)
+        .expect("Offset lies within the citation tokens.");
+    let text = &token.input[token.offset.start..offset];
+    Position {
+        line: token.start.line,
+        col_utf8: token.start.col_utf8 + text.len(),
+        col_utf16: token.start.col_utf16 + text.encode_utf16().count(),
+        col_grapheme: token.start.col_grapheme + text.chars().count(),
     }
 }
@@ -9,12 +9,13 @@ use unimarkup_commons::lexer::{
 
 use crate::{parser::InlineParser, tokenize::kind::InlineTokenKind};
 
-use self::{citation::Citation, hyperlink::Hyperlink};
+use self::{citation::Citation, hyperlink::Hyperlink, reference::InternalReference};
 
 use super::{Inline, InlineElement};
 
 pub mod citation;
 pub mod hyperlink;
+pub mod reference;
 
 /// Represents the text box element.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -177,7 +178,7 @@ pub(crate) fn parse<'slice, 'input>(
     )
 }
 
-/// Tries to parse text box variants like literature referencing.
+/// Tries to parse text box variants like literature and internal referencing.
 fn parse_box_variant<'slice, 'input>(
     mut parser: InlineParser<'slice, 'input>,
 ) -> (InlineParser<'slice, 'input>, Option<Inline>) {
@@ -185,6 +186,13 @@ fn parse_box_variant<'slice, 'input>(
     if kind == Some(InlineTokenKind::Cite) {
         return Citation::parse(parser);
     }
+    if parser
+        .iter
+        .peek()
+        .is_some_and(|token| token.kind == InlineTokenKind::Plain && token.as_str() == "#")
+    {
+        return InternalReference::parse(parser);
+    }
 
     (parser, None)
 }
@@ -0,0 +1,95 @@
+use std::rc::Rc;
+
+use unimarkup_commons::lexer::{position::Position, token::iterator::EndMatcher};
+
+use crate::{
+    element::{Inline, InlineElement},
+    parser::InlineParser,
+    tokenize::InlineToken,
+    InlineTokenKind,
+};
+
+/// Represents an internal reference `[#target-id]` to a labelled element of the document,
+/// e.g. a heading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternalReference {
+    /// The id of the referenced element.
+    target: String,
+    /// The start of this reference in the original content.
+    start: Position,
+    /// The end of this reference in the original content.
+    end: Position,
+}
+
+impl InternalReference {
+    pub fn new(target: String, start: Position, end: Position) -> Self {
+        Self { target, start, end }
+    }
+
+    /// Returns the id of the referenced element.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    pub(crate) fn parse<'slice, 'input>(
+        mut parser: InlineParser<'slice, 'input>,
+    ) -> (InlineParser<'slice, 'input>, Option<Inline>) {
+        let open_bracket = parser
+            .iter
+            .prev_token()
+            .expect("OpenBracket was consumed before parsing InternalReference.");
+
+        let (mut scoped_parser, outer_open_formats) =
+            parser.nest_scoped(Some(Rc::new(|matcher: &mut dyn EndMatcher| {
+                matcher.consumed_matches(&[InlineTokenKind::CloseBracket.into()])
+            })));
+
+        let hash_token_opt = scoped_parser.iter.next();
+        if hash_token_opt.is_none() {
+            return (scoped_parser.unfold_scoped(outer_open_formats), None);
+        }
+
+        let tokens = scoped_parser.iter.take_to_end();
+        let target = InlineToken::flatten(&tokens)
+            .unwrap_or_default()
+            .to_string();
+        // Ids never contain whitespace, and the reference must be closed.
+        if target.is_empty()
+            || target.chars().any(char::is_whitespace)
+            || !scoped_parser.iter.end_reached()
+        {
+            return (scoped_parser.unfold_scoped(outer_open_formats), None);
+        }
+
+        let close_bracket = scoped_parser
+            .iter
+            .prev_token()
+            .expect("Closing bracket was consumed => previous token must exist.");
+        parser = scoped_parser.unfold_scoped(outer_open_formats);
+
+        (
+            parser,
+            Some(InternalReference::new(target, open_bracket.start, close_bracket.end).into()),
+        )
+    }
+}
+
+impl From<InternalReference> for Inline {
+    fn from(value: InternalReference) -> Self {
+        Inline::InternalReference(value)
+    }
+}
+
+impl InlineElement for InternalReference {
+    fn as_unimarkup(&self) -> String {
+        format!("[#{}]", self.target)
+    }
+
+    fn start(&self) -> Position {
+        self.start
+    }
+
+    fn end(&self) -> Position {
+        self.end
+    }
+}
@@ -3,7 +3,7 @@
 use unimarkup_commons::lexer::token::iterator::{IteratorEndFn, IteratorPrefixFn, TokenIterator};
 
 use crate::{
-    element::{formatting::OpenFormatMap, Inline},
+    element::{formatting::OpenFormatMap, textbox::citation::CitationEntry, Inline},
     tokenize::{iterator::InlineTokenIterator, kind::InlineTokenKind},
 };
 
@@ -71,7 +71,7 @@ impl ParsedInlines {
 #[derive(Debug, Default, Clone)]
 pub struct InlineContext {
     pub flags: InlineContextFlags,
-    pub citations: Vec<Vec<String>>,
+    pub citations: Vec<Vec<CitationEntry>>,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
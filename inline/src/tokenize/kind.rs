@@ -401,6 +401,7 @@ impl From<TokenKind> for InlineTokenKind {
             | TokenKind::Hash(_)
             | TokenKind::Minus(_)
             | TokenKind::Plus(_)
+            | TokenKind::Semicolon(_)
             | TokenKind::EnclosedBlockEnd
             | TokenKind::PossibleDecorator
             | TokenKind::TerminalPunctuation => InlineTokenKind::Plain,
@@ -117,6 +117,7 @@ fn inner_snapshot(inline: &Inline) -> String {
                     s
                 })
         }
+        Inline::InternalReference(inline) => inline.target().to_string(),
         Inline::Verbatim(inline) => Snapshot::snap(inline),
         Inline::Newline(inline) => Snapshot::snap(inline.as_str()),
         Inline::ImplicitNewline(inline) => Snapshot::snap(inline.as_str()),
@@ -1,7 +1,22 @@
 //! Contains the Unimarkup Document structure used to store all information of a Unimarkup document in one structure.
 
-use crate::{elements::Blocks, metadata::Metadata};
-use unimarkup_commons::config::{output::OutputFormatKind, Config};
+use crate::{
+    elements::{
+        atomic::{Bibliography, Heading, TocOptions},
+        blocks::Block,
+        Blocks,
+    },
+    metadata::Metadata,
+    numbering::Counters,
+};
+use unimarkup_commons::{
+    config::{output::OutputFormatKind, Config},
+    lexer::position::Position,
+};
+use unimarkup_inline::element::{
+    textbox::{citation::CitationEntry, reference::InternalReference},
+    Inline,
+};
 
 /// Struct representing a Unimarkup document
 #[derive(Default, Debug)]
@@ -12,8 +27,8 @@ pub struct Document {
     pub config: Config,
     /// Citations used in the Unimarkup content.
     /// The citations are added in document flow.
-    /// Every citation may contain one or more citation entry IDs.
-    pub citations: Vec<Vec<String>>,
+    /// Every citation may contain one or more citation entries.
+    pub citations: Vec<Vec<CitationEntry>>,
 
     // Below fields not yet used!
     /// Field containing all macros defined in this Unimarkup document
@@ -26,9 +41,245 @@ pub struct Document {
     pub resources: Vec<String>,
 }
 
+/// Kinds of elements that may be referenced with an [`InternalReference`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetKind {
+    /// A heading.
+    Section,
+}
+
+/// Element that may be referenced with an [`InternalReference`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceTarget {
+    /// The id of the element.
+    pub id: String,
+    /// The kind of the element.
+    pub kind: TargetKind,
+    /// The number of the element, e.g. `2.1` for the first subsection of the second section.
+    /// Headings deeper than the numbering depth have no number.
+    pub number: Option<String>,
+    /// The start of the element in the original content.
+    pub start: Position,
+}
+
+/// Entry of the outline listed in a table of contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineEntry<'a> {
+    /// The heading of this entry.
+    pub heading: &'a Heading,
+    /// Entries of the subheadings of this entry.
+    pub children: Vec<OutlineEntry<'a>>,
+}
+
 impl Document {
     /// Returns the configured output formats for this Unimarkup document
     pub fn output_formats(&self) -> impl Iterator<Item = &OutputFormatKind> {
         self.config.output.formats.iter()
     }
+
+    /// Returns `true` if the bibliography is placed with at least one [`Bibliography`] element.
+    /// Otherwise, the bibliography is rendered at the end of the document.
+    pub fn places_bibliography(&self) -> bool {
+        fn contains_bibliography(blocks: &[Block]) -> bool {
+            blocks.iter().any(|block| match block {
+                Block::Bibliography(_) => true,
+                Block::BulletList(list) => list
+                    .entries
+                    .iter()
+                    .any(|entry| contains_bibliography(&entry.body)),
+                Block::BulletListEntry(entry) => contains_bibliography(&entry.body),
+                _ => false,
+            })
+        }
+
+        contains_bibliography(&self.blocks)
+    }
+
+    /// Returns the citation entries cited in the part of the document the given [`Bibliography`] covers.
+    /// The entries are not filtered by the item types set for the bibliography.
+    pub fn bibliography_entries<'a>(
+        &'a self,
+        bibliography: &'a Bibliography,
+    ) -> impl Iterator<Item = &'a CitationEntry> {
+        let headings: Vec<&Heading> = self
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Heading(heading) => Some(heading),
+                _ => None,
+            })
+            .collect();
+
+        let mut from: Option<Position> = None;
+        let mut to: Option<Position> = None;
+
+        if bibliography.options.section {
+            let section = headings
+                .iter()
+                .rposition(|heading| heading.start < bibliography.start);
+            from = section.map(|index| headings[index].start);
+            to = headings
+                .iter()
+                .enumerate()
+                .skip(section.map_or(0, |index| index + 1))
+                .find(|(_, heading)| {
+                    section.is_none_or(|index| heading.level as u8 <= headings[index].level as u8)
+                })
+                .map(|(_, heading)| heading.start);
+        }
+
+        if let Some(after) = &bibliography.options.after {
+            // Entries after an unknown heading are not listed.
+            let after_end = headings
+                .iter()
+                .find(|heading| &heading.id == after)
+                .map_or(Position::new(usize::MAX, usize::MAX), |heading| heading.end);
+            from = Some(from.map_or(after_end, |from| from.max(after_end)));
+        }
+
+        self.citations.iter().flatten().filter(move |entry| {
+            from.is_none_or(|from| entry.start() >= from) && to.is_none_or(|to| entry.start() < to)
+        })
+    }
+
+    /// Returns all elements of this document that may be referenced with an [`InternalReference`].
+    /// The elements are returned in document flow, and are numbered as set in the preamble.
+    ///
+    /// **Note:** Only headings are available as targets for now,
+    /// because tables, figures and equations are not yet available as blocks.
+    pub fn reference_targets(&self) -> Vec<ReferenceTarget> {
+        fn collect(blocks: &[Block], counters: &mut Counters, targets: &mut Vec<ReferenceTarget>) {
+            for block in blocks {
+                match block {
+                    Block::Heading(heading) => targets.push(ReferenceTarget {
+                        id: heading.id.clone(),
+                        kind: TargetKind::Section,
+                        number: counters.heading(heading.level.into()),
+                        start: heading.start,
+                    }),
+                    Block::BulletList(list) => list
+                        .entries
+                        .iter()
+                        .for_each(|entry| collect(&entry.body, counters, targets)),
+                    Block::BulletListEntry(entry) => collect(&entry.body, counters, targets),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut targets = Vec::new();
+        let mut counters = Counters::new(&self.config.preamble.numbering);
+        collect(&self.blocks, &mut counters, &mut targets);
+        targets
+    }
+
+    /// Returns all [`InternalReference`]s used in the content of this document in document flow.
+    pub fn internal_references(&self) -> Vec<&InternalReference> {
+        fn from_inlines<'a>(inlines: &'a [Inline], references: &mut Vec<&'a InternalReference>) {
+            for inline in inlines {
+                match inline {
+                    Inline::InternalReference(reference) => references.push(reference),
+                    inline => {
+                        if let Some(inner) = inline.inner() {
+                            from_inlines(inner, references);
+                        }
+                    }
+                }
+            }
+        }
+
+        fn from_blocks<'a>(blocks: &'a [Block], references: &mut Vec<&'a InternalReference>) {
+            for block in blocks {
+                match block {
+                    Block::Heading(heading) => from_inlines(&heading.content, references),
+                    Block::Paragraph(paragraph) => from_inlines(&paragraph.content, references),
+                    Block::BulletList(list) => list.entries.iter().for_each(|entry| {
+                        from_inlines(&entry.heading, references);
+                        from_blocks(&entry.body, references);
+                    }),
+                    Block::BulletListEntry(entry) => {
+                        from_inlines(&entry.heading, references);
+                        from_blocks(&entry.body, references);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut references = Vec::new();
+        from_blocks(&self.blocks, &mut references);
+        references
+    }
+
+    /// Returns `true` if a table of contents is placed with at least one [`TableOfContents`] element.
+    ///
+    /// [`TableOfContents`]: crate::elements::atomic::TableOfContents
+    pub fn places_toc(&self) -> bool {
+        fn contains_toc(blocks: &[Block]) -> bool {
+            blocks.iter().any(|block| match block {
+                Block::TableOfContents(_) => true,
+                Block::BulletList(list) => {
+                    list.entries.iter().any(|entry| contains_toc(&entry.body))
+                }
+                Block::BulletListEntry(entry) => contains_toc(&entry.body),
+                _ => false,
+            })
+        }
+
+        contains_toc(&self.blocks)
+    }
+
+    /// Returns the nested outline of the top-level headings of this document for the given [`TocOptions`].
+    ///
+    /// Headings deeper than the depth, headings with an excluded id,
+    /// and headings excluded with the attribute `"toc": false` are not listed together with their subheadings.
+    /// Subheadings that skip a level are listed as children of the closest heading above.
+    pub fn outline(&self, options: &TocOptions) -> Vec<OutlineEntry<'_>> {
+        let depth = options
+            .depth
+            .or(self.config.preamble.toc.toc_depth)
+            .unwrap_or(6);
+
+        let mut outline: Vec<OutlineEntry> = Vec::new();
+        // Levels of the entries on the path to the last added entry
+        let mut path: Vec<u8> = Vec::new();
+        let mut excluded_level: Option<u8> = None;
+
+        for heading in self.blocks.iter().filter_map(|block| match block {
+            Block::Heading(heading) => Some(heading),
+            _ => None,
+        }) {
+            let level = u8::from(heading.level);
+            match excluded_level {
+                Some(excluded) if level > excluded => continue,
+                _ => excluded_level = None,
+            }
+            if level > depth {
+                continue;
+            }
+            if !heading.in_toc() || options.exclude.contains(&heading.id) {
+                excluded_level = Some(level);
+                continue;
+            }
+
+            while path.last().is_some_and(|last| *last >= level) {
+                path.pop();
+            }
+
+            let mut siblings = &mut outline;
+            for _ in 0..path.len() {
+                siblings = &mut siblings
+                    .last_mut()
+                    .expect("Path only contains levels of added entries.")
+                    .children;
+            }
+            siblings.push(OutlineEntry {
+                heading,
+                children: Vec::new(),
+            });
+            path.push(level);
+        }
+
+        outline
+    }
 }
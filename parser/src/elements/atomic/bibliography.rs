@@ -0,0 +1,130 @@
+//! Contains the structs and parsers to parse bibliography elements.
+
+use serde::{Deserialize, Serialize};
+use unimarkup_commons::lexer::position::Position;
+
+use super::keyword::{keyword_as_unimarkup, parse_keyword_line};
+use crate::elements::BlockElement;
+use crate::{elements::blocks::Block, BlockParser};
+
+/// Keyword of the bibliography element.
+const BIBLIOGRAPHY_KEYWORD: &str = "{$um.bibliography}";
+
+/// Structure of a Unimarkup bibliography element.
+///
+/// The bibliography is rendered where this element is placed instead of at the end of the document.
+/// It is written as `{$um.bibliography}` on its own line,
+/// optionally followed by [`BibliographyOptions`] as JSON object, e.g. `{$um.bibliography}{"types": ["book"]}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bibliography {
+    /// Options to filter the listed entries and to set the title.
+    pub options: BibliographyOptions,
+    /// The start of this block in the original content.
+    pub start: Position,
+    /// The end of this block in the original content.
+    pub end: Position,
+}
+
+/// Options of a [`Bibliography`] element.
+///
+/// Entries must match all set filters to be listed.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct BibliographyOptions {
+    /// Title shown above the bibliography.
+    /// A localized title is used if no title is set, and no title is shown if the title is empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Only lists entries cited in the section the bibliography is placed in.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub section: bool,
+    /// Only lists entries cited after the heading with this id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    /// Only lists entries with one of these CSL item types, e.g. `book`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+}
+
+impl BlockElement for Bibliography {
+    fn as_unimarkup(&self) -> String {
+        keyword_as_unimarkup(BIBLIOGRAPHY_KEYWORD, &self.options)
+    }
+
+    fn start(&self) -> Position {
+        self.start
+    }
+
+    fn end(&self) -> Position {
+        self.end
+    }
+}
+
+impl Bibliography {
+    pub(crate) fn parse<'s, 'i>(
+        mut parser: BlockParser<'s, 'i>,
+    ) -> (BlockParser<'s, 'i>, Option<Block>) {
+        let Some((options, start, end)) = parse_keyword_line(&mut parser, BIBLIOGRAPHY_KEYWORD)
+        else {
+            return (parser, None);
+        };
+
+        (
+            parser,
+            Some(Block::Bibliography(Bibliography {
+                options,
+                start,
+                end,
+            })),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use unimarkup_commons::config::Config;
+
+    use super::*;
+    use crate::parse_unimarkup;
+
+    #[test]
+    fn parse_bibliography() {
+        let doc = parse_unimarkup("{$um.bibliography}", Config::default());
+
+        let Some(Block::Bibliography(bibliography)) = doc.blocks.first() else {
+            panic!("Expected a bibliography, got '{:?}'.", doc.blocks);
+        };
+        assert_eq!(bibliography.options, BibliographyOptions::default());
+        assert_eq!(bibliography.as_unimarkup(), "{$um.bibliography}");
+    }
+
+    #[test]
+    fn parse_bibliography_with_options() {
+        let input = r#"{$um.bibliography}{"title": "Books", "section": true, "types": ["book"]}"#;
+        let doc = parse_unimarkup(input, Config::default());
+
+        let Some(Block::Bibliography(bibliography)) = doc.blocks.first() else {
+            panic!("Expected a bibliography, got '{:?}'.", doc.blocks);
+        };
+        assert_eq!(
+            bibliography.options,
+            BibliographyOptions {
+                title: Some("Books".to_string()),
+                section: true,
+                after: None,
+                types: vec!["book".to_string()],
+            }
+        );
+        assert_eq!(
+            bibliography.as_unimarkup(),
+            r#"{$um.bibliography}{"title":"Books","section":true,"types":["book"]}"#
+        );
+    }
+
+    #[test]
+    fn invalid_options_are_paragraph() {
+        let doc = parse_unimarkup(r#"{$um.bibliography}{"unknown": 1}"#, Config::default());
+
+        assert!(matches!(doc.blocks.first(), Some(Block::Paragraph(_))));
+    }
+}
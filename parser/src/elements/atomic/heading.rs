@@ -1,6 +1,6 @@
 //! Contains the structs and parsers to parse heading elements.
 
-use std::rc::Rc;
+use std::{collections::HashSet, rc::Rc};
 
 use strum_macros::*;
 use unimarkup_commons::lexer::token::iterator::{EndMatcher, Itertools, PrefixMatcher};
@@ -10,6 +10,7 @@ use unimarkup_inline::parser;
 
 use crate::elements::BlockElement;
 use crate::{elements::blocks::Block, BlockParser};
+use unimarkup_commons::config::preamble::SlugConfig;
 use unimarkup_commons::lexer::position::Position;
 
 use super::log_id::AtomicError;
@@ -148,6 +149,15 @@ impl BlockElement for Heading {
 }
 
 impl Heading {
+    /// Returns `false` if this heading is excluded from tables of contents with the attribute `"toc": false`.
+    pub fn in_toc(&self) -> bool {
+        self.attributes
+            .as_ref()
+            .and_then(|attributes| serde_json::from_str::<serde_json::Value>(attributes).ok())
+            .and_then(|attributes| attributes.get("toc")?.as_bool())
+            .unwrap_or(true)
+    }
+
     pub(crate) fn parse<'s, 'i>(
         mut parser: BlockParser<'s, 'i>,
     ) -> (BlockParser<'s, 'i>, Option<Block>) {
@@ -196,7 +206,7 @@ impl Heading {
         }
 
         let content = parsed_inlines.to_inlines();
-        let id = as_id(&content);
+        let id = as_id(&content, &SlugConfig::default());
 
         //TODO: implement optional attribute parsing here
 
@@ -221,15 +231,105 @@ impl Heading {
     }
 }
 
-/// Converts the heading content into a valid ID.
+/// Converts the heading content into a valid ID using the given slug rules.
 ///
 /// Whitespaces are replaced with `-`, quotes and backslash are removed,
-/// and all other content is lowercased.
-fn as_id(content: &Vec<Inline>) -> String {
-    let mut s = content.as_unimarkup().to_lowercase();
+/// and all other content is lowercased unless the case is kept.
+/// With ASCII slugs, letters are transliterated (e.g. `ä` to `ae`), and other non-ASCII characters are removed.
+fn as_id(content: &Vec<Inline>, rules: &SlugConfig) -> String {
+    let mut s = content.as_unimarkup();
+    if !rules.slug_keep_case {
+        s = s.to_lowercase();
+    }
     s = s.replace(char::is_whitespace, "-");
     s = s.replace('\\', ""); // backslash removed to prevent html escapes
-    s.replace(['\'', '"'], "") // quotes removed to prevent early attribute closing
+    s = s.replace(['\'', '"'], ""); // quotes removed to prevent early attribute closing
+
+    if rules.slug_ascii {
+        s = transliterate(&s);
+    }
+    s
+}
+
+/// Transliterates the given slug to ASCII letters, digits, `-` and `_`.
+/// Repeated `-` that result from removed characters are combined.
+fn transliterate(slug: &str) -> String {
+    let mut ascii = String::with_capacity(slug.len());
+    for c in slug.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => ascii.push(c),
+            c if c.is_uppercase() => {
+                let lower = c.to_lowercase().next().unwrap_or(c);
+                if let Some(latin) = latin_letters(lower) {
+                    // Only the first letter is uppercased, e.g. `Ä` to `Ae`
+                    let mut chars = latin.chars();
+                    ascii.extend(chars.next().map(|first| first.to_ascii_uppercase()));
+                    ascii.extend(chars);
+                }
+            }
+            c => ascii.extend(latin_letters(c)),
+        }
+    }
+
+    ascii.split('-').filter(|part| !part.is_empty()).join("-")
+}
+
+/// Latin letters for lowercase letters with diacritics and ligatures.
+fn latin_letters(c: char) -> Option<&'static str> {
+    Some(match c {
+        'ä' | 'æ' => "ae",
+        'ö' | 'œ' => "oe",
+        'ü' => "ue",
+        'ß' => "ss",
+        'à' | 'á' | 'â' | 'ã' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'ğ' => "g",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ı' => "i",
+        'ł' | 'ľ' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ø' | 'ō' | 'ő' => "o",
+        'ř' => "r",
+        'ś' | 'š' | 'ş' => "s",
+        'ť' | 'ţ' => "t",
+        'ù' | 'ú' | 'û' | 'ů' | 'ū' | 'ű' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
+
+/// Sets document-wide unique ids for all headings of the given blocks using the given slug rules.
+///
+/// Ids of later headings with an already used id get the first free numeric suffix,
+/// e.g. the second `intro` becomes `intro-1`.
+pub(crate) fn assign_unique_ids(blocks: &mut [Block], rules: &SlugConfig) {
+    fn assign(blocks: &mut [Block], rules: &SlugConfig, used: &mut HashSet<String>) {
+        for block in blocks {
+            match block {
+                Block::Heading(heading) => {
+                    let id = as_id(&heading.content, rules);
+                    let mut unique = id.clone();
+                    let mut suffix = 0;
+                    while used.contains(&unique) {
+                        suffix += 1;
+                        unique = format!("{}-{}", id, suffix);
+                    }
+                    used.insert(unique.clone());
+                    heading.id = unique;
+                }
+                Block::BulletList(list) => list
+                    .entries
+                    .iter_mut()
+                    .for_each(|entry| assign(&mut entry.body, rules, used)),
+                Block::BulletListEntry(entry) => assign(&mut entry.body, rules, used),
+                _ => {}
+            }
+        }
+    }
+
+    assign(blocks, rules, &mut HashSet::new());
 }
 
 // Below consts allow matching without dynamic allocations.
@@ -321,3 +421,55 @@ const fn sub_heading_start(hashes_len: usize) -> &'static [TokenKind; 3] {
         &SUB_HEADING_LVL_6_HASH_PREFIX
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use unimarkup_commons::config::{preamble::SlugConfig, Config};
+
+    use crate::{elements::blocks::Block, parse_unimarkup};
+
+    fn heading_ids(input: &str, slugs: SlugConfig) -> Vec<String> {
+        let mut config = Config::default();
+        config.preamble.slugs = slugs;
+
+        parse_unimarkup(input, config)
+            .blocks
+            .into_iter()
+            .filter_map(|block| match block {
+                Block::Heading(heading) => Some(heading.id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn duplicate_ids_get_suffix() {
+        let ids = heading_ids(
+            "# Intro\n\n# Intro\n\n# Intro 1\n\n# Intro\n",
+            SlugConfig::default(),
+        );
+
+        assert_eq!(ids, ["intro", "intro-1", "intro-1-1", "intro-2"]);
+    }
+
+    #[test]
+    fn ascii_slugs() {
+        let ascii = SlugConfig {
+            slug_ascii: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            heading_ids("# Übersicht der Maße & Größen\n", ascii.clone()),
+            ["uebersicht-der-masse-groessen"]
+        );
+
+        let keep_case = SlugConfig {
+            slug_keep_case: true,
+            ..ascii
+        };
+        assert_eq!(
+            heading_ids("# Éléments Über\n", keep_case),
+            ["Elements-Ueber"]
+        );
+    }
+}
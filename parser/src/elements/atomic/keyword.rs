@@ -0,0 +1,64 @@
+//! Contains the functions shared by atomic elements written as keyword on its own line,
+//! optionally followed by options as JSON object, e.g. `{$um.toc}{"depth": 2}`.
+
+use serde::{de::DeserializeOwned, Serialize};
+use unimarkup_commons::lexer::{
+    position::Position,
+    token::{Token, TokenKind},
+};
+
+use crate::BlockParser;
+
+/// Parses the current line as the given keyword followed by optional options.
+/// Default options are used if no options are written after the keyword.
+///
+/// Returns the options together with the start and end of the line,
+/// or `None` if the line does not start with the keyword or the options are invalid.
+pub(crate) fn parse_keyword_line<T: DeserializeOwned + Default>(
+    parser: &mut BlockParser,
+    keyword: &str,
+) -> Option<(T, Position, Position)> {
+    let line: Vec<Token> = parser
+        .iter
+        .by_ref()
+        .take_while(|token| {
+            !matches!(
+                token.kind,
+                TokenKind::Newline | TokenKind::Blankline | TokenKind::Eoi
+            )
+        })
+        .copied()
+        .collect();
+
+    let content = Token::flatten(&line)?;
+    let options = content.trim_end().strip_prefix(keyword)?;
+
+    let options = if options.trim().is_empty() {
+        T::default()
+    } else {
+        // Invalid options are taken as paragraph
+        serde_json::from_str(options).ok()?
+    };
+
+    let start = line.first().expect("Content is not empty.").start;
+    let end = line.last().expect("Content is not empty.").end;
+
+    Some((options, start, end))
+}
+
+/// Returns the given keyword followed by the options as JSON object.
+/// Options are omitted if they equal the default options.
+pub(crate) fn keyword_as_unimarkup<T: Serialize + Default + PartialEq>(
+    keyword: &str,
+    options: &T,
+) -> String {
+    if *options == T::default() {
+        return keyword.to_string();
+    }
+
+    format!(
+        "{}{}",
+        keyword,
+        serde_json::to_string(options).unwrap_or_default()
+    )
+}
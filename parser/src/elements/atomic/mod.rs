@@ -1,9 +1,15 @@
 //! Contains atomic elements
 
+mod bibliography;
 mod heading;
+mod keyword;
 mod paragraph;
+mod toc;
 
+pub use bibliography::{Bibliography, BibliographyOptions};
+pub(crate) use heading::assign_unique_ids;
 pub use heading::{Heading, HeadingLevel};
 pub use paragraph::Paragraph;
+pub use toc::{TableOfContents, TocOptions};
 
 pub mod log_id;
@@ -0,0 +1,106 @@
+//! Contains the structs and parsers to parse table of contents elements.
+
+use serde::{Deserialize, Serialize};
+use unimarkup_commons::lexer::position::Position;
+
+use super::keyword::{keyword_as_unimarkup, parse_keyword_line};
+use crate::elements::BlockElement;
+use crate::{elements::blocks::Block, BlockParser};
+
+/// Keyword of the table of contents element.
+const TOC_KEYWORD: &str = "{$um.toc}";
+
+/// Structure of a Unimarkup table of contents element.
+///
+/// The table of contents lists the headings of the document where this element is placed.
+/// It is written as `{$um.toc}` on its own line,
+/// optionally followed by [`TocOptions`] as JSON object, e.g. `{$um.toc}{"depth": 2}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableOfContents {
+    /// Options to limit the listed headings and to set the title.
+    pub options: TocOptions,
+    /// The start of this block in the original content.
+    pub start: Position,
+    /// The end of this block in the original content.
+    pub end: Position,
+}
+
+/// Options of a [`TableOfContents`] element.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TocOptions {
+    /// Title shown above the table of contents.
+    /// A localized title is used if no title is set, and no title is shown if the title is empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Deepest heading level that is listed.
+    /// Falls back to the depth set in the preamble, or lists all levels if none is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<u8>,
+    /// Ids of headings that are not listed together with their subheadings.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+impl BlockElement for TableOfContents {
+    fn as_unimarkup(&self) -> String {
+        keyword_as_unimarkup(TOC_KEYWORD, &self.options)
+    }
+
+    fn start(&self) -> Position {
+        self.start
+    }
+
+    fn end(&self) -> Position {
+        self.end
+    }
+}
+
+impl TableOfContents {
+    pub(crate) fn parse<'s, 'i>(
+        mut parser: BlockParser<'s, 'i>,
+    ) -> (BlockParser<'s, 'i>, Option<Block>) {
+        let Some((options, start, end)) = parse_keyword_line(&mut parser, TOC_KEYWORD) else {
+            return (parser, None);
+        };
+
+        (
+            parser,
+            Some(Block::TableOfContents(TableOfContents {
+                options,
+                start,
+                end,
+            })),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use unimarkup_commons::config::Config;
+
+    use super::*;
+    use crate::parse_unimarkup;
+
+    #[test]
+    fn parse_toc_with_options() {
+        let input = r#"{$um.toc}{"depth": 2, "exclude": ["appendix"]}"#;
+        let doc = parse_unimarkup(input, Config::default());
+
+        let Some(Block::TableOfContents(toc)) = doc.blocks.first() else {
+            panic!("Expected a table of contents, got '{:?}'.", doc.blocks);
+        };
+        assert_eq!(
+            toc.options,
+            TocOptions {
+                title: None,
+                depth: Some(2),
+                exclude: vec!["appendix".to_string()],
+            }
+        );
+        assert_eq!(
+            toc.as_unimarkup(),
+            r#"{$um.toc}{"depth":2,"exclude":["appendix"]}"#
+        );
+    }
+}
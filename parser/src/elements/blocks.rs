@@ -1,9 +1,12 @@
 //! Defines the generic Unimarkup Block that is the base for all block elements.
 
-use unimarkup_commons::lexer::{position::Position, span::Span, symbol::SymbolKind};
+use unimarkup_commons::{
+    config::icu_locid::Locale,
+    lexer::{position::Position, span::Span, symbol::SymbolKind},
+};
 
 use super::{
-    atomic::{Heading, Paragraph},
+    atomic::{Bibliography, Heading, Paragraph, TableOfContents},
     enclosed::VerbatimBlock,
     indents::{BulletList, BulletListEntry},
     BlockElement,
@@ -25,9 +28,38 @@ pub enum Block {
     BulletList(BulletList),
     /// Represents the bullet list entry block
     BulletListEntry(BulletListEntry),
+    /// Represents the block placing the bibliography
+    Bibliography(Bibliography),
+    /// Represents the block placing a table of contents
+    TableOfContents(TableOfContents),
 }
 
 impl Block {
+    /// Returns the locale set with the `lang` attribute of this block, if any.
+    pub fn lang(&self) -> Option<Locale> {
+        let attributes = match self {
+            Block::Heading(heading) => heading.attributes.as_ref(),
+            Block::VerbatimBlock(verbatim) => verbatim.attributes.as_ref(),
+            _ => None,
+        }?;
+
+        let attributes: serde_json::Value = serde_json::from_str(attributes).ok()?;
+        attributes.get("lang")?.as_str()?.parse().ok()
+    }
+
+    /// Returns `true` if this block should be part of the output for the given locale.
+    /// Blocks without `lang` attribute are part of every output.
+    /// A `lang` attribute without region (e.g. `de`) matches all regions of this language.
+    pub fn matches_lang(&self, lang: &Locale) -> bool {
+        match self.lang() {
+            Some(block_lang) => {
+                block_lang.id.language == lang.id.language
+                    && (block_lang.id.region.is_none() || block_lang.id.region == lang.id.region)
+            }
+            None => true,
+        }
+    }
+
     /// Returns the variant of [`Block`] as string.
     /// e.g. "Paragraph" for [`Block::Paragraph`]
     pub fn variant_str(&self) -> &'static str {
@@ -38,6 +70,8 @@ impl Block {
             Block::VerbatimBlock(_) => "VerbatimBlock",
             Block::BulletList(_) => "BulletList",
             Block::BulletListEntry(_) => "BulletListEntry",
+            Block::Bibliography(_) => "Bibliography",
+            Block::TableOfContents(_) => "TableOfContents",
         }
     }
 }
@@ -51,6 +85,8 @@ impl BlockElement for Block {
             Block::VerbatimBlock(block) => block.as_unimarkup(),
             Block::BulletList(block) => block.as_unimarkup(),
             Block::BulletListEntry(block) => block.as_unimarkup(),
+            Block::Bibliography(block) => block.as_unimarkup(),
+            Block::TableOfContents(block) => block.as_unimarkup(),
         }
     }
 
@@ -62,6 +98,8 @@ impl BlockElement for Block {
             Block::VerbatimBlock(block) => block.start(),
             Block::BulletList(block) => block.start(),
             Block::BulletListEntry(block) => block.start(),
+            Block::Bibliography(block) => block.start(),
+            Block::TableOfContents(block) => block.start(),
         }
     }
 
@@ -73,6 +111,8 @@ impl BlockElement for Block {
             Block::VerbatimBlock(block) => block.end(),
             Block::BulletList(block) => block.end(),
             Block::BulletListEntry(block) => block.end(),
+            Block::Bibliography(block) => block.end(),
+            Block::TableOfContents(block) => block.end(),
         }
     }
 }
@@ -80,6 +80,9 @@ impl From<TokenKind> for PossibleBlockStart {
                     return PossibleBlockStart::VerbatimBlock;
                 }
             }
+            TokenKind::OpenBrace => {
+                return PossibleBlockStart::OpenBrace;
+            }
             TokenKind::Underline(_)
             | TokenKind::Caret(_)
             | TokenKind::Overline(_)
@@ -91,11 +94,11 @@ impl From<TokenKind> for PossibleBlockStart {
             | TokenKind::Dot(_)
             | TokenKind::Ampersand(_)
             | TokenKind::Comma(_)
+            | TokenKind::Semicolon(_)
             | TokenKind::OpenParenthesis
             | TokenKind::CloseParenthesis
             | TokenKind::OpenBracket
             | TokenKind::CloseBracket
-            | TokenKind::OpenBrace
             | TokenKind::CloseBrace
             | TokenKind::Whitespace
             | TokenKind::Newline
@@ -2,35 +2,109 @@
 
 use std::rc::Rc;
 
+use logid::{
+    log,
+    logging::event_entry::AddonKind,
+    lsp_types::{Diagnostic, DiagnosticSeverity, Range},
+};
 use unimarkup_commons::{
     config::preamble::Preamble,
-    lexer::token::{
-        iterator::{EndMatcher, PeekingNext},
-        Token, TokenKind,
+    lexer::{
+        position::Position,
+        token::{
+            iterator::{EndMatcher, PeekingNext},
+            Token, TokenKind,
+        },
     },
 };
 
-use crate::BlockParser;
+use crate::{log_id::ParserError, BlockParser};
+
+/// Minimum number of delimiter symbols needed to start or end a preamble.
+const PREAMBLE_DELIMITER_LEN: usize = 3;
+
+/// Supported formats of the preamble content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PreambleFormat {
+    /// Preamble enclosed in `+++`.
+    Yaml,
+    /// Preamble enclosed in `;;;`.
+    Json,
+}
+
+impl PreambleFormat {
+    fn from_delimiter(kind: TokenKind) -> Option<Self> {
+        match kind {
+            TokenKind::Plus(len) if len >= PREAMBLE_DELIMITER_LEN => Some(PreambleFormat::Yaml),
+            TokenKind::Semicolon(len) if len >= PREAMBLE_DELIMITER_LEN => {
+                Some(PreambleFormat::Json)
+            }
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            PreambleFormat::Yaml => "YAML",
+            PreambleFormat::Json => "JSON",
+        }
+    }
+}
+
+/// Error returned by deserializers with the position relative to the preamble content.
+struct DeserializeError {
+    msg: String,
+    /// 1-based line and column inside the preamble content, if the deserializer provides one.
+    location: Option<(usize, usize)>,
+}
+
+impl From<serde_yaml::Error> for DeserializeError {
+    fn from(value: serde_yaml::Error) -> Self {
+        DeserializeError {
+            location: value.location().map(|loc| (loc.line(), loc.column())),
+            msg: value.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for DeserializeError {
+    fn from(value: serde_json::Error) -> Self {
+        DeserializeError {
+            location: (value.line() > 0).then(|| (value.line(), value.column().max(1))),
+            msg: value.to_string(),
+        }
+    }
+}
 
+/// Parses the preamble at the start of the document.
+///
+/// The returned parser is only advanced if a preamble was found.
+/// If the preamble content is not valid, an error is logged and `None` is returned,
+/// but the preamble is still consumed to not treat it as regular content.
 pub(crate) fn parse_preamble<'s, 'i>(
     mut parser: BlockParser<'s, 'i>,
 ) -> (BlockParser<'s, 'i>, Option<Preamble>) {
-    match parser.iter.peeking_next(|_| true) {
-        Some(token) => {
-            if let TokenKind::Plus(len) = token.kind {
-                if len < 3
-                    || parser
-                        .iter
-                        .peeking_next(|t| t.kind == TokenKind::Newline)
-                        .is_none()
-                {
-                    return (parser, None);
-                }
-            } else {
+    let checkpoint = parser.iter.checkpoint();
+
+    let format = match parser.iter.peeking_next(|_| true) {
+        Some(token) => match PreambleFormat::from_delimiter(token.kind) {
+            Some(format)
+                if parser
+                    .iter
+                    .peeking_next(|t| t.kind == TokenKind::Newline)
+                    .is_some() =>
+            {
+                format
+            }
+            _ => {
+                parser.iter.rollback(checkpoint);
                 return (parser, None);
             }
+        },
+        None => {
+            parser.iter.rollback(checkpoint);
+            return (parser, None);
         }
-        None => return (parser, None),
     };
 
     let preamble_start = parser
@@ -45,18 +119,73 @@ pub(crate) fn parse_preamble<'s, 'i>(
         None,
         Some(Rc::new(move |matcher: &mut dyn EndMatcher| {
             matcher.consumed_matches(&[preamble_kind, TokenKind::Blankline])
+                || matcher.consumed_matches(&[preamble_kind, TokenKind::Eoi])
         })),
     );
 
     let preamble_tokens = preamble_parser.iter.take_to_end();
+    let closed = preamble_parser.iter.end_reached();
     parser = preamble_parser.into_inner();
-    let content = Token::flatten_ref(&preamble_tokens);
 
-    match content {
-        Some(preamble_content) => match serde_yaml::from_str(preamble_content) {
-            Ok(preamble) => (parser, Some(preamble)),
-            Err(_) => (parser, None),
-        },
-        None => (parser, None),
+    if !closed {
+        // Without closing delimiter, the content is not treated as preamble.
+        parser.iter.rollback(checkpoint);
+        return (parser, None);
+    }
+
+    let content = match Token::flatten_ref(&preamble_tokens) {
+        Some(content) => content,
+        None => return (parser, None),
+    };
+
+    let content_start = preamble_tokens
+        .first()
+        .map(|t| t.start)
+        .unwrap_or(preamble_start.end);
+
+    let deserialized: Result<Preamble, DeserializeError> = match format {
+        PreambleFormat::Yaml => serde_yaml::from_str(content).map_err(DeserializeError::from),
+        PreambleFormat::Json => serde_json::from_str(content).map_err(DeserializeError::from),
+    };
+
+    match deserialized {
+        Ok(preamble) => (parser, Some(preamble)),
+        Err(err) => {
+            log_invalid_preamble(format, &err, content_start);
+            (parser, None)
+        }
     }
 }
+
+/// Logs the deserialization error with the position mapped back to the Unimarkup document.
+fn log_invalid_preamble(format: PreambleFormat, err: &DeserializeError, content_start: Position) {
+    // Note: Line and column are 1-based for both, the document and the deserializers.
+    let (line, column) = match err.location {
+        Some((1, column)) => (content_start.line, content_start.col_utf8 + column - 1),
+        Some((line, column)) => (content_start.line + line - 1, column),
+        None => (content_start.line, content_start.col_utf8),
+    };
+
+    let lsp_pos = logid::lsp_types::Position::new(
+        line.saturating_sub(1) as u32,
+        column.saturating_sub(1) as u32,
+    );
+
+    log!(
+        ParserError::InvalidPreamble,
+        format!(
+            "Invalid {} preamble at line {}, column {}: {}",
+            format.as_str(),
+            line,
+            column,
+            err.msg
+        ),
+        add: AddonKind::Diagnostic(Diagnostic {
+            range: Range::new(lsp_pos, lsp_pos),
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("unimarkup".to_string()),
+            message: err.msg.clone(),
+            ..Default::default()
+        })
+    );
+}
@@ -6,6 +6,7 @@ pub mod document;
 pub mod elements;
 pub mod log_id;
 pub mod metadata;
+pub mod numbering;
 mod parser;
 pub mod security;
 
@@ -7,6 +7,8 @@ use logid::{ErrLogId, WarnLogId};
 pub enum ParserError {
     /// Log-id denoting that no Unimarkup element was detected
     NoUnimarkupDetected,
+    /// Log-id denoting that the preamble content could not be deserialized
+    InvalidPreamble,
 }
 
 /// Parser warning log-ids
@@ -0,0 +1,84 @@
+//! Contains the [`Counters`] used to number headings in document flow.
+
+use unimarkup_commons::config::preamble::Numbering;
+
+/// Number of heading levels.
+const LEVELS: usize = 6;
+
+/// Counters numbering headings in document flow,
+/// following the [`Numbering`] set in the preamble.
+#[derive(Debug, Clone)]
+pub struct Counters<'cfg> {
+    numbering: &'cfg Numbering,
+    sections: [usize; LEVELS],
+}
+
+impl<'cfg> Counters<'cfg> {
+    /// Creates counters starting at the beginning of a document.
+    pub fn new(numbering: &'cfg Numbering) -> Self {
+        Self {
+            numbering,
+            sections: [0; LEVELS],
+        }
+    }
+
+    /// Counts a heading of the given level from 1 to 6, and returns its hierarchical number, e.g. `2.1`.
+    ///
+    /// Returns `None` if the level is deeper than the numbering depth.
+    /// Internal references use all heading levels if no depth is set.
+    pub fn heading(&mut self, level: u8) -> Option<String> {
+        let level = (level as usize).clamp(1, LEVELS);
+        self.sections[level - 1] += 1;
+        self.sections[level..].fill(0);
+
+        let depth = self.numbering.numbering_depth.unwrap_or(LEVELS as u8) as usize;
+        (level <= depth).then(|| self.section_number(level))
+    }
+
+    /// Number of the current heading at the given level.
+    fn section_number(&self, level: usize) -> String {
+        let style = self.numbering.style();
+        self.sections[..level.min(LEVELS)]
+            .iter()
+            .map(|count| style.format(*count))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use unimarkup_commons::config::preamble::NumberingStyle;
+
+    use super::*;
+
+    #[test]
+    fn number_headings() {
+        let numbering = Numbering {
+            numbering_depth: Some(2),
+            ..Default::default()
+        };
+        let mut counters = Counters::new(&numbering);
+
+        assert_eq!(counters.heading(1).as_deref(), Some("1"));
+        assert_eq!(counters.heading(2).as_deref(), Some("1.1"));
+        assert_eq!(counters.heading(3), None);
+        assert_eq!(counters.heading(2).as_deref(), Some("1.2"));
+        assert_eq!(counters.heading(1).as_deref(), Some("2"));
+        assert_eq!(counters.heading(2).as_deref(), Some("2.1"));
+    }
+
+    #[test]
+    fn number_headings_with_style() {
+        let numbering = Numbering {
+            numbering_style: Some(NumberingStyle::Roman),
+            ..Default::default()
+        };
+        let mut counters = Counters::new(&numbering);
+
+        assert_eq!(counters.heading(1).as_deref(), Some("I"));
+        assert_eq!(counters.heading(2).as_deref(), Some("I.I"));
+        assert_eq!(counters.heading(2).as_deref(), Some("I.II"));
+        assert_eq!(counters.heading(1).as_deref(), Some("II"));
+    }
+}
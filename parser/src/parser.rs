@@ -10,12 +10,15 @@ use unimarkup_commons::{
         },
     },
 };
-use unimarkup_inline::parser::{InlineContext, InlineContextFlags};
+use unimarkup_inline::{
+    element::textbox::citation::CitationEntry,
+    parser::{InlineContext, InlineContextFlags},
+};
 
 use crate::{
     document::Document,
     elements::{
-        atomic::{Heading, Paragraph},
+        atomic::{assign_unique_ids, Bibliography, Heading, Paragraph, TableOfContents},
         blocks::Block,
         enclosed::VerbatimBlock,
         indents::BulletList,
@@ -26,26 +29,44 @@ use crate::{
     metadata::{Metadata, MetadataKind},
     security,
 };
-use unimarkup_commons::config::Config;
+use unimarkup_commons::config::{icu_locid::Locale, Config};
 
 /// Parses and returns a Unimarkup document.
-pub fn parse_unimarkup(um_content: &str, mut config: Config) -> Document {
+pub fn parse_unimarkup(um_content: &str, config: Config) -> Document {
+    parse_with_context(um_content, config, BlockContext::default())
+}
+
+/// Parses and returns a Unimarkup document for the given output locale.
+///
+/// The locale is set as main language of the document,
+/// and blocks with a `lang` attribute not matching the locale are skipped.
+pub fn parse_unimarkup_for_lang(um_content: &str, mut config: Config, lang: Locale) -> Document {
+    config.preamble.i18n.lang = Some(lang.clone());
+
+    let context = BlockContext {
+        lang: Some(lang),
+        ..Default::default()
+    };
+
+    parse_with_context(um_content, config, context)
+}
+
+fn parse_with_context(um_content: &str, mut config: Config, context: BlockContext) -> Document {
     let tokens = unimarkup_commons::lexer::token::lex_str(um_content);
 
-    let mut parser = BlockParser::new(TokenIterator::from(&*tokens), BlockContext::default());
+    let mut parser = BlockParser::new(TokenIterator::from(&*tokens), context);
 
-    let checkpoint = parser.iter.checkpoint();
     let (updated_parser, preamble) = parse_preamble(parser);
     parser = updated_parser;
 
-    match preamble.clone() {
-        Some(preamble) => config.preamble.merge(preamble),
-        None => {
-            parser.iter.rollback(checkpoint);
+    if let Some(preamble) = preamble.clone() {
+        if !config.merging.ignore_preamble {
+            config.preamble.merge(preamble);
         }
     }
 
-    let (parser, blocks) = BlockParser::parse(parser);
+    let (parser, mut blocks) = BlockParser::parse(parser);
+    assign_unique_ids(&mut blocks, &config.preamble.slugs);
 
     let input = config.input.clone();
     Document {
@@ -117,11 +138,17 @@ impl<'slice, 'input> BlockParser<'slice, 'input> {
                     // Token might be start of a block element
                     for parser_fn in get_parser_fn(block_start, &parser.context) {
                         let checkpoint = parser.iter.checkpoint();
+                        let citations_len = parser.context.citations.len();
                         let (updated_parser, block_opt) = parser_fn(parser);
                         parser = updated_parser;
                         match block_opt {
                             Some(block) => {
-                                blocks.push(block);
+                                if parser.context.matches_lang(&block) {
+                                    blocks.push(block);
+                                } else {
+                                    // Citations of skipped blocks must not be rendered.
+                                    parser.context.citations.truncate(citations_len);
+                                }
 
                                 if let Some(prev) = parser.iter.prev() {
                                     // To keep possibly consumed blank lines
@@ -232,7 +259,7 @@ fn get_parser_fn(start: PossibleBlockStart, context: &BlockContext) -> &'static
             PossibleBlockStart::LineBreak => &[],
             PossibleBlockStart::Decoration | PossibleBlockStart::Paragraph => &[],
             PossibleBlockStart::OpenBracket => &[],
-            PossibleBlockStart::OpenBrace => &[], //&[attribute_block_parser, block_macro_parser],
+            PossibleBlockStart::OpenBrace => &[Bibliography::parse, TableOfContents::parse], //&[attribute_block_parser, block_macro_parser],
         }
     }
 }
@@ -244,8 +271,11 @@ pub struct BlockContext {
     pub flags: BlockContextFlags,
     /// Citations used in the Unimarkup content.
     /// The citations are added in document flow.
-    /// Every citation may contain one or more citation entry IDs.
-    pub citations: Vec<Vec<String>>,
+    /// Every citation may contain one or more citation entries.
+    pub citations: Vec<Vec<CitationEntry>>,
+    /// Locale of the output the content is parsed for.
+    /// If set, blocks with a `lang` attribute not matching this locale are skipped.
+    pub lang: Option<Locale>,
 }
 
 /// Block context flags used to define parser behavior of block element parsing.
@@ -274,6 +304,14 @@ impl From<&BlockContext> for InlineContext {
 }
 
 impl BlockContext {
+    /// Returns `true` if the given block is part of the output for the locale of this context.
+    pub fn matches_lang(&self, block: &Block) -> bool {
+        match &self.lang {
+            Some(lang) => block.matches_lang(lang),
+            None => true,
+        }
+    }
+
     /// Updates the block context using the given [`InlineContext`].
     pub fn update_from(&mut self, mut inline_context: InlineContext) {
         // Flags are not updated, because they only "propagate" block->inline, but not the other way.
@@ -322,4 +360,102 @@ Funktioniert preamble parsing?
 
         assert!(!doc.metadata.is_empty());
     }
+
+    #[test]
+    fn json_preamble() {
+        let content = ";;;
+{
+    \"lang\": \"de-AT\"
+}
+;;;
+
+Funktioniert preamble parsing?
+        ";
+        let doc = parse_unimarkup(content, unimarkup_commons::config::Config::default());
+
+        assert_eq!(
+            doc.config.preamble.i18n.lang,
+            Some(unimarkup_commons::config::icu_locid::locale!("de-AT"))
+        );
+        assert_eq!(doc.blocks.len(), 1, "Preamble was parsed as content.");
+    }
+
+    #[test]
+    fn ignored_preamble() {
+        let content = "+++
+lang: \"de-AT\"
++++
+
+Preamble is ignored.
+        ";
+        let mut config = unimarkup_commons::config::Config::default();
+        config.merging.ignore_preamble = true;
+
+        let doc = parse_unimarkup(content, config);
+
+        assert!(doc.config.preamble.i18n.lang.is_none());
+        assert!(doc.metadata[0].preamble.is_some());
+    }
+
+    #[test]
+    fn invalid_preamble_is_not_content() {
+        let content = "+++
+lang: [de-AT
++++
+
+Only this paragraph is content.
+        ";
+        let doc = parse_unimarkup(content, unimarkup_commons::config::Config::default());
+
+        assert!(doc.metadata[0].preamble.is_none());
+        assert_eq!(
+            doc.blocks.len(),
+            1,
+            "Invalid preamble was parsed as content."
+        );
+    }
+
+    #[test]
+    fn parse_for_lang() {
+        let content = "+++
+lang: \"en\"
+output_langs: [\"de-AT\", \"en\"]
++++
+
+Paragraph content.
+        ";
+        let doc = crate::parse_unimarkup_for_lang(
+            content,
+            unimarkup_commons::config::Config::default(),
+            unimarkup_commons::config::icu_locid::locale!("de-AT"),
+        );
+
+        assert_eq!(
+            doc.config.preamble.i18n.lang,
+            Some(unimarkup_commons::config::icu_locid::locale!("de-AT"))
+        );
+        assert_eq!(doc.config.preamble.i18n.output_langs.len(), 2);
+    }
+
+    #[test]
+    fn block_matches_lang() {
+        use crate::elements::{
+            atomic::{Heading, HeadingLevel},
+            blocks::Block,
+        };
+        use unimarkup_commons::{config::icu_locid::locale, lexer::position::Position};
+
+        let heading = Block::Heading(Heading {
+            id: "heading".to_string(),
+            level: HeadingLevel::Level1,
+            content: vec![],
+            attributes: Some("{ \"lang\": \"de\" }".to_string()),
+            start: Position::default(),
+            end: Position::default(),
+        });
+
+        assert!(heading.matches_lang(&locale!("de-AT")));
+        assert!(heading.matches_lang(&locale!("de")));
+        assert!(!heading.matches_lang(&locale!("en-US")));
+    }
 }
@@ -0,0 +1,901 @@
+//! Conversion of BibTeX and BibLaTeX files into CSL items, and of CSL items into BibTeX.
+//!
+//! Field values are decoded from LaTeX into plain text. Fields without a CSL counterpart are
+//! reported as warnings naming the key of their entry.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use crate::latex::escape::escape_text;
+
+use super::{csl_types::CslItem, date_parts, latex, literal_date, ImportedItems};
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Converts all entries of the given BibTeX or BibLaTeX file content into CSL items.
+pub fn import(input: &str) -> ImportedItems {
+    let mut imported = ImportedItems::default();
+    let mut parser = Parser::new(input);
+    while let Some(entry) = parser.next_entry() {
+        match entry {
+            Ok(entry) => convert(&entry, &mut imported),
+            Err(line) => imported.warnings.push(format!(
+                "Could not parse the BibTeX entry starting in line {}",
+                line
+            )),
+        }
+    }
+    imported
+}
+
+/// Entry of a BibTeX file with its raw field values.
+struct Entry {
+    kind: String,
+    key: String,
+    fields: Vec<(String, String)>,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// Values defined by `@string` entries and the predefined month abbreviations.
+    strings: HashMap<String, String>,
+}
+
+impl Parser {
+    fn new(input: &str) -> Self {
+        Parser {
+            chars: input.chars().collect(),
+            pos: 0,
+            strings: MONTHS
+                .iter()
+                .enumerate()
+                .map(|(index, month)| (month.to_string(), (index + 1).to_string()))
+                .collect(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ()> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    fn line(&self, pos: usize) -> usize {
+        self.chars[..pos].iter().filter(|c| **c == '\n').count() + 1
+    }
+
+    /// Returns the next entry, or the line an unparsable entry starts in.
+    /// `@string` definitions are stored, `@preamble` and `@comment` blocks are skipped.
+    fn next_entry(&mut self) -> Option<Result<Entry, usize>> {
+        loop {
+            while self.peek()? != '@' {
+                self.pos += 1;
+            }
+            let start = self.pos;
+            self.pos += 1;
+            let kind = self.identifier().to_lowercase();
+            self.skip_whitespace();
+            let close = match self.peek() {
+                Some('{') => '}',
+                Some('(') => ')',
+                // An `@` outside of an entry is a comment.
+                _ => continue,
+            };
+            self.pos += 1;
+
+            let entry = match kind.as_str() {
+                "comment" | "preamble" => {
+                    self.skip_block(close);
+                    continue;
+                }
+                "string" => self.string_definition(close).map(|_| None),
+                _ => self.entry(kind, close).map(Some),
+            };
+            match entry {
+                Ok(Some(entry)) => return Some(Ok(entry)),
+                Ok(None) => {}
+                Err(()) => return Some(Err(self.line(start))),
+            }
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let mut identifier = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| !c.is_whitespace() && !"{}(),=\"#%'@".contains(*c))
+        {
+            identifier.push(c);
+            self.pos += 1;
+        }
+        identifier
+    }
+
+    fn skip_block(&mut self, close: char) {
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth > 0 => depth -= 1,
+                c if c == close && depth == 0 => return,
+                _ => {}
+            }
+        }
+    }
+
+    fn string_definition(&mut self, close: char) -> Result<(), ()> {
+        self.skip_whitespace();
+        let name = self.identifier().to_lowercase();
+        self.expect('=')?;
+        let value = self.value()?;
+        self.expect(close)?;
+        self.strings.insert(name, value);
+        Ok(())
+    }
+
+    fn entry(&mut self, kind: String, close: char) -> Result<Entry, ()> {
+        self.skip_whitespace();
+        let mut key = String::new();
+        while let Some(c) = self.peek().filter(|c| *c != ',' && *c != close) {
+            key.push(c);
+            self.pos += 1;
+        }
+        let key = key.trim().to_string();
+        if key.is_empty() {
+            return Err(());
+        }
+
+        let mut fields = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(',') => self.pos += 1,
+                _ => return Err(()),
+            }
+            self.skip_whitespace();
+            if self.peek() == Some(close) {
+                continue;
+            }
+            let name = self.identifier().to_lowercase();
+            if name.is_empty() {
+                return Err(());
+            }
+            self.expect('=')?;
+            fields.push((name, self.value()?));
+        }
+        Ok(Entry { kind, key, fields })
+    }
+
+    /// Raw value of a field, with concatenations and `@string` macros resolved.
+    fn value(&mut self) -> Result<String, ()> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => {
+                    self.pos += 1;
+                    value.push_str(&self.delimited('}')?);
+                }
+                Some('"') => {
+                    self.pos += 1;
+                    value.push_str(&self.delimited('"')?);
+                }
+                Some(_) => {
+                    let name = self.identifier();
+                    if name.is_empty() {
+                        return Err(());
+                    }
+                    match self.strings.get(&name.to_lowercase()) {
+                        Some(defined) => value.push_str(defined),
+                        None => value.push_str(&name),
+                    }
+                }
+                None => return Err(()),
+            }
+            self.skip_whitespace();
+            if self.peek() != Some('#') {
+                return Ok(value);
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Text up to the given delimiter outside of nested braces.
+    fn delimited(&mut self, end: char) -> Result<String, ()> {
+        let mut text = String::new();
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                c if c == end && depth == 0 => return Ok(text),
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+        Err(())
+    }
+}
+
+/// How a BibTeX field is converted.
+enum Target {
+    Text(&'static str),
+    /// Text that must not be decoded, e.g. URLs.
+    Verbatim(&'static str),
+    Names(&'static str),
+    Date(&'static str),
+    /// Publisher, or a field that is used as publisher if no `publisher` field is given.
+    Publisher,
+    Number,
+    Pages,
+    /// Fields that are converted together with other fields.
+    Combined,
+}
+
+fn target(field: &str, kind: &str) -> Option<Target> {
+    let target = match field {
+        "author" => Target::Names("author"),
+        "editor" => Target::Names("editor"),
+        "translator" => Target::Names("translator"),
+        "bookauthor" => Target::Names("container-author"),
+        "title" | "subtitle" | "year" | "month" | "day" => Target::Combined,
+        "shorttitle" => Target::Text("title-short"),
+        "booktitle" | "journal" | "journaltitle" => Target::Text("container-title"),
+        "shortjournal" => Target::Text("container-title-short"),
+        "series" => Target::Text("collection-title"),
+        "address" | "location" => Target::Text("publisher-place"),
+        "publisher" | "institution" | "school" | "organization" | "howpublished" => {
+            Target::Publisher
+        }
+        "volume" => Target::Text("volume"),
+        "volumes" => Target::Text("number-of-volumes"),
+        "number" => Target::Number,
+        "issue" => Target::Text("issue"),
+        "pages" => Target::Pages,
+        "pagetotal" => Target::Text("number-of-pages"),
+        "chapter" => Target::Text("chapter-number"),
+        "edition" => Target::Text("edition"),
+        "type" => Target::Text("genre"),
+        "version" => Target::Text("version"),
+        "eventtitle" => Target::Text("event-title"),
+        "venue" => Target::Text("event-place"),
+        "note" => Target::Text("note"),
+        "abstract" => Target::Text("abstract"),
+        "annote" | "annotation" => Target::Text("annote"),
+        "keywords" => Target::Text("keyword"),
+        "language" | "langid" => Target::Text("language"),
+        "isbn" => Target::Verbatim("ISBN"),
+        "issn" => Target::Verbatim("ISSN"),
+        "doi" => Target::Verbatim("DOI"),
+        "url" => Target::Verbatim("URL"),
+        "date" => Target::Date("issued"),
+        "urldate" => Target::Date("accessed"),
+        "eventdate" => Target::Date("event-date"),
+        "origdate" => Target::Date("original-date"),
+        _ => return None,
+    };
+    // Numbers of periodicals are issue numbers.
+    match target {
+        Target::Number if matches!(kind, "article" | "periodical" | "suppperiodical") => {
+            Some(Target::Text("issue"))
+        }
+        Target::Number => Some(Target::Text("number")),
+        target => Some(target),
+    }
+}
+
+/// CSL item type and genre of a BibTeX entry type.
+fn item_type(kind: &str) -> Option<(&'static str, Option<&'static str>)> {
+    let item_type = match kind {
+        "article" => "article-journal",
+        "book" | "mvbook" | "collection" | "mvcollection" | "proceedings" | "mvproceedings"
+        | "reference" | "mvreference" | "manual" => "book",
+        "inbook" | "bookinbook" | "suppbook" | "incollection" | "suppcollection" => "chapter",
+        "inproceedings" | "conference" => "paper-conference",
+        "inreference" => "entry-encyclopedia",
+        "booklet" => "pamphlet",
+        "mastersthesis" => return Some(("thesis", Some("Master's thesis"))),
+        "phdthesis" => return Some(("thesis", Some("PhD thesis"))),
+        "thesis" => "thesis",
+        "report" | "techreport" => "report",
+        "online" | "www" | "electronic" => "webpage",
+        "periodical" | "suppperiodical" => "periodical",
+        "misc" => "document",
+        "unpublished" => "manuscript",
+        "patent" => "patent",
+        "dataset" => "dataset",
+        "software" => "software",
+        "standard" => "standard",
+        "letter" => "personal_communication",
+        "jurisdiction" => "legal_case",
+        "legislation" => "legislation",
+        "movie" | "video" => "motion_picture",
+        "audio" | "music" => "song",
+        "artwork" | "image" => "graphic",
+        _ => return None,
+    };
+    Some((item_type, None))
+}
+
+fn convert(entry: &Entry, imported: &mut ImportedItems) {
+    let key = entry.key.as_str();
+    let mut item = Map::new();
+    item.insert("id".to_string(), Value::from(key));
+    let (item_type, genre) = item_type(&entry.kind).unwrap_or_else(|| {
+        imported.warnings.push(format!(
+            "Entry '{}' has the unsupported type '{}' and is imported as 'document'",
+            key, entry.kind
+        ));
+        ("document", None)
+    });
+    item.insert("type".to_string(), Value::from(item_type));
+    if let Some(genre) = genre {
+        item.insert("genre".to_string(), Value::from(genre));
+    }
+
+    let field = |name: &str| {
+        entry
+            .fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let has_publisher = field("publisher").is_some();
+
+    for (name, value) in &entry.fields {
+        let unmapped = format!(
+            "Field '{}' of entry '{}' has no CSL counterpart and was ignored",
+            name, key
+        );
+        match target(name, &entry.kind) {
+            Some(Target::Text(variable)) => {
+                item.insert(variable.to_string(), Value::from(latex::decode(value)));
+            }
+            Some(Target::Verbatim(variable)) => {
+                item.insert(variable.to_string(), Value::from(value.trim()));
+            }
+            Some(Target::Names(variable)) => {
+                item.insert(variable.to_string(), Value::Array(names(value)));
+            }
+            Some(Target::Date(variable)) => {
+                item.insert(variable.to_string(), date(value));
+            }
+            Some(Target::Publisher) => {
+                if (name == "publisher" || !has_publisher) && !item.contains_key("publisher") {
+                    item.insert("publisher".to_string(), Value::from(latex::decode(value)));
+                } else {
+                    imported.warnings.push(unmapped);
+                }
+            }
+            Some(Target::Pages) => {
+                let pages = latex::decode(&value.replace("--", "-"));
+                item.insert("page".to_string(), Value::from(pages));
+            }
+            Some(Target::Number) | Some(Target::Combined) => {}
+            None => imported.warnings.push(unmapped),
+        }
+    }
+
+    if let Some(title) = field("title") {
+        let title = match field("subtitle") {
+            Some(subtitle) => format!("{}: {}", latex::decode(title), latex::decode(subtitle)),
+            None => latex::decode(title),
+        };
+        item.insert("title".to_string(), Value::from(title));
+    }
+
+    if let (None, Some(year)) = (field("date"), field("year")) {
+        let year = latex::decode(year);
+        let issued = match year.parse::<u32>() {
+            Ok(year) => {
+                let mut parts = vec![year];
+                if let Some(month) = field("month").and_then(month) {
+                    parts.push(month);
+                    if let Some(day) = field("day").and_then(|day| day.trim().parse().ok()) {
+                        parts.push(day);
+                    }
+                }
+                date_parts(vec![parts])
+            }
+            Err(_) => literal_date(&year),
+        };
+        item.insert("issued".to_string(), issued);
+    }
+
+    imported.push(key, item);
+}
+
+/// Month number of a numeric month or a month name.
+fn month(value: &str) -> Option<u32> {
+    let value = latex::decode(value).to_lowercase();
+    match value.parse::<u32>() {
+        Ok(month) => Some(month).filter(|month| (1..=12).contains(month)),
+        Err(_) => MONTHS
+            .iter()
+            .position(|month| value.starts_with(month))
+            .map(|index| index as u32 + 1),
+    }
+}
+
+/// Converts an ISO 8601 date or date range as used by BibLaTeX, e.g. `2020-05/2021`.
+fn date(value: &str) -> Value {
+    let value = latex::decode(value);
+    let parse = |date: &str| -> Option<Vec<u32>> {
+        date.split('-')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u32>>>()
+            .filter(|parts| (1..=3).contains(&parts.len()))
+    };
+    let parts = match value.split_once('/') {
+        Some((start, "")) => parse(start).map(|start| vec![start]),
+        Some((start, end)) => parse(start).zip(parse(end)).map(|(s, e)| vec![s, e]),
+        None => parse(&value).map(|date| vec![date]),
+    };
+    match parts {
+        Some(parts) => date_parts(parts),
+        None => literal_date(&value),
+    }
+}
+
+/// Converts a list of names separated by `and`. A trailing `and others` is dropped.
+fn names(value: &str) -> Vec<Value> {
+    let mut names = vec![Vec::new()];
+    for word in words(value) {
+        if word.eq_ignore_ascii_case("and") {
+            names.push(Vec::new());
+        } else if let Some(name) = names.last_mut() {
+            name.push(word);
+        }
+    }
+    names
+        .into_iter()
+        .map(|words| words.join(" "))
+        .filter(|name| !name.is_empty() && name != "others")
+        .map(|name| self::name(&name))
+        .collect()
+}
+
+/// Converts a single name given as `First von Last`, `von Last, First` or
+/// `von Last, Jr, First`. A name that is completely enclosed in braces is taken literally.
+fn name(value: &str) -> Value {
+    let parts: Vec<String> = split_top_level(value, ',')
+        .into_iter()
+        .map(|part| part.trim().to_string())
+        .collect();
+    let mut name = Map::new();
+
+    let (given, von, family, suffix) = if parts.len() == 1 {
+        let words = words(&parts[0]);
+        if words.len() == 1 && words[0].starts_with('{') && words[0].ends_with('}') {
+            name.insert("literal".to_string(), Value::from(latex::decode(&words[0])));
+            return Value::Object(name);
+        }
+        let last = words.len().saturating_sub(1);
+        let lowercase: Vec<usize> = (0..last)
+            .filter(|index| latex::starts_lowercase(&words[*index]))
+            .collect();
+        match (lowercase.first(), lowercase.last()) {
+            (Some(&first), Some(&end)) => (
+                words[..first].to_vec(),
+                words[first..=end].to_vec(),
+                words[end + 1..].to_vec(),
+                None,
+            ),
+            _ => (
+                words[..last].to_vec(),
+                Vec::new(),
+                words[last..].to_vec(),
+                None,
+            ),
+        }
+    } else {
+        let words = words(&parts[0]);
+        let last = words.len().saturating_sub(1);
+        let von = words[..last]
+            .iter()
+            .take_while(|word| latex::starts_lowercase(word))
+            .count();
+        let (suffix, given) = match parts.len() {
+            2 => (None, parts[1].as_str()),
+            _ => (Some(parts[1].clone()), parts[parts.len() - 1].as_str()),
+        };
+        (
+            self::words(given),
+            words[..von].to_vec(),
+            words[von..].to_vec(),
+            suffix,
+        )
+    };
+
+    let mut insert = |variable: &str, words: &[String]| {
+        let text = latex::decode(&words.join(" "));
+        if !text.is_empty() {
+            name.insert(variable.to_string(), Value::from(text));
+        }
+    };
+    insert("family", &family);
+    insert("given", &given);
+    insert("non-dropping-particle", &von);
+    if let Some(suffix) = suffix {
+        insert("suffix", &[suffix]);
+    }
+    Value::Object(name)
+}
+
+/// Words of the given value separated by whitespace outside of braces.
+fn words(value: &str) -> Vec<String> {
+    split_top_level(value, ' ')
+        .into_iter()
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Splits the value at the given separator where it is not enclosed in braces. A space as
+/// separator matches all whitespace.
+fn split_top_level(value: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut depth = 0;
+    for c in value.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            c if depth == 0 && (c == separator || separator == ' ' && c.is_whitespace()) => {
+                parts.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(part) = parts.last_mut() {
+            part.push(c);
+        }
+    }
+    parts
+}
+
+/// CSL variables that are exported as BibTeX fields of the same meaning.
+/// Variables of names, dates and the container title are exported separately.
+const EXPORTED_FIELDS: &[(&str, &str)] = &[
+    ("title", "title"),
+    ("collection-title", "series"),
+    ("publisher-place", "address"),
+    ("volume", "volume"),
+    ("issue", "number"),
+    ("number", "number"),
+    ("page", "pages"),
+    ("chapter-number", "chapter"),
+    ("edition", "edition"),
+    ("note", "note"),
+    ("language", "language"),
+];
+
+/// CSL variables that are exported without escaping, because BibTeX styles read them verbatim.
+const EXPORTED_VERBATIM_FIELDS: &[(&str, &str)] = &[
+    ("DOI", "doi"),
+    ("URL", "url"),
+    ("ISBN", "isbn"),
+    ("ISSN", "issn"),
+];
+
+/// Converts the given CSL items into BibTeX entries that can be used with the default BibTeX styles.
+/// Items keep their CSL id as BibTeX key.
+pub fn export(items: &[CslItem]) -> String {
+    items
+        .iter()
+        .filter_map(|item| serde_json::to_value(item).ok())
+        .filter_map(|item| export_entry(&item))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn export_entry(item: &Value) -> Option<String> {
+    let key = match &item["id"] {
+        Value::String(id) => id.clone(),
+        Value::Number(id) => id.to_string(),
+        _ => return None,
+    };
+    let text = |variable: &str| match &item[variable] {
+        Value::String(value) if !value.trim().is_empty() => Some(value.trim().to_string()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    };
+    let kind = entry_type(
+        item["type"].as_str().unwrap_or_default(),
+        text("genre").as_deref(),
+    );
+
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    for (variable, field) in [
+        ("author", "author"),
+        ("editor", "editor"),
+        ("translator", "translator"),
+    ] {
+        let names = export_names(&item[variable]);
+        if !names.is_empty() {
+            fields.push((field, names));
+        }
+    }
+    for (variable, field) in EXPORTED_FIELDS {
+        // `issue` and `number` are both exported as `number`, so only the first one is kept.
+        if fields.iter().any(|(name, _)| name == field) {
+            continue;
+        }
+        if let Some(value) = text(variable) {
+            let value = match *variable {
+                "page" => escape_text(&value).replace('-', "--"),
+                _ => escape_text(&value),
+            };
+            fields.push((field, value));
+        }
+    }
+    if let Some(container) = text("container-title") {
+        let field = match kind {
+            "article" => "journal",
+            "incollection" | "inproceedings" => "booktitle",
+            _ => "howpublished",
+        };
+        fields.push((field, escape_text(&container)));
+    }
+    if let Some(publisher) = text("publisher") {
+        let field = match kind {
+            "phdthesis" | "mastersthesis" => "school",
+            "techreport" => "institution",
+            _ => "publisher",
+        };
+        fields.push((field, escape_text(&publisher)));
+    }
+    fields.extend(export_date(&item["issued"]));
+    for (variable, field) in EXPORTED_VERBATIM_FIELDS {
+        if let Some(value) = text(variable) {
+            fields.push((field, value));
+        }
+    }
+
+    let mut entry = format!("@{}{{{},\n", kind, key);
+    for (field, value) in fields {
+        // Months are exported as BibTeX macros, which must not be enclosed in braces.
+        match field {
+            "month" => entry.push_str(&format!("  {} = {},\n", field, value)),
+            _ => entry.push_str(&format!("  {} = {{{}}},\n", field, value)),
+        }
+    }
+    entry.push_str("}\n");
+    Some(entry)
+}
+
+/// BibTeX entry type of a CSL item type. The genre distinguishes master's from PhD theses.
+fn entry_type(item_type: &str, genre: Option<&str>) -> &'static str {
+    match item_type {
+        "article" | "article-journal" | "article-magazine" | "article-newspaper" => "article",
+        "book" => "book",
+        "chapter" => "incollection",
+        "paper-conference" => "inproceedings",
+        "thesis" if genre.is_some_and(|genre| genre.to_lowercase().contains("master")) => {
+            "mastersthesis"
+        }
+        "thesis" => "phdthesis",
+        "report" => "techreport",
+        "pamphlet" => "booklet",
+        "manuscript" => "unpublished",
+        _ => "misc",
+    }
+}
+
+/// Converts CSL names into a BibTeX name list in the form `von Last, Jr, First`.
+/// Literal names are enclosed in braces, so BibTeX does not split them.
+fn export_names(names: &Value) -> String {
+    let Some(names) = names.as_array() else {
+        return String::new();
+    };
+
+    names
+        .iter()
+        .filter_map(|name| {
+            let part = |variable: &str| {
+                name[variable]
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|part| !part.is_empty())
+                    .map(escape_text)
+            };
+            if let Some(literal) = part("literal") {
+                return Some(format!("{{{}}}", literal));
+            }
+
+            let family = [part("non-dropping-particle"), part("family")]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let given = [part("given"), part("dropping-particle")]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+            match (family.is_empty(), given.is_empty(), part("suffix")) {
+                (true, true, _) => None,
+                (true, false, _) => Some(given),
+                (false, true, _) => Some(family),
+                (false, false, Some(suffix)) => Some(format!("{}, {}, {}", family, suffix, given)),
+                (false, false, None) => Some(format!("{}, {}", family, given)),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+/// Converts the start of a CSL date into the `year` and `month` fields.
+fn export_date(date: &Value) -> Vec<(&'static str, String)> {
+    let start = date["date-parts"][0].as_array();
+    let part = |index: usize| {
+        start.and_then(|parts| parts.get(index)).and_then(|part| {
+            part.as_u64()
+                .or_else(|| part.as_f64().map(|part| part as u64))
+                .or_else(|| part.as_str().and_then(|part| part.parse().ok()))
+        })
+    };
+
+    match (part(0), part(1)) {
+        (Some(year), Some(month)) if (1..=12).contains(&month) => vec![
+            ("year", year.to_string()),
+            ("month", MONTHS[month as usize - 1].to_string()),
+        ],
+        (Some(year), _) => vec![("year", year.to_string())],
+        _ => match date["literal"].as_str() {
+            Some(literal) => vec![("year", escape_text(literal))],
+            None => vec![],
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BIBTEX: &str = r#"
+@string{ springer = "Springer" }
+@comment{ This entry is skipped. }
+
+@article{mueller2020,
+  author = {M{\"u}ller, J{\"o}rg and Ludwig van Beethoven and {World Health Organization}},
+  title = {The {DNA} of \emph{Homo sapiens}},
+  journal = "Journal of " # springer,
+  year = 2020,
+  month = may,
+  number = {3},
+  pages = {12--34},
+  doi = {10.1000/xyz_123},
+  file = {mueller.pdf},
+}
+
+@book(king1999,
+  author = {King, Jr., Martin Luther and others},
+  title = {Strength to Love},
+  subtitle = {Sermons},
+  date = {1999-04/2001},
+  location = {New York},
+  publisher = springer,
+  institution = {Ignored Institute}
+)
+"#;
+
+    fn field(imported: &ImportedItems, index: usize, variable: &str) -> Value {
+        serde_json::to_value(&imported.items[index]).unwrap()[variable].clone()
+    }
+
+    #[test]
+    fn import_entries() {
+        let imported = import(BIBTEX);
+
+        assert_eq!(imported.items.len(), 2);
+        assert_eq!(
+            field(&imported, 0, "author"),
+            serde_json::json!([
+                { "family": "Müller", "given": "Jörg" },
+                { "family": "Beethoven", "given": "Ludwig", "non-dropping-particle": "van" },
+                { "literal": "World Health Organization" }
+            ])
+        );
+        assert_eq!(field(&imported, 0, "type"), "article-journal");
+        assert_eq!(field(&imported, 0, "title"), "The DNA of Homo sapiens");
+        assert_eq!(
+            field(&imported, 0, "container-title"),
+            "Journal of Springer"
+        );
+        assert_eq!(field(&imported, 0, "issue"), "3");
+        assert_eq!(field(&imported, 0, "page"), "12-34");
+        assert_eq!(field(&imported, 0, "DOI"), "10.1000/xyz_123");
+        assert_eq!(
+            field(&imported, 0, "issued"),
+            serde_json::json!({ "date-parts": [[2020.0, 5.0]] })
+        );
+
+        assert_eq!(
+            field(&imported, 1, "author"),
+            serde_json::json!([{ "family": "King", "given": "Martin Luther", "suffix": "Jr." }])
+        );
+        assert_eq!(field(&imported, 1, "title"), "Strength to Love: Sermons");
+        assert_eq!(field(&imported, 1, "publisher"), "Springer");
+        assert_eq!(field(&imported, 1, "publisher-place"), "New York");
+        assert_eq!(
+            field(&imported, 1, "issued"),
+            serde_json::json!({ "date-parts": [[1999.0, 4.0], [2001.0]] })
+        );
+    }
+
+    #[test]
+    fn warn_about_unmapped_fields() {
+        let imported = import(BIBTEX);
+
+        assert_eq!(
+            imported.warnings,
+            vec![
+                "Field 'file' of entry 'mueller2020' has no CSL counterpart and was ignored",
+                "Field 'institution' of entry 'king1999' has no CSL counterpart and was ignored",
+            ]
+        );
+    }
+
+    #[test]
+    fn warn_about_invalid_entries() {
+        let imported = import("@misc{valid, title = {Valid}}\n\n@book{broken, title = }\n");
+
+        assert_eq!(imported.items.len(), 1);
+        assert_eq!(
+            imported.warnings,
+            vec!["Could not parse the BibTeX entry starting in line 3"]
+        );
+    }
+
+    #[test]
+    fn export_entries() {
+        let exported = export(&import(BIBTEX).items);
+
+        assert_eq!(
+            exported,
+            r#"@article{mueller2020,
+  author = {Müller, Jörg and van Beethoven, Ludwig and {World Health Organization}},
+  title = {The DNA of Homo sapiens},
+  number = {3},
+  pages = {12--34},
+  journal = {Journal of Springer},
+  year = {2020},
+  month = may,
+  doi = {10.1000/xyz_123},
+}
+
+@book{king1999,
+  author = {King, Jr., Martin Luther},
+  title = {Strength to Love: Sermons},
+  address = {New York},
+  publisher = {Springer},
+  year = {1999},
+  month = apr,
+}
+"#
+        );
+    }
+}
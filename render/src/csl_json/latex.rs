@@ -0,0 +1,267 @@
+//! Decoding of the LaTeX markup used in BibTeX field values into plain Unicode text.
+
+/// Accent commands with the letters they can be combined with, the resulting precomposed
+/// characters and the combining character used for all other letters.
+const ACCENTS: &[(&str, &str, &str, char)] = &[
+    (
+        "'",
+        "aeiouyAEIOUYcCnNsSzZrRlLgG",
+        "áéíóúýÁÉÍÓÚÝćĆńŃśŚźŹŕŔĺĹǵǴ",
+        '\u{301}',
+    ),
+    ("`", "aeiouAEIOUnN", "àèìòùÀÈÌÒÙǹǸ", '\u{300}'),
+    (
+        "^",
+        "aeiouAEIOUcCgGhHjJsSwWyY",
+        "âêîôûÂÊÎÔÛĉĈĝĜĥĤĵĴŝŜŵŴŷŶ",
+        '\u{302}',
+    ),
+    ("\"", "aeiouyAEIOUY", "äëïöüÿÄËÏÖÜŸ", '\u{308}'),
+    ("~", "anoANOiuIU", "ãñõÃÑÕĩũĨŨ", '\u{303}'),
+    ("=", "aeiouAEIOU", "āēīōūĀĒĪŌŪ", '\u{304}'),
+    (".", "czeCZEgGI", "ċżėĊŻĖġĠİ", '\u{307}'),
+    ("u", "agAGuUeEiIoO", "ăğĂĞŭŬĕĔĭĬŏŎ", '\u{306}'),
+    ("v", "cCsSzZrRnNeEdDtTlL", "čČšŠžŽřŘňŇěĚďĎťŤľĽ", '\u{30c}'),
+    ("H", "oOuU", "őŐűŰ", '\u{30b}'),
+    ("c", "cCsStTgGkKlLnNrR", "çÇşŞţŢģĢķĶļĻņŅŗŖ", '\u{327}'),
+    ("k", "aAeEiIuU", "ąĄęĘįĮųŲ", '\u{328}'),
+    ("r", "aAuU", "åÅůŮ", '\u{30a}'),
+    ("d", "", "", '\u{323}'),
+    ("b", "", "", '\u{331}'),
+    ("t", "", "", '\u{361}'),
+];
+
+/// Commands that stand for a single character or symbol.
+const SYMBOLS: &[(&str, &str)] = &[
+    ("ss", "ß"),
+    ("SS", "SS"),
+    ("ae", "æ"),
+    ("AE", "Æ"),
+    ("oe", "œ"),
+    ("OE", "Œ"),
+    ("o", "ø"),
+    ("O", "Ø"),
+    ("aa", "å"),
+    ("AA", "Å"),
+    ("l", "ł"),
+    ("L", "Ł"),
+    ("i", "ı"),
+    ("j", "ȷ"),
+    ("dh", "ð"),
+    ("DH", "Ð"),
+    ("th", "þ"),
+    ("TH", "Þ"),
+    ("ng", "ŋ"),
+    ("NG", "Ŋ"),
+    ("S", "§"),
+    ("P", "¶"),
+    ("copyright", "©"),
+    ("textregistered", "®"),
+    ("texttrademark", "™"),
+    ("pounds", "£"),
+    ("euro", "€"),
+    ("textendash", "–"),
+    ("textemdash", "—"),
+    ("textquoteleft", "‘"),
+    ("textquoteright", "’"),
+    ("textquotedblleft", "“"),
+    ("textquotedblright", "”"),
+    ("guillemotleft", "«"),
+    ("guillemotright", "»"),
+    ("ldots", "…"),
+    ("dots", "…"),
+    ("textellipsis", "…"),
+    ("textasciitilde", "~"),
+    ("textbackslash", "\\"),
+    ("textbar", "|"),
+    ("textless", "<"),
+    ("textgreater", ">"),
+    ("textdegree", "°"),
+    ("LaTeX", "LaTeX"),
+    ("TeX", "TeX"),
+];
+
+/// Decodes LaTeX accents, symbol commands and ligatures, and removes braces and formatting
+/// commands while keeping their arguments.
+pub(crate) fn decode(input: &str) -> String {
+    let mut decoder = Decoder {
+        chars: input.chars().collect(),
+        pos: 0,
+    };
+    // Unbalanced closing braces end a group early, so decoding continues after them.
+    let mut decoded = String::new();
+    while decoder.peek().is_some() {
+        decoded.push_str(&decoder.group());
+    }
+    decoded
+        .split(' ')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns `true` if the first letter of the given LaTeX word is lowercase, ignoring words that
+/// start with a brace-protected part.
+pub(crate) fn starts_lowercase(word: &str) -> bool {
+    if word.starts_with('{') && !word.starts_with("{\\") {
+        return false;
+    }
+    decode(word)
+        .chars()
+        .find(|c| c.is_alphabetic())
+        .is_some_and(char::is_lowercase)
+}
+
+struct Decoder {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Decoder {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next_is(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(offset, c)| self.chars.get(self.pos + offset) == Some(&c))
+    }
+
+    /// Decodes text until the end of the current brace group.
+    fn group(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => text.push_str(&self.group()),
+                '}' => break,
+                '\\' => text.push_str(&self.command()),
+                '$' => {}
+                '~' => text.push('\u{a0}'),
+                '-' if self.next_is("--") => {
+                    self.pos += 2;
+                    text.push('—');
+                }
+                '-' if self.next_is("-") => {
+                    self.pos += 1;
+                    text.push('–');
+                }
+                '`' if self.next_is("`") => {
+                    self.pos += 1;
+                    text.push('“');
+                }
+                '`' => text.push('‘'),
+                '\'' if self.next_is("'") => {
+                    self.pos += 1;
+                    text.push('”');
+                }
+                c if c.is_whitespace() => text.push(' '),
+                c => text.push(c),
+            }
+        }
+        text
+    }
+
+    /// Decodes the command following a backslash.
+    fn command(&mut self) -> String {
+        let Some(first) = self.peek() else {
+            return String::new();
+        };
+        self.pos += 1;
+        if !first.is_ascii_alphabetic() {
+            let name = first.to_string();
+            return match first {
+                '&' | '%' | '$' | '#' | '_' | '{' | '}' => name,
+                '\\' | ' ' | ',' | ';' => " ".to_string(),
+                _ if accent(&name).is_some() => self.accented(&name),
+                _ => String::new(),
+            };
+        }
+
+        let mut name = first.to_string();
+        while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+            name.push(c);
+            self.pos += 1;
+        }
+        if name.len() == 1 && accent(&name).is_some() {
+            return self.accented(&name);
+        }
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+        SYMBOLS
+            .iter()
+            .find(|(symbol, _)| *symbol == name)
+            .map(|(_, text)| text.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Applies the given accent to the first character of the following argument.
+    fn accented(&mut self, accent_name: &str) -> String {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+        let argument = match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                self.group()
+            }
+            Some('\\') => {
+                self.pos += 1;
+                self.command()
+            }
+            Some(c) => {
+                self.pos += 1;
+                c.to_string()
+            }
+            None => String::new(),
+        };
+        let mut chars = argument.chars();
+        let Some(base) = chars.next() else {
+            return String::new();
+        };
+        let rest: String = chars.collect();
+        let Some((_, bases, composed, combining)) = accent(accent_name) else {
+            return argument;
+        };
+        // Accents on a dotless i are placed on the regular letter.
+        let base = match base {
+            'ı' => 'i',
+            'ȷ' => 'j',
+            base => base,
+        };
+        match bases.chars().position(|c| c == base) {
+            Some(index) => format!("{}{}", composed.chars().nth(index).unwrap_or(base), rest),
+            None => format!("{}{}{}", base, combining, rest),
+        }
+    }
+}
+
+fn accent(name: &str) -> Option<&'static (&'static str, &'static str, &'static str, char)> {
+    ACCENTS.iter().find(|(accent, ..)| *accent == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_accents() {
+        assert_eq!(decode(r#"M{\"u}ller"#), "Müller");
+        assert_eq!(decode(r"Fran\c{c}ois Ca\~nas"), "François Cañas");
+        assert_eq!(decode(r"\v{S}koda {\'\i}ndice"), "Škoda índice");
+        assert_eq!(decode(r"Stra\ss e Erd\H os"), "Straße Erdős");
+        assert_eq!(decode(r"\d{s}"), "s\u{323}");
+    }
+
+    #[test]
+    fn decode_macros_and_ligatures() {
+        assert_eq!(
+            decode("The {DNA} of \\emph{Homo sapiens} -- a \\textit{study}"),
+            "The DNA of Homo sapiens – a study"
+        );
+        assert_eq!(decode("``Quoted''---and 10\\%"), "“Quoted”—and 10%");
+        assert_eq!(decode("Page~12 \\& $x$"), "Page\u{a0}12 & x");
+    }
+}
@@ -1 +1,39 @@
+pub mod bibtex;
 pub mod csl_types;
+mod latex;
+pub mod ris;
+
+use serde_json::{Map, Value};
+
+use csl_types::CslItem;
+
+/// CSL items converted from a reference file that is not CSL-JSON.
+#[derive(Debug, Default)]
+pub struct ImportedItems {
+    pub items: Vec<CslItem>,
+    /// Messages about entries or fields that could not be converted.
+    pub warnings: Vec<String>,
+}
+
+impl ImportedItems {
+    /// Converts the CSL-JSON fields of the entry with the given key into a [`CslItem`].
+    fn push(&mut self, key: &str, fields: Map<String, Value>) {
+        match serde_json::from_value::<CslItem>(Value::Object(fields)) {
+            Ok(item) => self.items.push(item),
+            Err(err) => self.warnings.push(format!(
+                "Entry '{}' could not be converted to CSL-JSON: {}",
+                key, err
+            )),
+        }
+    }
+}
+
+/// CSL-JSON date with the given date parts, e.g. `[[2020, 5, 12]]`.
+fn date_parts(parts: Vec<Vec<u32>>) -> Value {
+    serde_json::json!({ "date-parts": parts })
+}
+
+/// CSL-JSON date that is given as literal text.
+fn literal_date(literal: &str) -> Value {
+    serde_json::json!({ "literal": literal })
+}
@@ -0,0 +1,361 @@
+//! Conversion of RIS files into CSL items.
+//!
+//! Entries without an `ID` tag get the id `ris-<n>`, where `n` is the position of the entry in
+//! the file starting at 1. Tags without a CSL counterpart are reported as warnings naming the id
+//! of their entry.
+
+use serde_json::{Map, Value};
+
+use super::{date_parts, literal_date, ImportedItems};
+
+/// Converts all entries of the given RIS file content into CSL items.
+pub fn import(input: &str) -> ImportedItems {
+    let mut imported = ImportedItems::default();
+    let mut entry: Option<Vec<(String, String)>> = None;
+    let mut count = 0;
+
+    for line in input.lines() {
+        let Some((tag, value)) = tag_line(line) else {
+            // Lines without a tag continue the value of the previous tag.
+            if let Some((_, value)) = entry.as_mut().and_then(|entry| entry.last_mut()) {
+                if !line.trim().is_empty() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            }
+            continue;
+        };
+        match tag {
+            "TY" => {
+                if entry.is_some() {
+                    imported.warnings.push(format!(
+                        "RIS entry {} is not terminated by 'ER' and was ignored",
+                        count
+                    ));
+                }
+                count += 1;
+                entry = Some(vec![(tag.to_string(), value.to_string())]);
+            }
+            "ER" => match entry.take() {
+                Some(tags) => convert(&tags, count, &mut imported),
+                None => imported
+                    .warnings
+                    .push("RIS file contains an 'ER' tag without an entry".to_string()),
+            },
+            _ => match entry.as_mut() {
+                Some(tags) => tags.push((tag.to_string(), value.to_string())),
+                None => imported
+                    .warnings
+                    .push(format!("RIS tag '{}' outside of an entry was ignored", tag)),
+            },
+        }
+    }
+    if entry.is_some() {
+        imported.warnings.push(format!(
+            "RIS entry {} is not terminated by 'ER' and was ignored",
+            count
+        ));
+    }
+    imported
+}
+
+/// Splits a line of the form `TY  - JOUR` into tag and value.
+fn tag_line(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_start_matches('\u{feff}');
+    let tag = line.get(..2)?;
+    let rest = line.get(2..)?.trim_start_matches(' ');
+    if !tag
+        .chars()
+        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+    {
+        return None;
+    }
+    let value = rest.strip_prefix('-')?;
+    Some((tag, value.trim()))
+}
+
+/// How a RIS tag is converted.
+enum Target {
+    Text(&'static str),
+    /// Text of tags that may occur multiple times, joined by `, `.
+    List(&'static str),
+    Name(&'static str),
+    Date(&'static str),
+    StartPage,
+    EndPage,
+    /// ISBN or ISSN, depending on the item type.
+    SerialNumber,
+    /// Tags that are converted separately.
+    Ignored,
+}
+
+fn target(tag: &str) -> Option<Target> {
+    let target = match tag {
+        "AU" | "A1" => Target::Name("author"),
+        "A2" | "ED" => Target::Name("editor"),
+        "A3" => Target::Name("collection-editor"),
+        "A4" => Target::Name("translator"),
+        "TI" | "T1" => Target::Text("title"),
+        "T2" | "JF" | "BT" => Target::Text("container-title"),
+        "JO" | "JA" | "J2" => Target::Text("container-title-short"),
+        "T3" => Target::Text("collection-title"),
+        "ST" => Target::Text("title-short"),
+        "PY" | "Y1" | "DA" => Target::Date("issued"),
+        "Y2" => Target::Date("accessed"),
+        "VL" => Target::Text("volume"),
+        "IS" => Target::Text("issue"),
+        "SP" => Target::StartPage,
+        "EP" => Target::EndPage,
+        "ET" => Target::Text("edition"),
+        "NV" => Target::Text("number-of-volumes"),
+        "PB" => Target::Text("publisher"),
+        "CY" | "PP" => Target::Text("publisher-place"),
+        "SN" => Target::SerialNumber,
+        "DO" => Target::Text("DOI"),
+        "UR" => Target::Text("URL"),
+        "AB" | "N2" => Target::Text("abstract"),
+        "N1" => Target::List("note"),
+        "KW" => Target::List("keyword"),
+        "LA" => Target::Text("language"),
+        "M3" => Target::Text("genre"),
+        "CN" => Target::Text("call-number"),
+        "DP" | "DB" => Target::Text("source"),
+        "TY" | "ID" => Target::Ignored,
+        _ => return None,
+    };
+    Some(target)
+}
+
+/// CSL item type of a RIS reference type.
+fn item_type(kind: &str) -> Option<&'static str> {
+    let item_type = match kind {
+        "JOUR" | "JFULL" | "EJOUR" => "article-journal",
+        "MGZN" => "article-magazine",
+        "NEWS" => "article-newspaper",
+        "BOOK" | "EBOOK" | "EDBOOK" | "CTLG" => "book",
+        "CHAP" | "ECHAP" => "chapter",
+        "CONF" | "CPAPER" => "paper-conference",
+        "THES" => "thesis",
+        "RPRT" => "report",
+        "ELEC" | "WEB" | "BLOG" => "webpage",
+        "PAT" => "patent",
+        "DATA" => "dataset",
+        "COMP" => "software",
+        "UNPB" | "MANSCPT" => "manuscript",
+        "PAMP" => "pamphlet",
+        "MAP" => "map",
+        "BILL" => "bill",
+        "CASE" => "legal_case",
+        "STAT" => "legislation",
+        "HEAR" => "hearing",
+        "ICOMM" | "PCOMM" => "personal_communication",
+        "MPCT" | "VIDEO" => "motion_picture",
+        "SOUND" => "song",
+        "ART" | "FIGURE" => "graphic",
+        "ENCYC" | "DICT" => "entry-encyclopedia",
+        "STAND" => "standard",
+        "ABST" | "GEN" => "document",
+        _ => return None,
+    };
+    Some(item_type)
+}
+
+fn convert(tags: &[(String, String)], position: usize, imported: &mut ImportedItems) {
+    let tag = |name: &str| {
+        tags.iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let id = tag("ID")
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("ris-{}", position));
+    let kind = tag("TY").unwrap_or_default();
+
+    let mut item = Map::new();
+    item.insert("id".to_string(), Value::from(id.as_str()));
+    let item_type = item_type(kind).unwrap_or_else(|| {
+        imported.warnings.push(format!(
+            "Entry '{}' has the unsupported type '{}' and is imported as 'document'",
+            id, kind
+        ));
+        "document"
+    });
+    item.insert("type".to_string(), Value::from(item_type));
+
+    let (mut start_page, mut end_page) = (None, None);
+    for (tag, value) in tags {
+        if value.is_empty() {
+            continue;
+        }
+        match target(tag) {
+            Some(Target::Text(variable)) => {
+                item.entry(variable)
+                    .or_insert_with(|| Value::from(value.as_str()));
+            }
+            Some(Target::List(variable)) => {
+                let list = match item.get(variable).and_then(Value::as_str) {
+                    Some(list) => format!("{}, {}", list, value),
+                    None => value.clone(),
+                };
+                item.insert(variable.to_string(), Value::from(list));
+            }
+            Some(Target::Name(variable)) => {
+                if let Value::Array(names) = item
+                    .entry(variable)
+                    .or_insert_with(|| Value::Array(Vec::new()))
+                {
+                    names.push(name(value));
+                }
+            }
+            Some(Target::Date(variable)) => {
+                if let Some(date) = date(value) {
+                    item.entry(variable).or_insert(date);
+                }
+            }
+            Some(Target::StartPage) => start_page = Some(value.as_str()),
+            Some(Target::EndPage) => end_page = Some(value.as_str()),
+            Some(Target::SerialNumber) => {
+                let variable = match item_type {
+                    "book" | "chapter" => "ISBN",
+                    _ => "ISSN",
+                };
+                item.insert(variable.to_string(), Value::from(value.as_str()));
+            }
+            Some(Target::Ignored) => {}
+            None => imported.warnings.push(format!(
+                "Tag '{}' of entry '{}' has no CSL counterpart and was ignored",
+                tag, id
+            )),
+        }
+    }
+
+    let page = match (start_page, end_page) {
+        (Some(start), Some(end)) => Some(format!("{}-{}", start, end)),
+        (Some(page), None) | (None, Some(page)) => Some(page.to_string()),
+        (None, None) => None,
+    };
+    if let Some(page) = page {
+        item.insert("page".to_string(), Value::from(page));
+    }
+
+    imported.push(&id, item);
+}
+
+/// Converts a name given as `Last, First, Suffix`.
+fn name(value: &str) -> Value {
+    let mut parts = value.split(',').map(str::trim);
+    let mut name = Map::new();
+    let family = parts.next().unwrap_or_default();
+    match parts.next() {
+        Some(given) => {
+            name.insert("family".to_string(), Value::from(family));
+            if !given.is_empty() {
+                name.insert("given".to_string(), Value::from(given));
+            }
+            if let Some(suffix) = parts.next().filter(|suffix| !suffix.is_empty()) {
+                name.insert("suffix".to_string(), Value::from(suffix));
+            }
+        }
+        None => {
+            name.insert("literal".to_string(), Value::from(family));
+        }
+    }
+    Value::Object(name)
+}
+
+/// Converts a date given as `YYYY/MM/DD/other`, where all parts but the year are optional.
+fn date(value: &str) -> Option<Value> {
+    let mut parts = value.split('/');
+    let year = parts.next()?.trim();
+    let Ok(year) = year.parse::<u32>() else {
+        return Some(literal_date(value));
+    };
+    let mut date = vec![year];
+    for part in parts.take(2) {
+        match part.trim().parse::<u32>() {
+            Ok(number) if number > 0 => date.push(number),
+            _ => break,
+        }
+    }
+    Some(date_parts(vec![date]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RIS: &str = "TY  - JOUR
+ID  - smith2019
+AU  - Smith, Jane
+AU  - Doe, John, Jr.
+TI  - A study of
+  line continuations
+T2  - Journal of Tests
+PY  - 2019/03//
+VL  - 12
+IS  - 4
+SP  - 100
+EP  - 110
+SN  - 1234-5678
+KW  - testing
+KW  - ris
+L1  - file.pdf
+ER  -
+
+TY  - BOOK
+AU  - World Health Organization
+TI  - Report
+PY  - 2001
+SN  - 978-3-16-148410-0
+ER  -
+";
+
+    fn field(imported: &ImportedItems, index: usize, variable: &str) -> Value {
+        serde_json::to_value(&imported.items[index]).unwrap()[variable].clone()
+    }
+
+    #[test]
+    fn import_entries() {
+        let imported = import(RIS);
+
+        assert_eq!(imported.items.len(), 2);
+        assert_eq!(field(&imported, 0, "id"), "smith2019");
+        assert_eq!(field(&imported, 0, "type"), "article-journal");
+        assert_eq!(
+            field(&imported, 0, "author"),
+            serde_json::json!([
+                { "family": "Smith", "given": "Jane" },
+                { "family": "Doe", "given": "John", "suffix": "Jr." }
+            ])
+        );
+        assert_eq!(
+            field(&imported, 0, "title"),
+            "A study of line continuations"
+        );
+        assert_eq!(
+            field(&imported, 0, "issued"),
+            serde_json::json!({ "date-parts": [[2019.0, 3.0]] })
+        );
+        assert_eq!(field(&imported, 0, "page"), "100-110");
+        assert_eq!(field(&imported, 0, "ISSN"), "1234-5678");
+        assert_eq!(field(&imported, 0, "keyword"), "testing, ris");
+
+        assert_eq!(field(&imported, 1, "id"), "ris-2");
+        assert_eq!(
+            field(&imported, 1, "author"),
+            serde_json::json!([{ "literal": "World Health Organization" }])
+        );
+        assert_eq!(field(&imported, 1, "ISBN"), "978-3-16-148410-0");
+    }
+
+    #[test]
+    fn warn_about_unmapped_tags() {
+        let imported = import(RIS);
+
+        assert_eq!(
+            imported.warnings,
+            vec!["Tag 'L1' of entry 'smith2019' has no CSL counterpart and was ignored"]
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Assembles the output of the [`Processor`](super::processor::Processor) into the HTML that is
+//! placed into rendered documents.
+
+use super::processor::{BibliographyEntry, CITE_SEPARATOR};
+use super::style::Bibliography;
+
+const LINK_STYLE: &str = "color: inherit; text-decoration: none";
+
+/// Wraps each cite of a rendered cluster into a link to its bibliography entry.
+pub(crate) fn link_cites(cluster: &str, ids: &[String]) -> String {
+    cluster
+        .split(CITE_SEPARATOR)
+        .enumerate()
+        .map(|(index, cite)| {
+            format!(
+                "<a href=\"#{}\" style=\"{}\">{}</a>",
+                ids.get(index).map(String::as_str).unwrap_or_default(),
+                LINK_STYLE,
+                cite
+            )
+        })
+        .collect()
+}
+
+/// Reference to the footnote with the given number.
+pub(crate) fn footnote_link(number: usize) -> String {
+    format!(
+        "<a href=\"#footnote-{}/\" style=\"{}\">[{}]</a>",
+        number, LINK_STYLE, number
+    )
+}
+
+pub(crate) fn footnote(number: usize, content: &str) -> String {
+    format!(
+        "<div id=\"footnote-{}/\"><div class=\"footnote-left-margin\">[{}]</div> <div class=\"footnote-right-inline\">{}</div></div>",
+        number, number, content
+    )
+}
+
+/// Footnote that is placed by paged.js.
+pub(crate) fn pagedjs_footnote(content: &str) -> String {
+    format!("<span className=\"footnote\">{}</span>", content)
+}
+
+pub(crate) fn footnotes(footnotes: &[String]) -> String {
+    format!(
+        "<div style='margin-left: 4ch'>{}{}</div>",
+        css(&[(
+            ".footnote-left-margin",
+            &[
+                ("text-align", "right"),
+                ("position", "absolute"),
+                ("transform-origin", "top right"),
+                ("transform", "translate(-100%)"),
+                ("margin-left", "-1ch"),
+            ],
+        )]),
+        footnotes.concat()
+    )
+}
+
+pub(crate) fn bibliography(bibliography: &Bibliography, entries: &[BibliographyEntry]) -> String {
+    let padding_bottom = match bibliography.entry_spacing {
+        0 => "0.1em".to_string(),
+        spacing => format!("{}em", spacing),
+    };
+    let line_height = format!("{}em", bibliography.line_spacing);
+    let mut entry = vec![
+        ("padding-bottom", padding_bottom.as_str()),
+        ("line-height", line_height.as_str()),
+    ];
+    if bibliography.hanging_indent {
+        entry.extend([("padding-left", "1.3em"), ("text-indent", "-1.3em")]);
+    }
+
+    let max_offset = entries
+        .iter()
+        .filter_map(|entry| entry.first_field.as_ref())
+        .map(|field| field.chars().count())
+        .max()
+        .unwrap_or_default();
+    let margin_left = format!("{}ch", max_offset);
+    let mut rules: Vec<(&str, &[(&str, &str)])> = vec![(".csl-entry", &entry)];
+    let flush = [("position", "absolute")];
+    let right_inline = [("margin-left", margin_left.as_str())];
+    let margin = [
+        ("text-align", "right"),
+        ("position", "absolute"),
+        ("transform-origin", "top right"),
+        ("transform", "translate(-100%)"),
+        ("margin-left", "-1cm"),
+    ];
+    match bibliography.second_field_align.as_deref() {
+        Some("flush") => {
+            rules.push((".csl-left-margin", &flush));
+            rules.push((".csl-right-inline", &right_inline));
+        }
+        Some(_) => rules.push((".csl-left-margin", &margin)),
+        None => {}
+    }
+
+    let mut html = String::from("<div class=\"csl-bib-body\">\n");
+    html.push_str(&css(&rules));
+    for entry in entries {
+        html.push_str(&format!("<div id=\"{}\">{}</div>", entry.id, entry.html));
+    }
+    html.push_str("</div>");
+    html
+}
+
+fn css(rules: &[(&str, &[(&str, &str)])]) -> String {
+    let mut lines = vec!["<style scoped>".to_string()];
+    for (selector, declarations) in rules {
+        let declarations: String = declarations
+            .iter()
+            .map(|(property, value)| format!("{}:{};", property, value))
+            .collect();
+        lines.push(format!("{}{{{}}}", selector, declarations));
+    }
+    lines.push("</style>".to_string());
+    lines.join("\n")
+}
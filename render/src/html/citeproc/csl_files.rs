@@ -1,8 +1,10 @@
-use crate::log_id::GeneralWarning;
-use logid::log;
+use super::style::Style;
+use super::xml::{self, XmlNode};
+use crate::log_id::{CiteError, GeneralWarning};
+use logid::{log, pipe};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use unimarkup_commons::config::icu_locid::Locale;
 macro_rules! csl_files {
     ($($name:ident, $path:literal)|+) => {
@@ -68,41 +70,137 @@ csl_files!(
     "../../../csl_styles/turabian-author-date.csl"
 );
 
-pub fn get_style_string(path: PathBuf) -> String {
-    if path.is_file() {
-        return match fs::read_to_string(&path) {
-            Ok(csl_style) => csl_style,
-            Err(_) => {
-                log!(
-                    GeneralWarning::FileRead,
-                    format!("Could not read style file: '{:?}'", path.clone()),
-                );
-                IEEE.to_string()
+/// Ids of the bundled styles with their content.
+const BUNDLED_STYLES: &[(&str, &str)] = &[
+    ("american-medical-association", AMERICAN_MEDICAL_ASSOCIATION),
+    ("apa", APA),
+    ("bluebook-inline", BLUEBOOK_INLINE),
+    (
+        "chicago-fullnote-bibliography",
+        CHICAGO_FULLNOTE_BIBLIOGRAPHY,
+    ),
+    ("council-of-science-editors", COUNCIL_OF_SCIENCE_EDITORS),
+    ("harvard-cite-them-right", HARVARD_CITE_THEM_RIGHT),
+    ("ieee", IEEE),
+    ("turabian-author-date", TURABIAN_AUTHOR_DATE),
+];
+
+/// Returns the ids of all bundled styles, which may be used instead of a path to a style file.
+pub fn bundled_style_ids() -> impl Iterator<Item = &'static str> {
+    BUNDLED_STYLES.iter().map(|(id, _)| *id)
+}
+
+fn bundled_style(id: &str) -> Option<&'static str> {
+    BUNDLED_STYLES
+        .iter()
+        .find(|(style_id, _)| *style_id == id)
+        .map(|(_, style)| *style)
+}
+
+/// Loads the given style, which is either the path to a `.csl` file or the id of a bundled style.
+///
+/// Dependent styles are replaced by their parent style, which must be bundled.
+/// The returned style is ensured to be a valid CSL style.
+pub fn load_style(style: &Path) -> Result<String, CiteError> {
+    let text = if style.is_file() {
+        fs::read_to_string(style).map_err(|err| {
+            pipe!(
+                CiteError::UnknownStyle,
+                format!("Could not read style file {:?}: {}", style, err)
+            )
+        })?
+    } else {
+        style
+            .to_str()
+            .and_then(bundled_style)
+            .map(str::to_string)
+            .ok_or_else(|| {
+                pipe!(
+                    CiteError::UnknownStyle,
+                    format!(
+                        "The citation style {:?} is neither a file nor one of the bundled styles: {}",
+                        style,
+                        bundled_style_ids().collect::<Vec<_>>().join(", ")
+                    )
+                )
+            })?
+    };
+
+    let invalid = |err: String| {
+        pipe!(
+            CiteError::InvalidStyle,
+            format!("The citation style {:?} is invalid: {}", style, err)
+        )
+    };
+    let root = xml::parse(&text).map_err(|err| invalid(err.to_string()))?;
+    let text = match independent_parent(&root) {
+        Some(parent) => {
+            let parent_style = bundled_style(parent).ok_or_else(|| {
+                pipe!(
+                    CiteError::UnknownStyle,
+                    format!(
+                        "The parent style '{}' of the dependent style {:?} is not bundled",
+                        parent, style
+                    )
+                )
+            })?;
+            // The locale of the dependent style overrides the one of its parent.
+            match root.get("default-locale") {
+                Some(locale) => with_default_locale(parent_style, locale),
+                None => parent_style.to_string(),
             }
-        };
-    }
-    match path.to_str().unwrap_or("ieee") {
-        "american-medical-association" => AMERICAN_MEDICAL_ASSOCIATION.to_string(),
-        "apa" => APA.to_string(),
-        "bluebook-inline" => BLUEBOOK_INLINE.to_string(),
-        "chicago-fullnote-bibliography" => CHICAGO_FULLNOTE_BIBLIOGRAPHY.to_string(),
-        "council-of-science-editors" => COUNCIL_OF_SCIENCE_EDITORS.to_string(),
-        "harvard-cite-them-right" => HARVARD_CITE_THEM_RIGHT.to_string(),
-        "ieee" => IEEE.to_string(),
-        "turabian-author-date" => TURABIAN_AUTHOR_DATE.to_string(),
-        _ => {
-            log!(
-                GeneralWarning::UnsupportedCslStyle,
-                format!("The csl style '{:?}' is not supported", path.to_str()),
-            );
-            IEEE.to_string()
         }
-    }
+        None => text,
+    };
+
+    Style::parse(&text).map_err(|err| invalid(err.to_string()))?;
+    Ok(text)
+}
+
+/// Id of the parent of a dependent style, taken from its `independent-parent` link.
+fn independent_parent(root: &XmlNode) -> Option<&str> {
+    let href = root
+        .child("info")?
+        .children_named("link")
+        .find(|link| link.attr("rel") == "independent-parent")?
+        .get("href")?;
+    href.trim_end_matches('/').rsplit('/').next()
+}
+
+/// Sets the `default-locale` attribute of the `style` element.
+fn with_default_locale(style: &str, locale: &str) -> String {
+    const ATTRIBUTE: &str = "default-locale=\"";
+
+    let Some(start) = style.find("<style") else {
+        return style.to_string();
+    };
+    let end = start + style[start..].find('>').unwrap_or_default();
+    let tag = &style[start..end];
+    let tag = match tag.find(ATTRIBUTE) {
+        Some(attribute) => {
+            let value_start = attribute + ATTRIBUTE.len();
+            let value_end = value_start + tag[value_start..].find('"').unwrap_or_default();
+            format!("{}{}{}", &tag[..value_start], locale, &tag[value_end..])
+        }
+        None => format!(
+            "<style default-locale=\"{}\"{}",
+            locale,
+            &tag["<style".len()..]
+        ),
+    };
+    format!("{}{}{}", &style[..start], tag, &style[end..])
+}
+
+/// Returns the given style, or the IEEE style if it cannot be loaded.
+pub fn get_style_string(path: PathBuf) -> String {
+    load_style(&path).unwrap_or_else(|_| IEEE.to_string())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::html::citeproc::csl_files::{get_locale_string, get_style_string};
+    use crate::html::citeproc::csl_files::{
+        bundled_style_ids, get_locale_string, get_style_string, load_style,
+    };
     use std::collections::HashMap;
     use std::path::PathBuf;
     use std::str::FromStr;
@@ -165,4 +263,31 @@ mod tests {
         let actual = get_style_string(path);
         assert!(actual.contains("<title>Cite Them Right 12th edition - Harvard</title>"));
     }
+
+    #[test]
+    fn load_style_bundled_and_file() {
+        for id in bundled_style_ids() {
+            assert!(
+                load_style(&PathBuf::from(id)).is_ok(),
+                "Bundled style '{}'",
+                id
+            );
+        }
+        assert!(load_style(&PathBuf::from("./csl_styles/ieee.csl")).is_ok());
+        assert!(load_style(&PathBuf::from("unknown-style")).is_err());
+    }
+
+    #[test]
+    fn load_style_invalid() {
+        let path = PathBuf::from("./src/html/citeproc/test_files/invalid-style.csl");
+        assert!(load_style(&path).is_err());
+    }
+
+    #[test]
+    fn load_dependent_style() {
+        let path = PathBuf::from("./src/html/citeproc/test_files/dependent-style.csl");
+        let actual = load_style(&path).unwrap();
+        assert!(actual.contains("<title>American Psychological Association 7th edition</title>"));
+        assert!(actual.contains("<style default-locale=\"de-DE\" xmlns="));
+    }
 }
@@ -0,0 +1,1239 @@
+//! Evaluation of style elements for a single reference.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+
+use super::locale::Locale;
+use super::output::{self, Inline};
+use super::reference::{DateValue, Name, Reference, SimpleDate};
+use super::style::{
+    Attrs, Condition, Date, DatePart, DatePartName, DemoteParticle, Element, Label, Match,
+    NameOptions, Names, Number, NumberForm, Plural, Position, SortKey, Style, TermForm, TextCase,
+    TextSource,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Mode {
+    Citation,
+    Bibliography,
+    Sort,
+}
+
+/// How far given names are expanded to disambiguate names.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum GivenName {
+    #[default]
+    AsStyled,
+    /// Short names get initials, or full given names if the style sets no initials.
+    Initials,
+    Full,
+}
+
+/// Properties of a cite that are not part of the reference.
+#[derive(Clone, Debug)]
+pub(crate) struct CiteState {
+    pub position: Position,
+    pub near_note: bool,
+    pub locator: Option<String>,
+    pub label: Option<String>,
+    pub citation_number: usize,
+    pub first_reference_note_number: Option<usize>,
+    pub year_suffix: Option<String>,
+    pub disambiguate: bool,
+    /// Number of names shown before et-al, raised to disambiguate cites.
+    pub names_use_first: Option<usize>,
+    pub given_name: GivenName,
+    /// Only the first name of a list is expanded.
+    pub given_name_primary_only: bool,
+    pub suppress_author: bool,
+}
+
+impl Default for CiteState {
+    fn default() -> Self {
+        CiteState {
+            position: Position::First,
+            near_note: false,
+            locator: None,
+            label: None,
+            citation_number: 0,
+            first_reference_note_number: None,
+            year_suffix: None,
+            disambiguate: false,
+            names_use_first: None,
+            given_name: GivenName::AsStyled,
+            given_name_primary_only: false,
+            suppress_author: false,
+        }
+    }
+}
+
+pub(crate) struct Evaluator<'a> {
+    style: &'a Style,
+    locale: &'a Locale,
+    reference: &'a Reference,
+    state: &'a CiteState,
+    mode: Mode,
+    name_options: NameOptions,
+    english: bool,
+    sort_key: Option<&'a SortKey>,
+    /// Variables that were used as substitute and are not rendered again.
+    suppressed: RefCell<HashSet<String>>,
+    /// Variables rendered so far, in order.
+    rendered: RefCell<Vec<String>>,
+    year_suffix_done: Cell<bool>,
+    author_done: Cell<bool>,
+    /// Number of variables called and found, to decide if groups are suppressed.
+    called: Cell<usize>,
+    found: Cell<usize>,
+}
+
+impl<'a> Evaluator<'a> {
+    pub(crate) fn new(
+        style: &'a Style,
+        locale: &'a Locale,
+        reference: &'a Reference,
+        state: &'a CiteState,
+        mode: Mode,
+        lang: &str,
+    ) -> Self {
+        let context_options = match mode {
+            Mode::Bibliography => style
+                .bibliography
+                .as_ref()
+                .map(|bibliography| bibliography.name_options.clone())
+                .unwrap_or_default(),
+            _ => style.citation.name_options.clone(),
+        };
+        let english = lang.starts_with("en")
+            && reference
+                .strings
+                .get("language")
+                .is_none_or(|language| language.starts_with("en"));
+        Evaluator {
+            style,
+            locale,
+            reference,
+            state,
+            mode,
+            name_options: style.name_options.merge(&context_options),
+            english,
+            sort_key: None,
+            suppressed: RefCell::default(),
+            rendered: RefCell::default(),
+            year_suffix_done: Cell::new(false),
+            author_done: Cell::new(false),
+            called: Cell::new(0),
+            found: Cell::new(0),
+        }
+    }
+
+    /// Uses the name options of the bibliography, for sorting the bibliography.
+    pub(crate) fn in_bibliography(mut self) -> Self {
+        if let Some(bibliography) = &self.style.bibliography {
+            self.name_options = self.style.name_options.merge(&bibliography.name_options);
+        }
+        self
+    }
+
+    /// Renders the elements of a layout, without the affixes and formatting of the layout.
+    pub(crate) fn render_layout(&self, elements: &[Element]) -> Vec<Inline> {
+        self.render_children(elements, "")
+    }
+
+    /// Renders each element of a layout on its own.
+    pub(crate) fn render_layout_parts(&self, elements: &[Element]) -> Vec<Vec<Inline>> {
+        elements
+            .iter()
+            .map(|element| self.render_element(element))
+            .filter(|nodes| !output::is_empty(nodes))
+            .collect()
+    }
+
+    /// Value of a sort key for the reference.
+    pub(crate) fn sort_value(&mut self, key: &'a SortKey) -> String {
+        self.sort_key = Some(key);
+        match &key.source {
+            super::style::SortSource::Macro(name) => match self.style.macros.get(name) {
+                Some(elements) => output::plain_text(&self.render_children(elements, "")),
+                None => String::new(),
+            },
+            super::style::SortSource::Variable(name) => {
+                if self.reference.names.contains_key(name) {
+                    let names = Names {
+                        variables: vec![name.clone()],
+                        name: None,
+                        et_al: None,
+                        label: None,
+                        label_before_name: false,
+                        substitute: None,
+                        delimiter: None,
+                        attrs: Attrs::default(),
+                    };
+                    output::plain_text(&self.render_names(&names))
+                } else if let Some(date) = self.reference.dates.get(name) {
+                    date_sort_key(date)
+                } else if name == "citation-number" {
+                    format!("{:08}", self.state.citation_number)
+                } else {
+                    let value = self.reference.string(name, false).unwrap_or_default();
+                    if value.chars().all(|c| c.is_ascii_digit()) && !value.is_empty() {
+                        format!("{:0>12}", value)
+                    } else {
+                        value.to_string()
+                    }
+                }
+            }
+        }
+    }
+
+    fn render_children(&self, elements: &[Element], delimiter: &str) -> Vec<Inline> {
+        let mut nodes = Vec::new();
+        for element in elements {
+            let rendered = self.render_element(element);
+            if output::is_empty(&rendered) {
+                continue;
+            }
+            if !nodes.is_empty() && !delimiter.is_empty() {
+                nodes.push(Inline::Text(delimiter.to_string()));
+            }
+            nodes.extend(rendered);
+        }
+        nodes
+    }
+
+    fn render_element(&self, element: &Element) -> Vec<Inline> {
+        match element {
+            Element::Text(text) => self.render_text(&text.source, &text.attrs),
+            Element::Number(number) => self.render_number(number),
+            Element::Label(label) => self.render_label(label),
+            Element::Names(names) => self.render_names(names),
+            Element::Date(date) => self.render_date(date),
+            Element::Group(group) => {
+                let (called, found) = (self.called.get(), self.found.get());
+                let nodes = self.render_children(&group.children, &group.delimiter);
+                if self.called.get() > called && self.found.get() == found {
+                    return Vec::new();
+                }
+                self.wrap(nodes, &group.attrs)
+            }
+            Element::Choose(branches) => {
+                for branch in branches {
+                    let matches = match &branch.conditions {
+                        None => true,
+                        Some((mode, conditions)) => self.test_conditions(*mode, conditions),
+                    };
+                    if matches {
+                        return self.render_children(&branch.children, "");
+                    }
+                }
+                Vec::new()
+            }
+        }
+    }
+
+    /// Applies text case, quotes, formatting, affixes and display to rendered output.
+    fn wrap(&self, mut nodes: Vec<Inline>, attrs: &Attrs) -> Vec<Inline> {
+        if output::is_empty(&nodes) {
+            return Vec::new();
+        }
+        if let Some(case) = attrs.text_case {
+            if self.english || !matches!(case, TextCase::Title) {
+                output::apply_text_case(&mut nodes, case);
+            }
+        }
+        if self.mode == Mode::Sort {
+            return nodes;
+        }
+        if attrs.quotes {
+            nodes = vec![Inline::Quoted(nodes)];
+        }
+        if !attrs.formatting.is_empty() {
+            nodes = vec![Inline::Fmt(attrs.formatting.clone(), nodes)];
+        }
+        if !attrs.prefix.is_empty() {
+            nodes.insert(0, Inline::Text(attrs.prefix.clone()));
+        }
+        if !attrs.suffix.is_empty() {
+            nodes.push(Inline::Text(attrs.suffix.clone()));
+        }
+        if let Some(display) = &attrs.display {
+            nodes = vec![Inline::Display(display.clone(), nodes)];
+        }
+        nodes
+    }
+
+    fn text(&self, value: &str, attrs: &Attrs) -> Vec<Inline> {
+        let value = if attrs.strip_periods {
+            value.replace('.', "")
+        } else {
+            value.to_string()
+        };
+        self.wrap(vec![Inline::Text(value)], attrs)
+    }
+
+    fn mark_found(&self, variable: &str) {
+        self.found.set(self.found.get() + 1);
+        self.rendered.borrow_mut().push(variable.to_string());
+    }
+
+    fn is_suppressed(&self, variable: &str) -> bool {
+        self.suppressed.borrow().contains(variable)
+    }
+
+    /// Value of an ordinary or cite-specific variable.
+    fn variable(&self, name: &str, short: bool) -> Option<String> {
+        if self.is_suppressed(name) {
+            return None;
+        }
+        let value = match name {
+            "citation-number" => Some(self.state.citation_number.to_string())
+                .filter(|_| self.state.citation_number > 0),
+            // Sort keys are computed before year-suffixes are assigned.
+            "year-suffix" if self.mode == Mode::Sort => None,
+            "year-suffix" => self.state.year_suffix.clone(),
+            "locator" => self.state.locator.clone().map(|locator| {
+                if self.state.label.as_deref().unwrap_or("page") == "page" {
+                    self.format_page_range(&locator)
+                } else {
+                    locator
+                }
+            }),
+            "first-reference-note-number" => self
+                .state
+                .first_reference_note_number
+                .map(|number| number.to_string()),
+            "page" => self
+                .reference
+                .string(name, false)
+                .map(|page| self.format_page_range(page)),
+            _ => self.reference.string(name, short).map(str::to_string),
+        };
+        value.filter(|value| !value.is_empty())
+    }
+
+    fn render_text(&self, source: &TextSource, attrs: &Attrs) -> Vec<Inline> {
+        match source {
+            TextSource::Variable { name, short } => {
+                self.called.set(self.called.get() + 1);
+                let Some(value) = self.variable(name, *short) else {
+                    return Vec::new();
+                };
+                if name == "year-suffix" {
+                    self.year_suffix_done.set(true);
+                }
+                self.mark_found(name);
+                self.text(&value, attrs)
+            }
+            TextSource::Macro(name) => match self.style.macros.get(name) {
+                Some(elements) => {
+                    let nodes = self.render_children(elements, "");
+                    self.wrap(nodes, attrs)
+                }
+                None => Vec::new(),
+            },
+            TextSource::Term { name, form, plural } => {
+                match self.locale.term(name, *form, *plural) {
+                    Some(term) if !term.is_empty() => self.text(term, attrs),
+                    _ => Vec::new(),
+                }
+            }
+            TextSource::Value(value) => self.text(value, attrs),
+        }
+    }
+
+    fn render_number(&self, number: &Number) -> Vec<Inline> {
+        self.called.set(self.called.get() + 1);
+        let Some(value) = self.variable(&number.variable, false) else {
+            return Vec::new();
+        };
+        self.mark_found(&number.variable);
+        if !is_numeric(&value) {
+            return self.text(&value, &number.attrs);
+        }
+
+        let gender = self.locale.gender(&number.variable);
+        let mut formatted = String::new();
+        for token in tokenize_numbers(&value) {
+            match token {
+                NumberToken::Number(digits) => {
+                    let number_value: i64 = digits.parse().unwrap_or_default();
+                    match number.form {
+                        NumberForm::Numeric => formatted.push_str(&digits),
+                        NumberForm::Ordinal => {
+                            formatted.push_str(&digits);
+                            formatted.push_str(&self.locale.ordinal_suffix(number_value, gender));
+                        }
+                        NumberForm::LongOrdinal => {
+                            formatted.push_str(&self.locale.long_ordinal(number_value, gender))
+                        }
+                        NumberForm::Roman => formatted.push_str(&roman(number_value)),
+                    }
+                }
+                NumberToken::Other(text) => formatted.push_str(&text),
+            }
+        }
+        self.text(&formatted, &number.attrs)
+    }
+
+    fn render_label(&self, label: &Label) -> Vec<Inline> {
+        let Some(value) = self.variable(&label.variable, false) else {
+            return Vec::new();
+        };
+        let term = match label.variable.as_str() {
+            "locator" => self
+                .state
+                .label
+                .clone()
+                .unwrap_or_else(|| "page".to_string()),
+            "number-of-pages" => "page".to_string(),
+            "chapter-number" => "chapter".to_string(),
+            "collection-number" | "number-of-volumes" => "volume".to_string(),
+            variable => variable.to_string(),
+        };
+        let plural = match label.plural {
+            Plural::Always => true,
+            Plural::Never => false,
+            Plural::Contextual if label.variable == "number-of-pages" => {
+                value.trim().parse::<i64>().is_ok_and(|pages| pages > 1)
+            }
+            Plural::Contextual => has_multiple_numbers(&value),
+        };
+        match self.locale.term(&term, label.form, plural) {
+            Some(text) if !text.is_empty() => self.text(text, &label.attrs),
+            _ => Vec::new(),
+        }
+    }
+
+    fn names_label(&self, label: &Label, variable: &str, count: usize) -> Vec<Inline> {
+        let plural = match label.plural {
+            Plural::Always => true,
+            Plural::Never => false,
+            Plural::Contextual => count > 1,
+        };
+        match self.locale.term(variable, label.form, plural) {
+            Some(text) if !text.is_empty() => self.text(text, &label.attrs),
+            _ => Vec::new(),
+        }
+    }
+
+    fn render_names(&self, names: &Names) -> Vec<Inline> {
+        self.called.set(self.called.get() + 1);
+        let name_element = names.name.clone().unwrap_or_default();
+        let options = self.name_options.merge(&name_element.options);
+        let count_only = options.form.as_deref() == Some("count");
+
+        let mut parts: Vec<Vec<Inline>> = Vec::new();
+        let mut count = 0;
+        for variable in &names.variables {
+            if self.is_suppressed(variable) {
+                continue;
+            }
+            let Some(list) = self.reference.names.get(variable) else {
+                continue;
+            };
+            let (mut nodes, shown) = self.render_name_list(list, names, &options);
+            if output::is_empty(&nodes) && !count_only {
+                continue;
+            }
+            count += shown;
+            self.rendered.borrow_mut().push(variable.clone());
+            if let Some(label) = &names.label {
+                if self.mode != Mode::Sort {
+                    let label_nodes = self.names_label(label, variable, list.len());
+                    if names.label_before_name {
+                        nodes.splice(0..0, label_nodes);
+                    } else {
+                        nodes.extend(label_nodes);
+                    }
+                }
+            }
+            parts.push(nodes);
+        }
+
+        let mut nodes = Vec::new();
+        if count_only {
+            if count > 0 {
+                nodes.push(Inline::Text(count.to_string()));
+            }
+        } else if parts.is_empty() {
+            if let Some(substitute) = &names.substitute {
+                nodes = self.render_substitute(names, substitute);
+            }
+        } else {
+            let delimiter = names
+                .delimiter
+                .clone()
+                .or_else(|| options.names_delimiter.clone())
+                .unwrap_or_default();
+            for (index, part) in parts.into_iter().enumerate() {
+                if index > 0 {
+                    nodes.push(Inline::Text(delimiter.clone()));
+                }
+                nodes.extend(part);
+            }
+        }
+
+        if output::is_empty(&nodes) {
+            return Vec::new();
+        }
+        if !self.author_done.replace(true) && self.state.suppress_author {
+            return Vec::new();
+        }
+        self.found.set(self.found.get() + 1);
+        self.wrap(vec![Inline::Names(nodes)], &names.attrs)
+    }
+
+    /// Renders the first substitute that produces output and suppresses the variables it used.
+    fn render_substitute(&self, names: &Names, substitute: &[Element]) -> Vec<Inline> {
+        for element in substitute {
+            let before = self.rendered.borrow().len();
+            let nodes = match element {
+                Element::Names(inner) if inner.name.is_none() => {
+                    let inherited = Names {
+                        name: names.name.clone(),
+                        et_al: inner.et_al.clone().or_else(|| names.et_al.clone()),
+                        label: inner.label.clone().or_else(|| names.label.clone()),
+                        label_before_name: if inner.label.is_some() {
+                            inner.label_before_name
+                        } else {
+                            names.label_before_name
+                        },
+                        substitute: None,
+                        ..(**inner).clone()
+                    };
+                    self.render_names_without_marker(&inherited)
+                }
+                Element::Names(inner) => {
+                    let inner = Names {
+                        substitute: None,
+                        ..(**inner).clone()
+                    };
+                    self.render_names_without_marker(&inner)
+                }
+                element => self.render_element(element),
+            };
+            if !output::is_empty(&nodes) {
+                let rendered = self.rendered.borrow()[before..].to_vec();
+                self.suppressed.borrow_mut().extend(rendered);
+                return nodes;
+            }
+        }
+        Vec::new()
+    }
+
+    /// Renders substituted names, which count as the names of the enclosing element.
+    fn render_names_without_marker(&self, names: &Names) -> Vec<Inline> {
+        let author_done = self.author_done.get();
+        let called = self.called.get();
+        let nodes = self.render_names(names);
+        self.author_done.set(author_done);
+        self.called.set(called);
+        if !output::is_empty(&nodes) {
+            self.found.set(self.found.get() - 1);
+        }
+        match nodes.as_slice() {
+            [Inline::Names(children)] => children.clone(),
+            _ => nodes,
+        }
+    }
+
+    fn render_name_list(
+        &self,
+        list: &[Name],
+        names: &Names,
+        options: &NameOptions,
+    ) -> (Vec<Inline>, usize) {
+        let total = list.len();
+        let subsequent = self.mode == Mode::Citation && self.state.position != Position::First;
+        let (mut min, mut use_first) = if subsequent && options.et_al_subsequent_min.is_some() {
+            (
+                options.et_al_subsequent_min,
+                options
+                    .et_al_subsequent_use_first
+                    .or(options.et_al_use_first),
+            )
+        } else {
+            (options.et_al_min, options.et_al_use_first)
+        };
+        let mut use_last = options.et_al_use_last == Some(true);
+        if let Some(key) = self.sort_key {
+            min = key.names_min.or(min);
+            use_first = key.names_use_first.or(use_first);
+            use_last = key.names_use_last.unwrap_or(use_last);
+        }
+
+        let mut shown = total;
+        if let (Some(min), Some(first)) = (min, use_first) {
+            if total >= min && first < total {
+                shown = first;
+            }
+        }
+        if let Some(extra) = self.state.names_use_first {
+            if self.mode == Mode::Citation && extra > shown {
+                shown = extra.min(total);
+            }
+        }
+        let truncated = shown < total;
+        let use_last = use_last && truncated && shown >= 1 && total >= shown + 2;
+        if shown == 0 {
+            return (Vec::new(), 0);
+        }
+
+        let name_element = names.name.clone().unwrap_or_default();
+        let delimiter = options.name_delimiter.clone().unwrap_or(", ".to_string());
+        let and = match options.and.as_deref() {
+            Some("text") => self
+                .locale
+                .term("and", TermForm::Long, false)
+                .map(str::to_string),
+            Some("symbol") => Some("&".to_string()),
+            _ => None,
+        };
+        let inverted: Vec<bool> = (0..shown)
+            .map(|index| {
+                self.mode == Mode::Sort
+                    || match options.name_as_sort_order.as_deref() {
+                        Some("all") => true,
+                        Some("first") => index == 0,
+                        _ => false,
+                    }
+            })
+            .collect();
+
+        let mut nodes = Vec::new();
+        for index in 0..shown {
+            if index > 0 {
+                if index + 1 == shown && !truncated && and.is_some() {
+                    let and = and.as_deref().unwrap_or_default();
+                    let use_delimiter = match options.delimiter_precedes_last.as_deref() {
+                        Some("always") => true,
+                        Some("never") => false,
+                        Some("after-inverted-name") => inverted[index - 1],
+                        _ => shown >= 3,
+                    };
+                    nodes.push(Inline::Text(if use_delimiter {
+                        format!("{}{} ", delimiter, and)
+                    } else {
+                        format!(" {} ", and)
+                    }));
+                } else {
+                    nodes.push(Inline::Text(delimiter.clone()));
+                }
+            }
+            nodes.extend(self.format_name(
+                &list[index],
+                index,
+                inverted[index],
+                options,
+                &name_element,
+            ));
+        }
+
+        if use_last {
+            nodes.push(Inline::Text(format!("{}… ", delimiter)));
+            nodes.extend(self.format_name(
+                &list[total - 1],
+                total - 1,
+                inverted[0] && options.name_as_sort_order.as_deref() == Some("all"),
+                options,
+                &name_element,
+            ));
+        } else if truncated && self.mode != Mode::Sort {
+            let term_name = names
+                .et_al
+                .as_ref()
+                .map_or("et-al", |et_al| et_al.term.as_str());
+            if let Some(term) = self.locale.term(term_name, TermForm::Long, false) {
+                if !term.is_empty() {
+                    let use_delimiter = match options.delimiter_precedes_et_al.as_deref() {
+                        Some("always") => true,
+                        Some("never") => false,
+                        Some("after-inverted-name") => inverted[shown - 1],
+                        _ => shown >= 2,
+                    };
+                    nodes.push(Inline::Text(if use_delimiter {
+                        delimiter.clone()
+                    } else {
+                        " ".to_string()
+                    }));
+                    let attrs = names
+                        .et_al
+                        .as_ref()
+                        .map(|et_al| et_al.attrs.clone())
+                        .unwrap_or_default();
+                    nodes.extend(self.text(term, &attrs));
+                }
+            }
+        }
+        (nodes, shown)
+    }
+
+    fn format_name(
+        &self,
+        name: &Name,
+        index: usize,
+        inverted: bool,
+        options: &NameOptions,
+        element: &super::style::Name,
+    ) -> Vec<Inline> {
+        if name.is_literal() {
+            return self.wrap(vec![Inline::Text(name.literal.clone())], &element.attrs);
+        }
+
+        let mut long = options.form.as_deref() != Some("short");
+        let mut initialize_with = options.initialize_with.clone();
+        let expand = self.mode != Mode::Sort && (!self.state.given_name_primary_only || index == 0);
+        if expand {
+            match self.state.given_name {
+                GivenName::AsStyled => {}
+                GivenName::Initials if !long => long = true,
+                GivenName::Initials | GivenName::Full => {
+                    long = true;
+                    initialize_with = None;
+                }
+            }
+        }
+
+        let family_particle = |with_particle: bool| {
+            if with_particle && !name.non_dropping_particle.is_empty() {
+                format!("{} {}", name.non_dropping_particle, name.family)
+            } else {
+                name.family.clone()
+            }
+        };
+
+        if !long || name.given.is_empty() {
+            let family = self.wrap(vec![Inline::Text(family_particle(true))], &element.family);
+            return self.wrap(family, &element.attrs);
+        }
+
+        let given = match &initialize_with {
+            Some(with) if options.initialize != Some(false) => {
+                initialize(&name.given, with, self.style.initialize_with_hyphen)
+            }
+            _ => name.given.clone(),
+        };
+
+        let mut nodes = Vec::new();
+        if inverted {
+            let demote = match self.mode {
+                Mode::Sort => self.style.demote_non_dropping_particle != DemoteParticle::Never,
+                _ => self.style.demote_non_dropping_particle == DemoteParticle::DisplayAndSort,
+            };
+            let separator = options.sort_separator.clone().unwrap_or(", ".to_string());
+            let mut given_part = given;
+            if !name.dropping_particle.is_empty() {
+                given_part = format!("{} {}", given_part, name.dropping_particle);
+            }
+            if demote && !name.non_dropping_particle.is_empty() {
+                given_part = format!("{} {}", given_part, name.non_dropping_particle);
+            }
+            nodes.extend(self.wrap(
+                vec![Inline::Text(family_particle(!demote))],
+                &element.family,
+            ));
+            nodes.push(Inline::Text(separator.clone()));
+            nodes.extend(self.wrap(vec![Inline::Text(given_part)], &element.given));
+            if !name.suffix.is_empty() {
+                nodes.push(Inline::Text(format!("{}{}", separator, name.suffix)));
+            }
+        } else {
+            let mut given_part = given;
+            if !name.dropping_particle.is_empty() {
+                given_part = format!("{} {}", given_part, name.dropping_particle);
+            }
+            nodes.extend(self.wrap(vec![Inline::Text(given_part)], &element.given));
+            nodes.push(Inline::Text(" ".to_string()));
+            nodes.extend(self.wrap(vec![Inline::Text(family_particle(true))], &element.family));
+            if !name.suffix.is_empty() {
+                let separator = if name.comma_suffix { ", " } else { " " };
+                nodes.push(Inline::Text(format!("{}{}", separator, name.suffix)));
+            }
+        }
+        self.wrap(nodes, &element.attrs)
+    }
+
+    fn render_date(&self, date: &Date) -> Vec<Inline> {
+        self.called.set(self.called.get() + 1);
+        if self.is_suppressed(&date.variable) {
+            return Vec::new();
+        }
+        let Some(value) = self.reference.dates.get(&date.variable) else {
+            return Vec::new();
+        };
+        self.mark_found(&date.variable);
+        if self.mode == Mode::Sort {
+            // Only the parts shown by the date element take part in sorting.
+            let (parts, _) = self.date_parts(date);
+            let shown = |name| parts.iter().any(|part| part.name == name);
+            let mut value = value.clone();
+            for date in value.start.iter_mut().chain(value.end.iter_mut()) {
+                if !shown(DatePartName::Month) {
+                    date.month = 0;
+                }
+                if !shown(DatePartName::Day) {
+                    date.day = 0;
+                }
+            }
+            return vec![Inline::Text(date_sort_key(&value))];
+        }
+        let Some(start) = value.start else {
+            return self.text(value.literal.as_deref().unwrap_or_default(), &date.attrs);
+        };
+
+        let (parts, delimiter) = self.date_parts(date);
+        let available = |part: &DatePart, date: &SimpleDate| match part.name {
+            DatePartName::Year => true,
+            DatePartName::Month => date.month > 0,
+            DatePartName::Day => date.month > 0 && date.day > 0,
+        };
+        let parts: Vec<&DatePart> = parts
+            .iter()
+            .filter(|part| available(part, &start))
+            .collect();
+
+        let mut nodes = Vec::new();
+        let push = |nodes: &mut Vec<Inline>, rendered: Vec<Inline>| {
+            if !output::is_empty(&rendered) {
+                if !nodes.is_empty() && !delimiter.is_empty() {
+                    nodes.push(Inline::Text(delimiter.clone()));
+                }
+                nodes.extend(rendered);
+            }
+        };
+
+        let range_level = value.end.and_then(|end| {
+            if end.year != start.year {
+                Some(DatePartName::Year)
+            } else if end.month != start.month && end.month > 0 {
+                Some(DatePartName::Month)
+            } else if end.day != start.day && end.day > 0 {
+                Some(DatePartName::Day)
+            } else {
+                None
+            }
+        });
+
+        match (range_level, value.end) {
+            (Some(level), Some(end)) => {
+                let in_range: Vec<bool> = parts.iter().map(|part| part.name <= level).collect();
+                let first = in_range.iter().position(|&flag| flag).unwrap_or(0);
+                let last = in_range.iter().rposition(|&flag| flag).unwrap_or(0);
+                let range_delimiter = parts
+                    .iter()
+                    .find(|part| part.name == level)
+                    .and_then(|part| part.range_delimiter.clone())
+                    .unwrap_or("–".to_string());
+                for part in &parts[..first] {
+                    push(&mut nodes, self.render_date_part(part, &start, None));
+                }
+                let mut range = Vec::new();
+                for (offset, part) in parts[first..=last].iter().enumerate() {
+                    let affixes = if offset + first == last {
+                        Some((true, false))
+                    } else {
+                        None
+                    };
+                    push(&mut range, self.render_date_part(part, &start, affixes));
+                }
+                range.push(Inline::Text(range_delimiter));
+                let mut end_nodes = Vec::new();
+                for (offset, part) in parts[first..=last].iter().enumerate() {
+                    let affixes = if offset == 0 {
+                        Some((false, true))
+                    } else {
+                        None
+                    };
+                    push(&mut end_nodes, self.render_date_part(part, &end, affixes));
+                }
+                range.extend(end_nodes);
+                push(&mut nodes, range);
+                for part in &parts[last + 1..] {
+                    push(&mut nodes, self.render_date_part(part, &start, None));
+                }
+            }
+            _ => {
+                for part in &parts {
+                    push(&mut nodes, self.render_date_part(part, &start, None));
+                }
+            }
+        }
+        self.wrap(nodes, &date.attrs)
+    }
+
+    /// Date parts to render and their delimiter, taking localized date formats into account.
+    fn date_parts(&self, date: &Date) -> (Vec<DatePart>, String) {
+        let Some(localized) = date
+            .form
+            .as_ref()
+            .and_then(|form| self.locale.date_formats.get(form))
+        else {
+            return (date.parts.clone(), date.delimiter.clone());
+        };
+        let parts = localized
+            .parts
+            .iter()
+            .filter(|part| part.name >= date.date_parts)
+            .map(|part| {
+                let mut part = part.clone();
+                if let Some(custom) = date.parts.iter().find(|custom| custom.name == part.name) {
+                    if custom.form.is_some() {
+                        part.form = custom.form.clone();
+                    }
+                    if custom.range_delimiter.is_some() {
+                        part.range_delimiter = custom.range_delimiter.clone();
+                    }
+                    if !custom.attrs.formatting.is_empty() {
+                        part.attrs.formatting = custom.attrs.formatting.clone();
+                    }
+                    if custom.attrs.text_case.is_some() {
+                        part.attrs.text_case = custom.attrs.text_case;
+                    }
+                    part.attrs.strip_periods |= custom.attrs.strip_periods;
+                }
+                part
+            })
+            .collect();
+        (parts, localized.delimiter.clone())
+    }
+
+    /// Renders one date part, where `drop_affixes` removes the (suffix, prefix) next to a range delimiter.
+    fn render_date_part(
+        &self,
+        part: &DatePart,
+        date: &SimpleDate,
+        drop_affixes: Option<(bool, bool)>,
+    ) -> Vec<Inline> {
+        let mut attrs = part.attrs.clone();
+        if let Some((suffix, prefix)) = drop_affixes {
+            if suffix {
+                attrs.suffix.clear();
+            }
+            if prefix {
+                attrs.prefix.clear();
+            }
+        }
+        let form = part.form.as_deref();
+        let text = match part.name {
+            DatePartName::Year => {
+                let mut year = if date.year < 0 {
+                    format!(
+                        "{}{}",
+                        -date.year,
+                        self.locale
+                            .term("bc", TermForm::Long, false)
+                            .unwrap_or_default()
+                    )
+                } else if form == Some("short") {
+                    format!("{:02}", date.year % 100)
+                } else if date.year < 1000 {
+                    format!(
+                        "{}{}",
+                        date.year,
+                        self.locale
+                            .term("ad", TermForm::Long, false)
+                            .unwrap_or_default()
+                    )
+                } else {
+                    date.year.to_string()
+                };
+                if !self.style.renders_year_suffix && !self.year_suffix_done.get() {
+                    if let Some(suffix) = &self.state.year_suffix {
+                        self.year_suffix_done.set(true);
+                        year.push_str(suffix);
+                    }
+                }
+                year
+            }
+            DatePartName::Month if date.month > 12 => self
+                .locale
+                .term(
+                    &format!("season-{:02}", date.month - 12),
+                    TermForm::Long,
+                    false,
+                )
+                .unwrap_or_default()
+                .to_string(),
+            DatePartName::Month => match form {
+                Some("numeric") => date.month.to_string(),
+                Some("numeric-leading-zeros") => format!("{:02}", date.month),
+                _ => {
+                    let term_form = if form == Some("short") {
+                        TermForm::Short
+                    } else {
+                        TermForm::Long
+                    };
+                    self.locale
+                        .term(&format!("month-{:02}", date.month), term_form, false)
+                        .map(str::to_string)
+                        .unwrap_or_else(|| date.month.to_string())
+                }
+            },
+            DatePartName::Day => match form {
+                Some("numeric-leading-zeros") => format!("{:02}", date.day),
+                Some("ordinal") if !self.locale.limit_day_ordinals_to_day_1 || date.day == 1 => {
+                    let gender = self.locale.gender(&format!("month-{:02}", date.month));
+                    format!(
+                        "{}{}",
+                        date.day,
+                        self.locale.ordinal_suffix(i64::from(date.day), gender)
+                    )
+                }
+                _ => date.day.to_string(),
+            },
+        };
+        self.text(&text, &attrs)
+    }
+
+    fn test_conditions(&self, mode: Match, conditions: &[Condition]) -> bool {
+        let mut results = conditions
+            .iter()
+            .map(|condition| self.test_condition(condition));
+        match mode {
+            Match::All => results.all(|result| result),
+            Match::Any => results.any(|result| result),
+            Match::None => !results.any(|result| result),
+        }
+    }
+
+    fn test_condition(&self, condition: &Condition) -> bool {
+        match condition {
+            Condition::Type(kind) => self.reference.kind == *kind,
+            Condition::Variable(name) => match name.as_str() {
+                "locator" => self.state.locator.is_some(),
+                "year-suffix" => self.state.year_suffix.is_some(),
+                "citation-number" => self.state.citation_number > 0,
+                "first-reference-note-number" => self.state.first_reference_note_number.is_some(),
+                name => self.reference.has_variable(name),
+            },
+            Condition::IsNumeric(name) => {
+                let value = match name.as_str() {
+                    "locator" => self.state.locator.clone(),
+                    name => self.reference.string(name, false).map(str::to_string),
+                };
+                value.is_some_and(|value| is_numeric(&value))
+            }
+            Condition::IsUncertainDate(name) => self
+                .reference
+                .dates
+                .get(name)
+                .is_some_and(|date| date.circa),
+            Condition::Locator(label) => {
+                self.state.locator.is_some()
+                    && self.state.label.as_deref().unwrap_or("page") == label
+            }
+            Condition::Position(position) => {
+                if self.mode != Mode::Citation {
+                    return false;
+                }
+                let current = self.state.position;
+                match position {
+                    Position::First => current == Position::First,
+                    Position::Subsequent => current != Position::First,
+                    Position::Ibid => {
+                        matches!(current, Position::Ibid | Position::IbidWithLocator)
+                    }
+                    Position::IbidWithLocator => current == Position::IbidWithLocator,
+                    Position::NearNote => current != Position::First && self.state.near_note,
+                }
+            }
+            Condition::Disambiguate(value) => self.state.disambiguate == *value,
+        }
+    }
+
+    fn format_page_range(&self, value: &str) -> String {
+        let delimiter = self
+            .locale
+            .term("page-range-delimiter", TermForm::Long, false)
+            .unwrap_or("–");
+        format_page_range(value, self.style.page_range_format.as_deref(), delimiter)
+    }
+}
+
+fn date_sort_key(date: &DateValue) -> String {
+    let key =
+        |date: &SimpleDate| format!("{:05}{:02}{:02}", date.year + 10000, date.month, date.day);
+    match (date.start, date.end) {
+        (Some(start), Some(end)) => format!("{}-{}", key(&start), key(&end)),
+        (Some(start), None) => key(&start),
+        _ => date.literal.clone().unwrap_or_default(),
+    }
+}
+
+/// Converts a given name into initials, e.g. `Min-Jun` into `M.-J.` with `. ` as `with`.
+pub(crate) fn initialize(given: &str, with: &str, hyphen: bool) -> String {
+    let mut initials = String::new();
+    for word in given.split_whitespace() {
+        for (index, part) in word.split('-').filter(|part| !part.is_empty()).enumerate() {
+            if index > 0 {
+                initials.truncate(initials.trim_end().len());
+                if hyphen {
+                    initials.push('-');
+                }
+            }
+            let Some(first) = part.chars().next() else {
+                continue;
+            };
+            if first.is_lowercase() {
+                initials.push_str(part);
+                initials.push(' ');
+            } else {
+                initials.extend(first.to_uppercase());
+                initials.push_str(with);
+            }
+        }
+    }
+    initials.trim_end().to_string()
+}
+
+/// `true` if the value consists of numbers, which may have letter affixes and be separated by
+/// `-`, `,` or `&`.
+pub(crate) fn is_numeric(value: &str) -> bool {
+    let value = value.trim();
+    !value.is_empty()
+        && value.split(['-', '–', ',', '&']).all(|part| {
+            let part = part.trim();
+            let letters = |c: char| c.is_alphabetic();
+            let digits = part.trim_start_matches(letters).trim_end_matches(letters);
+            !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+fn has_multiple_numbers(value: &str) -> bool {
+    tokenize_numbers(value)
+        .iter()
+        .filter(|token| matches!(token, NumberToken::Number(_)))
+        .count()
+        > 1
+}
+
+enum NumberToken {
+    Number(String),
+    Other(String),
+}
+
+/// Splits a numeric value into numbers and the text between them, with range hyphens replaced by
+/// en dashes and list separators normalized.
+fn tokenize_numbers(value: &str) -> Vec<NumberToken> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_number = false;
+    let flush = |tokens: &mut Vec<NumberToken>, current: &mut String, in_number: bool| {
+        if current.is_empty() {
+            return;
+        }
+        let text = std::mem::take(current);
+        tokens.push(if in_number {
+            NumberToken::Number(text)
+        } else {
+            let trimmed = text.trim();
+            NumberToken::Other(match trimmed {
+                "-" | "–" => "–".to_string(),
+                "," => ", ".to_string(),
+                "&" => " & ".to_string(),
+                _ => text,
+            })
+        });
+    };
+    for c in value.trim().chars() {
+        let digit = c.is_ascii_digit();
+        if digit != in_number {
+            flush(&mut tokens, &mut current, in_number);
+            in_number = digit;
+        }
+        current.push(c);
+    }
+    flush(&mut tokens, &mut current, in_number);
+    tokens
+}
+
+fn roman(number: i64) -> String {
+    if !(1..4000).contains(&number) {
+        return number.to_string();
+    }
+    const NUMERALS: [(i64, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut rest = number;
+    let mut text = String::new();
+    for (value, numeral) in NUMERALS {
+        while rest >= value {
+            text.push_str(numeral);
+            rest -= value;
+        }
+    }
+    text
+}
+
+/// Formats page ranges like `123-145` according to the style's page-range-format.
+pub(crate) fn format_page_range(value: &str, format: Option<&str>, delimiter: &str) -> String {
+    value
+        .split(", ")
+        .map(|range| {
+            let Some((start, end)) = range.split_once(['-', '–']) else {
+                return range.to_string();
+            };
+            let (start, end) = (start.trim(), end.trim_start_matches('-').trim());
+            let numeric = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+            if !numeric(start) || !numeric(end) {
+                return format!("{}{}{}", start, delimiter, end);
+            }
+            let end = match format {
+                Some(format) => collapse_range_end(start, end, format),
+                None => end.to_string(),
+            };
+            format!("{}{}{}", start, delimiter, end)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn collapse_range_end(start: &str, end: &str, format: &str) -> String {
+    // Expand abbreviated ends like `123-45` first.
+    let expanded = if end.len() < start.len() {
+        format!("{}{}", &start[..start.len() - end.len()], end)
+    } else {
+        end.to_string()
+    };
+    if expanded.len() != start.len() {
+        return expanded;
+    }
+    let common = start
+        .chars()
+        .zip(expanded.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let minimal = |keep: usize| {
+        let changed = expanded.len() - common;
+        expanded[expanded.len() - changed.max(keep).min(expanded.len())..].to_string()
+    };
+    match format {
+        "minimal" => minimal(1),
+        "minimal-two" => minimal(2),
+        "chicago" | "chicago-15" | "chicago-16" => {
+            let first: i64 = start.parse().unwrap_or_default();
+            if first < 100 || first % 100 == 0 {
+                expanded
+            } else if first % 100 < 10 {
+                minimal(1)
+            } else if start.len() == 4 && expanded.len() - common >= 3 && format != "chicago-16" {
+                expanded
+            } else {
+                minimal(2)
+            }
+        }
+        _ => expanded,
+    }
+}
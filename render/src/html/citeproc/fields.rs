@@ -0,0 +1,270 @@
+//! Locale-aware formatting of single reference fields, as selected by distinct references like
+//! `&&id.issued.year&&`.
+//!
+//! The first field names a variable of the reference, and an optional second field selects a format:
+//!
+//! - names, e.g. `author`: the names joined by the localized "and". [`ET_AL_MIN`] or more names
+//!   are shortened to the first name followed by the localized "et al.".
+//!   `family` only shows the family names, `all` never shortens, and a number selects a single name.
+//! - dates, e.g. `issued`: the date in the localized text form.
+//!   `year`, `month` and `day` select a single part, and `numeric` uses the localized numeric form.
+//! - `page`: the page range delimited by an en dash. `first` selects the first page.
+//! - other variables are shown as they are.
+//!
+//! Fields that do not match any of these formats are not formatted, so they may be looked up in
+//! the raw CSL-JSON item instead, e.g. `author.0.family`.
+
+use super::eval::format_page_range;
+use super::locale::Locale;
+use super::reference::{DateValue, Name, Reference, SimpleDate};
+use super::style::{DatePart, DatePartName, TermForm};
+
+/// Number of names from which a name list is shortened with "et al.".
+pub(crate) const ET_AL_MIN: usize = 4;
+
+/// Formats the field of the reference selected by `fields`,
+/// or returns `None` if the fields do not select a formatted field.
+pub(crate) fn format_field(
+    reference: &Reference,
+    locale: &Locale,
+    fields: &[String],
+) -> Option<String> {
+    let (variable, format) = match fields {
+        [variable] => (variable, None),
+        [variable, format] => (variable, Some(format.as_str())),
+        _ => return None,
+    };
+
+    if let Some(names) = reference.names.get(variable) {
+        return format_names(names, locale, format);
+    }
+    if let Some(date) = reference.dates.get(variable) {
+        return format_date(date, locale, format);
+    }
+
+    let value = reference.strings.get(variable)?;
+    match (variable.as_str(), format) {
+        ("page", None) => Some(format_page_range(value, None, "–")),
+        ("page", Some("first")) => value
+            .split(['-', '–', ','])
+            .next()
+            .map(|page| page.trim().to_string()),
+        (_, None) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn format_names(names: &[Name], locale: &Locale, format: Option<&str>) -> Option<String> {
+    let (family_only, shorten) = match format {
+        None => (false, true),
+        Some("family") => (true, true),
+        Some("all") => (false, false),
+        Some(index) => {
+            let index: usize = index.parse().ok()?;
+            return names.get(index).map(|name| display_name(name, false));
+        }
+    };
+
+    let names: Vec<String> = names
+        .iter()
+        .map(|name| display_name(name, family_only))
+        .collect();
+    if shorten && names.len() >= ET_AL_MIN {
+        let et_al = locale
+            .term("et-al", TermForm::Long, false)
+            .unwrap_or("et al.");
+        return Some(format!("{} {}", names[0], et_al));
+    }
+
+    let and = locale.term("and", TermForm::Long, false).unwrap_or("and");
+    Some(match names.as_slice() {
+        [] => String::new(),
+        [name] => name.clone(),
+        [names @ .., last] => format!("{} {} {}", names.join(", "), and, last),
+    })
+}
+
+/// Name in display order, e.g. `Jane van Smith Jr.`.
+fn display_name(name: &Name, family_only: bool) -> String {
+    if name.is_literal() {
+        return name.literal.clone();
+    }
+
+    let join = |parts: &[&str]| {
+        parts
+            .iter()
+            .filter(|part| !part.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let family = join(&[name.non_dropping_particle.as_str(), name.family.as_str()]);
+    if family_only {
+        return family;
+    }
+
+    let mut display = join(&[
+        name.given.as_str(),
+        name.dropping_particle.as_str(),
+        family.as_str(),
+    ]);
+    if !name.suffix.is_empty() {
+        display.push_str(if name.comma_suffix { ", " } else { " " });
+        display.push_str(&name.suffix);
+    }
+    display
+}
+
+fn format_date(date: &DateValue, locale: &Locale, format: Option<&str>) -> Option<String> {
+    let Some(start) = date.start else {
+        return date.literal.clone().filter(|_| format.is_none());
+    };
+
+    match format {
+        None => {
+            let mut text = localized_date(start, locale, "text");
+            if let Some(end) = date.end {
+                text = format!("{}–{}", text, localized_date(end, locale, "text"));
+            }
+            Some(text)
+        }
+        Some("numeric") => Some(localized_date(start, locale, "numeric")),
+        Some("year") => Some(start.year.to_string()),
+        Some("month") => (start.month > 0).then(|| month_name(start.month, locale, None)),
+        Some("day") => (start.day > 0).then(|| start.day.to_string()),
+        _ => None,
+    }
+}
+
+/// Date in the given localized form, leaving out parts the date does not have.
+fn localized_date(date: SimpleDate, locale: &Locale, form: &str) -> String {
+    let Some(format) = locale.date_formats.get(form) else {
+        return [date.year as u32, date.month, date.day]
+            .iter()
+            .filter(|part| **part > 0)
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join("-");
+    };
+
+    let parts: Vec<(&DatePart, String)> = format
+        .parts
+        .iter()
+        .filter_map(|part| date_part(date, part, locale).map(|text| (part, text)))
+        .collect();
+    let mut text = String::new();
+    for (index, (part, part_text)) in parts.iter().enumerate() {
+        if index > 0 {
+            text.push_str(&format.delimiter);
+        }
+        text.push_str(&part.attrs.prefix);
+        text.push_str(part_text);
+        if index + 1 < parts.len() {
+            text.push_str(&part.attrs.suffix);
+        }
+    }
+    text
+}
+
+fn date_part(date: SimpleDate, part: &DatePart, locale: &Locale) -> Option<String> {
+    let form = part.form.as_deref();
+    match part.name {
+        DatePartName::Year => Some(date.year.to_string()),
+        DatePartName::Month if date.month == 0 => None,
+        DatePartName::Month => Some(match form {
+            Some("numeric") if date.month <= 12 => date.month.to_string(),
+            Some("numeric-leading-zeros") if date.month <= 12 => format!("{:02}", date.month),
+            _ => month_name(date.month, locale, form),
+        }),
+        DatePartName::Day if date.day == 0 => None,
+        DatePartName::Day => Some(match form {
+            Some("numeric-leading-zeros") => format!("{:02}", date.day),
+            Some("ordinal") => {
+                let gender = locale.gender(&format!("month-{:02}", date.month.min(12)));
+                format!(
+                    "{}{}",
+                    date.day,
+                    locale.ordinal_suffix(date.day as i64, gender)
+                )
+            }
+            _ => date.day.to_string(),
+        }),
+    }
+}
+
+/// Localized name of the month, or of the season for months 13 to 16.
+fn month_name(month: u32, locale: &Locale, form: Option<&str>) -> String {
+    let term = match month {
+        13..=16 => format!("season-{:02}", month - 12),
+        month => format!("month-{:02}", month),
+    };
+    let form = TermForm::parse(form.unwrap_or_default());
+    locale
+        .term(&term, form, false)
+        .map(str::to_string)
+        .unwrap_or_else(|| month.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::citeproc::csl_files::get_locale_string;
+    use std::collections::HashMap;
+
+    fn reference() -> Reference {
+        Reference::from_value(&serde_json::json!({
+            "id": "smith2019",
+            "type": "article-journal",
+            "author": [
+                { "family": "Smith", "given": "Jane" },
+                { "family": "Doe", "given": "John", "suffix": "Jr." },
+                { "family": "Berg", "given": "Anna", "non-dropping-particle": "van" },
+                { "literal": "World Health Organization" }
+            ],
+            "editor": [{ "family": "Lee", "given": "Kim" }, { "family": "Park", "given": "Min" }],
+            "issued": { "date-parts": [[2019, 3, 1]] },
+            "page": "100-110",
+            "volume": 12.0
+        }))
+        .unwrap()
+    }
+
+    fn field(fields: &str, lang: &str) -> Option<String> {
+        let locale_text = get_locale_string(lang.parse().unwrap(), HashMap::new());
+        let locale = Locale::new(&locale_text, &[], lang);
+        let fields: Vec<String> = fields.split('.').map(str::to_string).collect();
+        format_field(&reference(), &locale, &fields)
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(field("author", "en-US").unwrap(), "Jane Smith et al.");
+        assert_eq!(field("editor", "en-US").unwrap(), "Kim Lee and Min Park");
+        assert_eq!(field("editor", "de-DE").unwrap(), "Kim Lee und Min Park");
+        assert_eq!(
+            field("author.all", "en-US").unwrap(),
+            "Jane Smith, John Doe Jr., Anna van Berg and World Health Organization"
+        );
+        assert_eq!(field("editor.family", "en-US").unwrap(), "Lee and Park");
+        assert_eq!(field("author.2", "en-US").unwrap(), "Anna van Berg");
+        assert_eq!(field("author.0.family", "en-US"), None);
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(field("issued", "en-US").unwrap(), "March 1, 2019");
+        assert_eq!(field("issued", "de-DE").unwrap(), "1. März 2019");
+        assert_eq!(field("issued.numeric", "en-US").unwrap(), "03/01/2019");
+        assert_eq!(field("issued.year", "de-DE").unwrap(), "2019");
+        assert_eq!(field("issued.month", "de-DE").unwrap(), "März");
+        assert_eq!(field("issued.day", "en-US").unwrap(), "1");
+    }
+
+    #[test]
+    fn numbers_and_pages() {
+        assert_eq!(field("page", "en-US").unwrap(), "100–110");
+        assert_eq!(field("page.first", "en-US").unwrap(), "100");
+        assert_eq!(field("volume", "en-US").unwrap(), "12");
+        assert_eq!(field("volume.first", "en-US"), None);
+    }
+}
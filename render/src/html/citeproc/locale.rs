@@ -0,0 +1,183 @@
+//! Terms, date formats and options of a CSL locale.
+//!
+//! A [`Locale`] is built from the locale file and then overridden by the `cs:locale` sections of
+//! the style, in the same order citeproc-js applies them.
+
+use std::collections::HashMap;
+
+use super::style::{self, Date, TermForm};
+use super::xml::{self, XmlNode};
+
+#[derive(Clone, Debug, Default)]
+struct Term {
+    single: String,
+    multiple: String,
+    /// Grammatical gender of the noun this term stands for.
+    gender: Option<String>,
+    /// Match rule of ordinal terms.
+    match_rule: Option<String>,
+}
+
+/// Key of a term: name, form and gender form.
+type TermKey = (String, TermForm, Option<String>);
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Locale {
+    terms: HashMap<TermKey, Term>,
+    pub date_formats: HashMap<String, Date>,
+    pub punctuation_in_quote: bool,
+    pub limit_day_ordinals_to_day_1: bool,
+}
+
+impl Locale {
+    /// Builds the locale from the given locale file and the `cs:locale` sections of the style that
+    /// apply to `lang`.
+    pub(crate) fn new(locale_text: &str, style_locales: &[XmlNode], lang: &str) -> Self {
+        let mut locale = Locale::default();
+        if let Ok(root) = xml::parse(locale_text) {
+            locale.apply(&root);
+        }
+
+        let language = lang.split('-').next().unwrap_or(lang);
+        let mut wanted = vec![None, Some(language)];
+        if language != lang {
+            wanted.push(Some(lang));
+        }
+        for lang in wanted {
+            for section in style_locales
+                .iter()
+                .filter(|node| node.get("xml:lang") == lang)
+            {
+                locale.apply(section);
+            }
+        }
+        locale
+    }
+
+    fn apply(&mut self, root: &XmlNode) {
+        if let Some(options) = root.child("style-options") {
+            if let Some(value) = options.get("punctuation-in-quote") {
+                self.punctuation_in_quote = value == "true";
+            }
+            if let Some(value) = options.get("limit-day-ordinals-to-day-1") {
+                self.limit_day_ordinals_to_day_1 = value == "true";
+            }
+        }
+        for date in root.children_named("date") {
+            let format = style::parse_date(date);
+            if let Some(form) = &format.form {
+                self.date_formats.insert(form.clone(), format);
+            }
+        }
+        let Some(terms) = root.child("terms") else {
+            return;
+        };
+
+        // Defining any ordinal term replaces all ordinal terms of the previous locale.
+        if terms
+            .children_named("term")
+            .any(|term| term.attr("name").starts_with("ordinal"))
+        {
+            self.terms
+                .retain(|(name, ..), _| !name.starts_with("ordinal"));
+        }
+
+        for node in terms.children_named("term") {
+            let name = node.attr("name").to_string();
+            let form = TermForm::parse(node.attr("form"));
+            let gender_form = node.get("gender-form").map(str::to_string);
+            let (single, multiple) = match (node.child("single"), node.child("multiple")) {
+                (None, None) => (node.text.clone(), node.text.clone()),
+                (single, multiple) => (
+                    single.map(|n| n.text.clone()).unwrap_or_default(),
+                    multiple.map(|n| n.text.clone()).unwrap_or_default(),
+                ),
+            };
+            let term = Term {
+                single,
+                multiple,
+                gender: node.get("gender").map(str::to_string),
+                match_rule: node.get("match").map(str::to_string),
+            };
+            self.terms.insert((name, form, gender_form), term);
+        }
+    }
+
+    fn find(&self, name: &str, form: TermForm, gender: Option<&str>) -> Option<&Term> {
+        let mut form = Some(form);
+        while let Some(current) = form {
+            let gendered = gender.and_then(|gender| {
+                self.terms
+                    .get(&(name.to_string(), current, Some(gender.to_string())))
+            });
+            if let Some(term) =
+                gendered.or_else(|| self.terms.get(&(name.to_string(), current, None)))
+            {
+                return Some(term);
+            }
+            form = current.fallback();
+        }
+        None
+    }
+
+    /// Localized term, falling back to other forms if the term does not exist in the given form.
+    pub(crate) fn term(&self, name: &str, form: TermForm, plural: bool) -> Option<&str> {
+        self.find(name, form, None).map(|term| {
+            if plural {
+                term.multiple.as_str()
+            } else {
+                term.single.as_str()
+            }
+        })
+    }
+
+    /// Grammatical gender of the noun term with the given name.
+    pub(crate) fn gender(&self, name: &str) -> Option<&str> {
+        self.find(name, TermForm::Long, None)
+            .and_then(|term| term.gender.as_deref())
+    }
+
+    /// Ordinal suffix for the given number.
+    pub(crate) fn ordinal_suffix(&self, number: i64, gender: Option<&str>) -> String {
+        let number = number.abs();
+        let candidates = [
+            (format!("ordinal-{:02}", number % 100), number % 100 >= 10),
+            (format!("ordinal-{:02}", number % 10), false),
+        ];
+        for (name, two_digits) in candidates {
+            if let Some(term) = self.find(&name, TermForm::Long, gender) {
+                let value: i64 = name["ordinal-".len()..].parse().unwrap_or(-1);
+                let default_rule = if two_digits {
+                    "last-two-digits"
+                } else {
+                    "last-digit"
+                };
+                let matches = match term.match_rule.as_deref().unwrap_or(default_rule) {
+                    "whole-number" => number == value,
+                    "last-two-digits" => number % 100 == value,
+                    _ => number % 10 == value,
+                };
+                if matches {
+                    return term.single.clone();
+                }
+            }
+        }
+        self.find("ordinal", TermForm::Long, gender)
+            .map(|term| term.single.clone())
+            .unwrap_or_default()
+    }
+
+    /// Number written as an ordinal word, for numbers up to ten.
+    pub(crate) fn long_ordinal(&self, number: i64, gender: Option<&str>) -> String {
+        if (1..=10).contains(&number) {
+            if let Some(term) = self.find(
+                &format!("long-ordinal-{:02}", number),
+                TermForm::Long,
+                gender,
+            ) {
+                return term.single.clone();
+            }
+        }
+        format!("{}{}", number, self.ordinal_suffix(number, gender))
+    }
+}
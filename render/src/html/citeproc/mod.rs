@@ -1,132 +1,388 @@
+mod assemble;
 mod csl_files;
+mod eval;
+mod fields;
+mod locale;
+mod output;
+mod processor;
+mod reference;
+mod style;
+pub mod validate;
+mod xml;
 
 use logid::log;
-use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 
 use crate::csl_json::csl_types::{CslData, CslItem};
+use crate::csl_json::{bibtex, ris};
 use crate::html::citeproc::csl_files::{get_locale_string, get_style_string};
+use crate::html::citeproc::processor::{CiteItem, Processor};
+use crate::html::citeproc::reference::Reference;
 use crate::log_id::{CiteError, GeneralWarning};
-use rustyscript::{import, json_args, serde_json, ModuleWrapper};
-use unimarkup_commons::config::icu_locid::locale;
+use serde_json::Value;
+use unimarkup_commons::config::icu_locid::{locale, Locale};
+use unimarkup_commons::config::preamble::Citedata;
+use unimarkup_inline::element::textbox::citation::CitationEntry;
 use unimarkup_parser::document::Document;
 
+pub use csl_files::{bundled_style_ids, load_style};
+
+/// Rendered in place of citations that could not be rendered, e.g. because their id is unknown.
+pub const CITATION_ERROR: &str = "########### CITATION ERROR ###########";
+
+/// Renders citations, footnotes and the bibliography of a document using its CSL style.
+///
+/// The parsed style, locale and references are kept until the content of one of them changes,
+/// so repeated calls for the same document do not load them again.
+/// Style and locale are only read again if the citation settings of the document change,
+/// while the references are read on every call to detect changes of their content.
+#[derive(Default)]
 pub struct CiteprocWrapper {
-    module: ModuleWrapper,
+    /// Processor with style, locale and references loaded, but without any citations.
+    loaded: Option<Processor>,
+    /// Citation settings and language of the document, with the style and locale read for them.
+    loaded_settings: Option<LoadedSettings>,
+    /// Hash of the references' content, the style and the locale `loaded` was created from.
+    loaded_hash: Option<u64>,
+    processor: Option<Processor>,
+    footnotes: Vec<String>,
+    /// Citations the processor currently holds.
+    processed: Option<ProcessedCitations>,
+    /// Rendered authors by reference id.
+    authors: HashMap<String, String>,
+}
+
+/// Style and locale read for the citation settings and language of a document.
+struct LoadedSettings {
+    settings: (Citedata, Option<Locale>),
+    style: String,
+    locale: String,
+}
+
+/// Citations processed by [`CiteprocWrapper::get_citation_strings`].
+struct ProcessedCitations {
+    /// Hash of the loaded content the citations were processed with.
+    hash: u64,
+    citations: Vec<Vec<CitationEntry>>,
+    for_pagedjs: bool,
+    rendered: Vec<String>,
 }
 
 impl CiteprocWrapper {
-    pub fn new() -> Result<CiteprocWrapper, CiteError> {
-        match import("render/src/html/citeproc/js/citeproc_adapter.js") {
-            Ok(module) => Ok(CiteprocWrapper { module }),
-            Err(_err) => Err(CiteError::ModuleImportError),
-        }
+    pub fn new() -> CiteprocWrapper {
+        CiteprocWrapper::default()
     }
 
-    pub fn new_with_path(path: &str) -> Result<CiteprocWrapper, CiteError> {
-        match import(path) {
-            Ok(module) => Ok(CiteprocWrapper { module }),
-            Err(_err) => Err(CiteError::ModuleImportError),
-        }
+    /// Resets the processor to a state without citations.
+    pub fn init_processor(&mut self, doc: &Document) -> Result<(), CiteError> {
+        self.processor = Some(self.load(doc)?.clone());
+        self.footnotes.clear();
+        self.processed = None;
+        Ok(())
     }
 
-    pub fn init_processor(&mut self, doc: &Document, for_pagedjs: bool) -> Result<(), CiteError> {
-        let csl_data = get_csl_data(&doc.config.preamble.cite.references);
-        let style_id = doc
-            .config
-            .preamble
-            .cite
-            .style
-            .clone()
-            .unwrap_or(PathBuf::from(String::from("ieee")));
-        let doc_locale = doc
-            .config
-            .preamble
-            .i18n
-            .lang
-            .clone()
-            .unwrap_or(locale!("en-US"));
-        let citation_locales = doc.config.preamble.cite.citation_locales.clone();
-
-        let citation_text = serde_json::ser::to_string(&csl_data).unwrap();
-        let locale = get_locale_string(doc_locale, citation_locales);
-        let style = get_style_string(style_id);
-
-        self.module
-            .call::<()>(
-                "initProcessor",
-                json_args!(citation_text, locale, style, for_pagedjs),
+    /// Returns the processor for the style, locale and references of the given document.
+    /// Style, locale and references are only parsed again if their content changed.
+    fn load(&mut self, doc: &Document) -> Result<&mut Processor, CiteError> {
+        let settings = (
+            doc.config.preamble.cite.clone(),
+            doc.config.preamble.i18n.lang.clone(),
+        );
+        let loaded_settings = match self.loaded_settings.take() {
+            Some(loaded) if loaded.settings == settings => loaded,
+            _ => read_settings(doc, settings),
+        };
+        let hash = content_hash(
+            &doc.config.preamble.cite.references,
+            &loaded_settings.style,
+            &loaded_settings.locale,
+        );
+
+        if self.loaded.is_none() || self.loaded_hash != Some(hash) {
+            let csl_data = get_csl_data(&doc.config.preamble.cite.references);
+            let processor = Processor::new(
+                &loaded_settings.style,
+                &loaded_settings.locale,
+                &processor_items(&csl_data),
             )
-            .map_err(|_| CiteError::ProcessorInitializationError)
+            .map_err(|_| CiteError::ProcessorInitializationError)?;
+            self.loaded = Some(processor);
+            self.loaded_hash = Some(hash);
+            self.authors.clear();
+        }
+        self.loaded_settings = Some(loaded_settings);
+
+        self.loaded
+            .as_mut()
+            .ok_or(CiteError::ProcessorInitializationError)
     }
 
-    // returns the citation strings to be placed inline in the same order as the citation_ids
+    // returns the citation strings to be placed inline in the same order as the citations
     // the CitationItems have to have the same order that they should appear in the output, because this considers
     // disambiguation and short forms of citations if the same entry was cited before
     pub fn get_citation_strings(
         &mut self,
         doc: &Document,
-        citation_id_vectors: &[serde_json::Value],
+        citations: &[Vec<CitationEntry>],
         for_pagedjs: bool,
     ) -> Result<Vec<String>, CiteError> {
-        match self.init_processor(doc, for_pagedjs) {
-            Ok(_) => self
-                .module
-                .call("getCitationStrings", citation_id_vectors)
-                .map_err(|_| CiteError::CitationError),
-            Err(e) => Err(e),
+        self.load(doc)?;
+        if let Some(processed) = self.processed.as_ref().filter(|processed| {
+            Some(processed.hash) == self.loaded_hash
+                && processed.citations == citations
+                && processed.for_pagedjs == for_pagedjs
+        }) {
+            return Ok(processed.rendered.clone());
         }
+
+        self.init_processor(doc)?;
+        let processor = self
+            .processor
+            .as_mut()
+            .ok_or(CiteError::ProcessorInitializationError)?;
+        // Note: Unknown ids are reported after parsing, so only their cites are rendered as error here.
+        let items: Vec<Vec<CiteItem>> = citations
+            .iter()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter(|entry| processor.has_reference(entry.id()))
+                    .map(cite_item)
+                    .collect()
+            })
+            .collect();
+        let known_items: Vec<Vec<CiteItem>> = items
+            .iter()
+            .filter(|items| !items.is_empty())
+            .cloned()
+            .collect();
+        let mut clusters = processor
+            .process_citations(&known_items)
+            .map_err(|_| CiteError::CitationError)?
+            .into_iter();
+
+        let mut rendered = Vec::with_capacity(citations.len());
+        for (entries, items) in citations.iter().zip(&items) {
+            if items.is_empty() {
+                rendered.push(CITATION_ERROR.to_string());
+                continue;
+            }
+            let cluster = clusters.next().ok_or(CiteError::CitationError)?;
+
+            let ids: Vec<String> = items.iter().map(|item| item.id.clone()).collect();
+            let mut linked = assemble::link_cites(&cluster, &ids);
+            if entries.len() > items.len() {
+                linked.push(' ');
+                linked.push_str(CITATION_ERROR);
+            }
+
+            if !processor.is_note_style() {
+                rendered.push(linked);
+            } else if for_pagedjs {
+                rendered.push(assemble::pagedjs_footnote(&linked));
+            } else {
+                let number = self.footnotes.len() + 1;
+                rendered.push(assemble::footnote_link(number));
+                self.footnotes.push(assemble::footnote(number, &linked));
+            }
+        }
+
+        if let Some(hash) = self.loaded_hash {
+            self.processed = Some(ProcessedCitations {
+                hash,
+                citations: citations.to_vec(),
+                for_pagedjs,
+                rendered: rendered.clone(),
+            });
+        }
+        Ok(rendered)
     }
 
+    /// Returns the authors of the reference with the given id, as if it was the only cited reference.
+    /// Rendered citations, footnotes and the bibliography are not affected.
     pub fn get_author_only(
         &mut self,
         doc: &Document,
         cite_id: String,
     ) -> Result<String, CiteError> {
-        match self.init_processor(doc, false) {
-            Ok(_) => self
-                .module
-                .call("getAuthorOnly", json_args!(cite_id))
-                .map_err(|_| CiteError::CitationError),
-            Err(e) => Err(e),
+        self.load(doc)?;
+        if let Some(authors) = self.authors.get(&cite_id) {
+            return Ok(authors.clone());
         }
+
+        let authors = self
+            .loaded
+            .as_mut()
+            .ok_or(CiteError::ProcessorInitializationError)?
+            .author_only(&cite_id)
+            .map_err(|_| CiteError::CitationError)?;
+        self.authors.insert(cite_id, authors.clone());
+        Ok(authors)
+    }
+
+    /// Returns the field of the given CSL-JSON item selected by `fields`, formatted for the locale of the document.
+    /// Returns `None` if the fields do not select a formatted field (see [`fields`]).
+    pub fn get_formatted_field(
+        &mut self,
+        doc: &Document,
+        item: &Value,
+        fields: &[String],
+    ) -> Result<Option<String>, CiteError> {
+        let processor = self.load(doc)?;
+        Ok(Reference::from_value(item)
+            .and_then(|reference| fields::format_field(&reference, processor.locale(), fields)))
     }
 
     pub fn get_footnotes(&mut self) -> Result<String, CiteError> {
-        let has_footnotes = self
-            .module
-            .call("hasFootnotes", json_args!())
-            .map_err(|_| CiteError::CheckForFootnotesError)?;
-        if has_footnotes {
-            self.module
-                .call("getFootnotesString", json_args!())
-                .map_err(|_| CiteError::GetFootnotesError)
-        } else {
+        if self.processor.is_none() {
+            return Err(CiteError::GetFootnotesError);
+        }
+        if self.footnotes.is_empty() {
             Ok("".to_string())
+        } else {
+            Ok(assemble::footnotes(&self.footnotes))
         }
     }
 
     pub fn get_bibliography(&mut self) -> Result<String, CiteError> {
-        self.module
-            .call("getBibliography", json_args!())
-            .map_err(|_| CiteError::GetBibliographyError)
+        self.get_filtered_bibliography(None)
+    }
+
+    /// Returns the bibliography of the cited references with one of the given ids,
+    /// or an empty string if none of them is cited.
+    /// All cited references are listed if no ids are given.
+    pub fn get_filtered_bibliography(
+        &self,
+        ids: Option<&HashSet<String>>,
+    ) -> Result<String, CiteError> {
+        let processor = self
+            .processor
+            .as_ref()
+            .ok_or(CiteError::GetBibliographyError)?;
+        match (
+            &processor.style().bibliography,
+            processor.bibliography_of(ids),
+        ) {
+            (Some(_), Some(entries)) if entries.is_empty() && ids.is_some() => Ok(String::new()),
+            (Some(bibliography), Some(entries)) => {
+                Ok(assemble::bibliography(bibliography, &entries))
+            }
+            _ => Err(CiteError::GetBibliographyError),
+        }
+    }
+}
+
+/// Reads the style and locale for the given citation settings and language of the document.
+fn read_settings(doc: &Document, settings: (Citedata, Option<Locale>)) -> LoadedSettings {
+    let style_id = doc
+        .config
+        .preamble
+        .cite
+        .style
+        .clone()
+        .unwrap_or(PathBuf::from(String::from("ieee")));
+    let doc_locale = doc
+        .config
+        .preamble
+        .i18n
+        .lang
+        .clone()
+        .unwrap_or(locale!("en-US"));
+    let citation_locales = doc.config.preamble.cite.citation_locales.clone();
+
+    LoadedSettings {
+        settings,
+        style: get_style_string(style_id),
+        locale: get_locale_string(doc_locale, citation_locales),
+    }
+}
+
+/// Hash of the content of the given reference files, the style and the locale.
+fn content_hash(references: &HashSet<PathBuf>, style: &str, locale: &str) -> u64 {
+    let mut paths: Vec<&PathBuf> = references.iter().collect();
+    paths.sort();
+
+    let mut hasher = DefaultHasher::new();
+    for path in paths {
+        path.hash(&mut hasher);
+        fs::read(path).ok().hash(&mut hasher);
     }
+    style.hash(&mut hasher);
+    locale.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Cite of the processor for the given citation entry.
+fn cite_item(entry: &CitationEntry) -> CiteItem {
+    CiteItem {
+        id: entry.id().to_string(),
+        locator: entry.locator().map(|locator| locator.value().to_string()),
+        label: entry.locator().map(|locator| locator.label().to_string()),
+        prefix: entry.prefix().map(str::to_string),
+        suffix: entry.suffix().map(str::to_string),
+        suppress_author: entry.suppress_author(),
+        author_only: entry.author_only(),
+    }
+}
+
+/// Returns `true` if the citation processor can render the given item, which requires an issued date.
+pub(crate) fn is_processable(item: &CslItem) -> bool {
+    item.issued.is_some()
+}
+
+/// Items in the CSL-JSON form the processor works on.
+///
+/// Items without issued date are left out and URLs are not rendered.
+fn processor_items(csl_data: &CslData) -> Vec<Value> {
+    csl_data
+        .items
+        .iter()
+        .filter(|item| is_processable(item))
+        .filter_map(|item| serde_json::to_value(item).ok())
+        .map(|mut item| {
+            if let Some(object) = item.as_object_mut() {
+                object.remove("URL");
+            }
+            item
+        })
+        .collect()
 }
 
 pub fn get_csl_data(references: &HashSet<PathBuf>) -> CslData {
     let mut citation_items: Vec<CslItem> = vec![];
     for reference in references {
         if let Ok(citation_string) = fs::read_to_string(reference.clone().into_os_string()) {
-            match serde_json::from_str::<CslData>(&citation_string) {
-                Ok(mut citation_data) => citation_items.append(&mut citation_data.items),
-                Err(e) => {
-                    log!(
-                        GeneralWarning::JSONDeserialization,
-                        format!("JSON deserializaion failed with error: '{:?}'", e)
-                    );
+            let extension = reference
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map(str::to_lowercase);
+            let imported = match extension.as_deref() {
+                Some("bib" | "bibtex") => bibtex::import(&citation_string),
+                Some("ris") => ris::import(&citation_string),
+                _ => {
+                    match serde_json::from_str::<CslData>(&citation_string) {
+                        Ok(mut citation_data) => citation_items.append(&mut citation_data.items),
+                        Err(e) => {
+                            log!(
+                                GeneralWarning::JSONDeserialization,
+                                format!("JSON deserializaion failed with error: '{:?}'", e)
+                            );
+                        }
+                    }
+                    continue;
                 }
+            };
+            for warning in imported.warnings {
+                log!(
+                    GeneralWarning::ReferenceImport,
+                    format!("{} in '{:?}'", warning, &reference)
+                );
             }
+            citation_items.extend(imported.items);
         } else {
             log!(
                 GeneralWarning::FileRead,
@@ -142,7 +398,6 @@ pub fn get_csl_data(references: &HashSet<PathBuf>) -> CslData {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use std::str::FromStr;
     use unimarkup_commons::config::icu_locid::{locale, Locale};
     use unimarkup_commons::config::preamble::{Citedata, I18n, Preamble};
@@ -164,12 +419,18 @@ mod tests {
                         references: citation_paths,
                         citation_locales,
                         csl_locales: vec![],
+                        warn_unused_references: false,
                     },
                     render: Default::default(),
                     i18n: I18n {
                         lang: Some(doc_locale),
                         output_langs: Default::default(),
                     },
+                    numbering: Default::default(),
+                    toc: Default::default(),
+                    slugs: Default::default(),
+                    styling: Default::default(),
+                    latex: Default::default(),
                 },
                 output: Default::default(),
                 merging: Default::default(),
@@ -185,8 +446,7 @@ mod tests {
 
     #[test]
     fn test_no_footnotes() {
-        let mut under_test =
-            CiteprocWrapper::new_with_path("./src/html/citeproc/js/citeproc_adapter.js").unwrap();
+        let mut under_test = CiteprocWrapper::new();
         let mut citation_paths: HashSet<PathBuf> = HashSet::new();
         citation_paths.insert(
             PathBuf::from_str("./src/html/citeproc/test_files/citation_items.csl").unwrap(),
@@ -198,10 +458,13 @@ mod tests {
             PathBuf::from_str("./csl_locales/locales-de-DE.xml").unwrap(),
         );
         let style_id = PathBuf::from_str("./csl_styles/apa.csl").unwrap();
-        let serde_result = serde_json::to_value(vec![vec!["id-1"], vec!["id-1", "id-2"]]).unwrap();
+        let citations = vec![
+            vec![CitationEntry::new("id-1")],
+            vec![CitationEntry::new("id-1"), CitationEntry::new("id-2")],
+        ];
         let for_pagedjs = false;
         let doc = test_document(citation_paths, doc_locale, citation_locales, style_id);
-        let actual_citations = under_test.get_citation_strings(&doc, &[serde_result], for_pagedjs);
+        let actual_citations = under_test.get_citation_strings(&doc, &citations, for_pagedjs);
 
         assert!(actual_citations.is_ok(), "A cite error occurred");
         let unwrapped_actual = actual_citations.unwrap();
@@ -221,8 +484,7 @@ mod tests {
 
     #[test]
     fn test_footnotes() {
-        let mut under_test =
-            CiteprocWrapper::new_with_path("./src/html/citeproc/js/citeproc_adapter.js").unwrap();
+        let mut under_test = CiteprocWrapper::new();
         let mut citation_paths: HashSet<PathBuf> = HashSet::new();
         citation_paths.insert(
             PathBuf::from_str("./src/html/citeproc/test_files/citation_items.csl").unwrap(),
@@ -234,10 +496,13 @@ mod tests {
             PathBuf::from_str("./csl_locales/locales-de-DE.xml").unwrap(),
         );
         let style_id = PathBuf::from_str("./csl_styles/chicago-fullnote-bibliography.csl").unwrap();
-        let serde_result = serde_json::to_value(vec![vec!["id-1"], vec!["id-1", "id-2"]]).unwrap();
+        let citations = vec![
+            vec![CitationEntry::new("id-1")],
+            vec![CitationEntry::new("id-1"), CitationEntry::new("id-2")],
+        ];
         let for_pagedjs = false;
         let doc = test_document(citation_paths, doc_locale, citation_locales, style_id);
-        let actual_citations = under_test.get_citation_strings(&doc, &[serde_result], for_pagedjs);
+        let actual_citations = under_test.get_citation_strings(&doc, &citations, for_pagedjs);
 
         assert!(actual_citations.is_ok(), "A cite error occurred");
         let unwrapped_actual = actual_citations.unwrap();
@@ -256,8 +521,7 @@ mod tests {
 
     #[test]
     fn test_for_pagedjs() {
-        let mut under_test =
-            CiteprocWrapper::new_with_path("./src/html/citeproc/js/citeproc_adapter.js").unwrap();
+        let mut under_test = CiteprocWrapper::new();
         let mut citation_paths: HashSet<PathBuf> = HashSet::new();
         citation_paths.insert(
             PathBuf::from_str("./src/html/citeproc/test_files/citation_items.csl").unwrap(),
@@ -269,10 +533,13 @@ mod tests {
             PathBuf::from_str("./csl_locales/locales-de-DE.xml").unwrap(),
         );
         let style_id = PathBuf::from_str("./csl_styles/chicago-fullnote-bibliography.csl").unwrap();
-        let serde_result = serde_json::to_value(vec![vec!["id-1"], vec!["id-1", "id-2"]]).unwrap();
+        let citations = vec![
+            vec![CitationEntry::new("id-1")],
+            vec![CitationEntry::new("id-1"), CitationEntry::new("id-2")],
+        ];
         let for_pagedjs = true;
         let doc = test_document(citation_paths, doc_locale, citation_locales, style_id);
-        let actual_citations = under_test.get_citation_strings(&doc, &[serde_result], for_pagedjs);
+        let actual_citations = under_test.get_citation_strings(&doc, &citations, for_pagedjs);
 
         assert!(actual_citations.is_ok(), "A cite error occurred");
         let unwrapped_actual = actual_citations.unwrap();
@@ -288,6 +555,141 @@ mod tests {
         assert!(actual_bibliography.starts_with("<div class=\"csl-bib-body\""));
     }
 
+    #[test]
+    fn test_locator_affixes_and_suppressed_author() {
+        let mut under_test = CiteprocWrapper::new();
+        let mut citation_paths = HashSet::new();
+        citation_paths.insert(
+            PathBuf::from_str("./src/html/citeproc/test_files/citation_items.csl").unwrap(),
+        );
+        let style_id = PathBuf::from_str("./csl_styles/apa.csl").unwrap();
+        let doc = test_document(citation_paths, locale!("en-US"), HashMap::new(), style_id);
+        let citations = unimarkup_parser::parse_unimarkup(
+            "[&&id-1, p. 12] and [&&\"see\" -id-2, chap. 3 \"for details\"]",
+            Config::default(),
+        )
+        .citations;
+
+        let actual = under_test
+            .get_citation_strings(&doc, &citations, false)
+            .unwrap();
+
+        assert_eq!(actual.len(), 2);
+        // Note: Both items are by Cook from 1997, so APA disambiguates the years with suffixes.
+        assert!(actual[0].contains("(Cook, 1997b, p. 12)"));
+        assert!(actual[1].contains("see "));
+        assert!(actual[1].contains("for details"));
+        assert!(!actual[1].contains("Cook"));
+    }
+
+    #[test]
+    fn test_unknown_id_only_fails_its_cite() {
+        let mut under_test = CiteprocWrapper::new();
+        let mut citation_paths = HashSet::new();
+        citation_paths.insert(
+            PathBuf::from_str("./src/html/citeproc/test_files/citation_items.csl").unwrap(),
+        );
+        let style_id = PathBuf::from_str("./csl_styles/apa.csl").unwrap();
+        let doc = test_document(citation_paths, locale!("en-US"), HashMap::new(), style_id);
+        let citations = vec![
+            vec![CitationEntry::new("id-1")],
+            vec![CitationEntry::new("unknown")],
+            vec![CitationEntry::new("id-2"), CitationEntry::new("unknown")],
+        ];
+
+        let actual = under_test
+            .get_citation_strings(&doc, &citations, false)
+            .unwrap();
+
+        assert_eq!(actual.len(), 3);
+        assert!(actual[0].contains("Cook"));
+        assert_eq!(actual[1], CITATION_ERROR);
+        assert!(actual[2].contains("Cook"));
+        assert!(actual[2].ends_with(CITATION_ERROR));
+    }
+
+    #[test]
+    fn test_edited_references_are_reloaded() {
+        let items =
+            fs::read_to_string("./src/html/citeproc/test_files/citation_items.csl").unwrap();
+        let references = tempfile::Builder::new().suffix(".csl").tempfile().unwrap();
+        fs::write(references.path(), &items).unwrap();
+        let style_id = PathBuf::from_str("./csl_styles/apa.csl").unwrap();
+        let doc = test_document(
+            HashSet::from([references.path().to_path_buf()]),
+            locale!("en-US"),
+            HashMap::new(),
+            style_id,
+        );
+        let citations = vec![vec![CitationEntry::new("id-1")]];
+        let mut under_test = CiteprocWrapper::new();
+
+        let first = under_test
+            .get_citation_strings(&doc, &citations, false)
+            .unwrap();
+        fs::write(references.path(), items.replacen("Cook", "Baker", 1)).unwrap();
+        let second = under_test
+            .get_citation_strings(&doc, &citations, false)
+            .unwrap();
+
+        assert!(first[0].contains("Cook"), "{}", first[0]);
+        assert!(second[0].contains("Baker"), "{}", second[0]);
+    }
+
+    #[test]
+    fn test_processor_is_reused() {
+        let mut under_test = CiteprocWrapper::new();
+        let mut citation_paths = HashSet::new();
+        citation_paths.insert(
+            PathBuf::from_str("./src/html/citeproc/test_files/citation_items.csl").unwrap(),
+        );
+        let style_id = PathBuf::from_str("./csl_styles/apa.csl").unwrap();
+        let doc = test_document(citation_paths, locale!("en-US"), HashMap::new(), style_id);
+        let citations = vec![vec![CitationEntry::new("id-1")]];
+
+        let first = under_test
+            .get_citation_strings(&doc, &citations, false)
+            .unwrap();
+        let hash = under_test.loaded_hash;
+        let bibliography = under_test.get_bibliography().unwrap();
+        let authors = under_test
+            .get_author_only(&doc, "id-2".to_string())
+            .unwrap();
+
+        assert!(authors.contains("Cook"));
+        assert_eq!(
+            under_test.authors.get("id-2"),
+            Some(&authors),
+            "Authors must be cached."
+        );
+        assert_eq!(
+            under_test.get_bibliography().unwrap(),
+            bibliography,
+            "Authors must not be added to the bibliography."
+        );
+        assert_eq!(
+            under_test
+                .get_citation_strings(&doc, &citations, false)
+                .unwrap(),
+            first
+        );
+        assert_eq!(
+            under_test.loaded_hash, hash,
+            "Processor must not be reloaded."
+        );
+
+        let other_citations = vec![vec![CitationEntry::new("id-2")]];
+        assert_eq!(
+            under_test
+                .get_citation_strings(&doc, &other_citations, false)
+                .unwrap(),
+            CiteprocWrapper::new()
+                .get_citation_strings(&doc, &other_citations, false)
+                .unwrap(),
+            "Rendering authors must not change the loaded processor."
+        );
+    }
+
     #[test]
     fn test_get_csl_string_two_files() {
         let mut paths = HashSet::new();
@@ -301,4 +703,132 @@ mod tests {
 
         assert_eq!(actual_object.items.len(), 8);
     }
+
+    #[test]
+    fn test_get_csl_data_from_bibtex_and_ris() {
+        let mut paths = HashSet::new();
+        paths.insert(
+            PathBuf::from_str("./src/html/citeproc/test_files/citation_items.bib").unwrap(),
+        );
+        paths.insert(
+            PathBuf::from_str("./src/html/citeproc/test_files/citation_items.ris").unwrap(),
+        );
+        let actual_object: CslData = get_csl_data(&paths);
+
+        let mut ids: Vec<String> = actual_object
+            .items
+            .iter()
+            .map(|item| item.id.to_string())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["doe2018", "knuth1984", "lamport1994"]);
+    }
+
+    /// Runs the cases of a compatibility fixture, whose expected output was produced by
+    /// citeproc-js with the same style, locale and references.
+    fn run_compat_fixture(style: &str) {
+        let fixture_path = format!("./src/html/citeproc/test_files/compat/{}.json", style);
+        let fixture: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(fixture_path).unwrap()).unwrap();
+        let style_id = PathBuf::from(format!("./csl_styles/{}.csl", style));
+
+        for case in fixture["cases"].as_array().unwrap() {
+            let mut citation_paths = HashSet::new();
+            citation_paths.insert(PathBuf::from(format!(
+                "./src/html/citeproc/test_files/compat/{}",
+                case["items"].as_str().unwrap()
+            )));
+            let doc_locale = Locale::from_str(case["locale"].as_str().unwrap()).unwrap();
+            let doc = test_document(citation_paths, doc_locale, HashMap::new(), style_id.clone());
+            let mut under_test = CiteprocWrapper::new();
+
+            if let Some(id) = case["author_only"].as_str() {
+                let actual = under_test.get_author_only(&doc, id.to_string()).unwrap();
+                assert_eq!(
+                    actual, case["expected"],
+                    "author-only of '{}' in '{}'",
+                    id, style
+                );
+                continue;
+            }
+
+            let clusters: Vec<Vec<String>> =
+                serde_json::from_value(case["clusters"].clone()).unwrap();
+            let citations: Vec<Vec<CitationEntry>> = clusters
+                .iter()
+                .map(|ids| ids.iter().map(CitationEntry::new).collect())
+                .collect();
+            let for_pagedjs = case["for_pagedjs"].as_bool().unwrap();
+            let context = format!("{} ({}, pagedjs: {})", style, case["locale"], for_pagedjs);
+
+            let actual_citations = under_test
+                .get_citation_strings(&doc, &citations, for_pagedjs)
+                .unwrap();
+            let expected_citations: Vec<String> =
+                serde_json::from_value(case["citations"].clone()).unwrap();
+            assert_eq!(
+                actual_citations, expected_citations,
+                "citations of {}",
+                context
+            );
+
+            let actual_footnotes = under_test.get_footnotes().unwrap();
+            assert_eq!(
+                actual_footnotes, case["footnotes"],
+                "footnotes of {}",
+                context
+            );
+
+            let actual_bibliography = under_test.get_bibliography();
+            match case["bibliography"].as_str() {
+                Some(expected) => assert_eq!(
+                    actual_bibliography.unwrap(),
+                    expected,
+                    "bibliography of {}",
+                    context
+                ),
+                None => assert!(actual_bibliography.is_err(), "bibliography of {}", context),
+            }
+        }
+    }
+
+    #[test]
+    fn compat_american_medical_association() {
+        run_compat_fixture("american-medical-association");
+    }
+
+    #[test]
+    fn compat_apa() {
+        run_compat_fixture("apa");
+    }
+
+    #[test]
+    fn compat_bluebook_inline() {
+        run_compat_fixture("bluebook-inline");
+    }
+
+    #[test]
+    fn compat_chicago_fullnote_bibliography() {
+        run_compat_fixture("chicago-fullnote-bibliography");
+    }
+
+    #[test]
+    fn compat_council_of_science_editors() {
+        run_compat_fixture("council-of-science-editors");
+    }
+
+    #[test]
+    fn compat_harvard_cite_them_right() {
+        run_compat_fixture("harvard-cite-them-right");
+    }
+
+    #[test]
+    fn compat_ieee() {
+        run_compat_fixture("ieee");
+    }
+
+    #[test]
+    fn compat_turabian_author_date() {
+        run_compat_fixture("turabian-author-date");
+    }
 }
@@ -0,0 +1,504 @@
+//! Rendered output of the processor and its conversion to HTML.
+//!
+//! Elements are rendered into a tree of [`Inline`] nodes. Before the tree is written as HTML, it is
+//! flattened and punctuation is cleaned up across node boundaries, so that e.g. a suffix `.`
+//! following a title that ends in `?` is dropped.
+
+use super::locale::Locale;
+use super::style::{Formatting, TextCase};
+
+#[derive(Clone, Debug)]
+pub(crate) enum Inline {
+    Text(String),
+    Fmt(Formatting, Vec<Inline>),
+    Quoted(Vec<Inline>),
+    Display(String, Vec<Inline>),
+    /// Output of a `cs:names` element, which is replaced by subsequent-author-substitute.
+    Names(Vec<Inline>),
+}
+
+pub(crate) fn is_empty(nodes: &[Inline]) -> bool {
+    nodes.iter().all(|node| match node {
+        Inline::Text(text) => text.is_empty(),
+        Inline::Fmt(_, children)
+        | Inline::Quoted(children)
+        | Inline::Display(_, children)
+        | Inline::Names(children) => is_empty(children),
+    })
+}
+
+/// Text content without formatting, with quotes written as `"`.
+pub(crate) fn plain_text(nodes: &[Inline]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        match node {
+            Inline::Text(value) => text.push_str(value),
+            Inline::Fmt(_, children) | Inline::Display(_, children) | Inline::Names(children) => {
+                text.push_str(&plain_text(children))
+            }
+            Inline::Quoted(children) => {
+                text.push('"');
+                text.push_str(&plain_text(children));
+                text.push('"');
+            }
+        }
+    }
+    text
+}
+
+fn texts_mut(nodes: &mut [Inline]) -> Vec<&mut String> {
+    let mut texts = Vec::new();
+    for node in nodes {
+        match node {
+            Inline::Text(value) => texts.push(value),
+            Inline::Fmt(_, children)
+            | Inline::Quoted(children)
+            | Inline::Display(_, children)
+            | Inline::Names(children) => texts.extend(texts_mut(children)),
+        }
+    }
+    texts
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CaseChange {
+    Keep,
+    Lower,
+    Upper,
+}
+
+/// Words that stay lowercase in title case.
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "above", "across", "against", "along", "among", "an", "and", "around", "as",
+    "at", "before", "behind", "below", "beneath", "beside", "between", "beyond", "but", "by",
+    "down", "during", "for", "from", "in", "inside", "into", "nor", "of", "off", "on", "onto",
+    "or", "out", "over", "per", "since", "so", "than", "the", "through", "till", "to", "toward",
+    "towards", "under", "until", "up", "upon", "via", "vs", "with", "within", "without", "yet",
+];
+
+/// Applies the text case to all text of the nodes, treating them as one string.
+pub(crate) fn apply_text_case(nodes: &mut [Inline], case: TextCase) {
+    let mut texts = texts_mut(nodes);
+    let full: Vec<char> = texts.iter().flat_map(|text| text.chars()).collect();
+    let changes = case_changes(&full, case);
+
+    let mut index = 0;
+    for text in texts.iter_mut() {
+        let mut changed = String::with_capacity(text.len());
+        for c in text.chars() {
+            match changes[index] {
+                CaseChange::Keep => changed.push(c),
+                CaseChange::Lower => changed.extend(c.to_lowercase()),
+                CaseChange::Upper => changed.extend(c.to_uppercase()),
+            }
+            index += 1;
+        }
+        **text = changed;
+    }
+}
+
+fn case_changes(full: &[char], case: TextCase) -> Vec<CaseChange> {
+    let mut changes = vec![CaseChange::Keep; full.len()];
+    let first_letter = full.iter().position(|c| c.is_alphabetic());
+    let all_upper = !full.iter().any(|c| c.is_lowercase());
+
+    match case {
+        TextCase::Lowercase => changes.fill(CaseChange::Lower),
+        TextCase::Uppercase => changes.fill(CaseChange::Upper),
+        TextCase::CapitalizeFirst => {
+            if let Some(index) = first_letter {
+                changes[index] = CaseChange::Upper;
+            }
+        }
+        TextCase::Sentence => {
+            if all_upper {
+                changes.fill(CaseChange::Lower);
+            }
+            if let Some(index) = first_letter {
+                changes[index] = CaseChange::Upper;
+            }
+        }
+        TextCase::CapitalizeAll => {
+            for (start, _) in words(full) {
+                if let Some(offset) = full[start..].iter().position(|c| c.is_alphabetic()) {
+                    changes[start + offset] = CaseChange::Upper;
+                }
+            }
+        }
+        TextCase::Title => {
+            if all_upper {
+                changes.fill(CaseChange::Lower);
+            }
+            let words = words(full);
+            let count = words.len();
+            let mut after_colon = false;
+            for (number, (start, end)) in words.into_iter().enumerate() {
+                let word: String = full[start..end]
+                    .iter()
+                    .map(|c| {
+                        if all_upper {
+                            c.to_ascii_lowercase()
+                        } else {
+                            *c
+                        }
+                    })
+                    .collect();
+                let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+                let is_edge = number == 0 || number + 1 == count || after_colon;
+                after_colon = word.ends_with(':');
+                if !is_edge && STOP_WORDS.contains(&core) {
+                    continue;
+                }
+                // Capitalize each part of hyphenated words, but keep words with inner capitals.
+                let mut part_start = true;
+                for (offset, c) in word.chars().enumerate() {
+                    if c.is_alphabetic() {
+                        let has_inner_capitals = word
+                            .chars()
+                            .skip(offset + 1)
+                            .take_while(|c| *c != '-' && *c != '/')
+                            .any(char::is_uppercase);
+                        if part_start && c.is_lowercase() && !has_inner_capitals {
+                            changes[start + offset] = CaseChange::Upper;
+                        }
+                        part_start = false;
+                    } else if c == '-' || c == '/' {
+                        part_start = true;
+                    } else if c.is_numeric() {
+                        part_start = false;
+                    }
+                }
+            }
+        }
+    }
+    changes
+}
+
+/// Start and end indices of the whitespace separated words.
+fn words(full: &[char]) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (index, c) in full.iter().enumerate() {
+        match (c.is_whitespace(), start) {
+            (true, Some(begin)) => {
+                words.push((begin, index));
+                start = None;
+            }
+            (false, None) => start = Some(index),
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        words.push((begin, full.len()));
+    }
+    words
+}
+
+/// Flattened output: text, formatting tags and quote marks.
+#[derive(Clone, Debug)]
+enum Atom {
+    Text(String),
+    /// Formatting tag, which punctuation is merged across.
+    Tag(String),
+    /// Quote mark or display wrapper, which stops punctuation merging.
+    Boundary(String),
+    CloseQuote(String),
+}
+
+#[derive(Clone, Copy, Default)]
+struct Inherited {
+    italic: bool,
+    small_caps: bool,
+    bold: bool,
+    underline: bool,
+    vertical: bool,
+    quote_depth: usize,
+}
+
+struct Flattener<'a> {
+    locale: &'a Locale,
+    atoms: Vec<Atom>,
+}
+
+impl Flattener<'_> {
+    fn quote(&self, name: &str, default: &str) -> String {
+        self.locale
+            .term(name, super::style::TermForm::Long, false)
+            .unwrap_or(default)
+            .to_string()
+    }
+
+    fn flatten(&mut self, nodes: &[Inline], inherited: &mut Inherited) {
+        for node in nodes {
+            match node {
+                Inline::Text(text) => {
+                    if !text.is_empty() {
+                        self.atoms.push(Atom::Text(text.clone()));
+                    }
+                }
+                Inline::Names(children) => self.flatten(children, inherited),
+                Inline::Fmt(formatting, children) => {
+                    if is_empty(children) {
+                        continue;
+                    }
+                    let saved = *inherited;
+                    let tags = self.open_tags(formatting, inherited);
+                    for (open, _) in &tags {
+                        self.atoms.push(Atom::Tag(open.clone()));
+                    }
+                    self.flatten(children, inherited);
+                    for (_, close) in tags.iter().rev() {
+                        self.atoms.push(Atom::Tag(close.clone()));
+                    }
+                    *inherited = saved;
+                }
+                Inline::Quoted(children) => {
+                    if is_empty(children) {
+                        continue;
+                    }
+                    let (open, close) = if inherited.quote_depth.is_multiple_of(2) {
+                        (
+                            self.quote("open-quote", "“"),
+                            self.quote("close-quote", "”"),
+                        )
+                    } else {
+                        (
+                            self.quote("open-inner-quote", "‘"),
+                            self.quote("close-inner-quote", "’"),
+                        )
+                    };
+                    self.atoms.push(Atom::Boundary(open));
+                    inherited.quote_depth += 1;
+                    self.flatten(children, inherited);
+                    inherited.quote_depth -= 1;
+                    self.atoms.push(Atom::CloseQuote(close));
+                }
+                Inline::Display(display, children) => {
+                    if is_empty(children) {
+                        continue;
+                    }
+                    let (open, close) = match display.as_str() {
+                        "block" => ("\n\n    <div class=\"csl-block\">", "</div>\n"),
+                        "left-margin" => ("\n    <div class=\"csl-left-margin\">", "</div>"),
+                        "right-inline" => ("<div class=\"csl-right-inline\">", "</div>\n  "),
+                        _ => ("<div class=\"csl-indent\">", "</div>\n  "),
+                    };
+                    self.atoms.push(Atom::Boundary(open.to_string()));
+                    self.flatten(children, inherited);
+                    self.atoms.push(Atom::Boundary(close.to_string()));
+                }
+            }
+        }
+    }
+
+    fn open_tags(
+        &self,
+        formatting: &Formatting,
+        inherited: &mut Inherited,
+    ) -> Vec<(String, String)> {
+        let span = |style: &str| (format!("<span style=\"{}\">", style), "</span>".to_string());
+        let mut tags = Vec::new();
+        match formatting.font_style.as_deref() {
+            Some("italic") if inherited.italic => {
+                tags.push(span("font-style:normal;"));
+                inherited.italic = false;
+            }
+            Some("italic") => {
+                tags.push(("<i>".to_string(), "</i>".to_string()));
+                inherited.italic = true;
+            }
+            Some("oblique") => tags.push(("<em>".to_string(), "</em>".to_string())),
+            Some("normal") if inherited.italic => {
+                tags.push(span("font-style:normal;"));
+                inherited.italic = false;
+            }
+            _ => {}
+        }
+        match formatting.font_variant.as_deref() {
+            Some("small-caps") if !inherited.small_caps => {
+                tags.push(span("font-variant:small-caps;"));
+                inherited.small_caps = true;
+            }
+            Some("normal") if inherited.small_caps => {
+                tags.push(span("font-variant:normal;"));
+                inherited.small_caps = false;
+            }
+            _ => {}
+        }
+        match formatting.font_weight.as_deref() {
+            Some("bold") if !inherited.bold => {
+                tags.push(("<b>".to_string(), "</b>".to_string()));
+                inherited.bold = true;
+            }
+            Some("light") => tags.push(span("font-weight:lighter;")),
+            Some("normal") if inherited.bold => {
+                tags.push(span("font-weight:normal;"));
+                inherited.bold = false;
+            }
+            _ => {}
+        }
+        match formatting.text_decoration.as_deref() {
+            Some("underline") if !inherited.underline => {
+                tags.push(span("text-decoration:underline;"));
+                inherited.underline = true;
+            }
+            Some("none") if inherited.underline => {
+                tags.push(span("text-decoration:none;"));
+                inherited.underline = false;
+            }
+            _ => {}
+        }
+        match formatting.vertical_align.as_deref() {
+            Some("sup") => {
+                tags.push(("<sup>".to_string(), "</sup>".to_string()));
+                inherited.vertical = true;
+            }
+            Some("sub") => {
+                tags.push(("<sub>".to_string(), "</sub>".to_string()));
+                inherited.vertical = true;
+            }
+            Some("baseline") if inherited.vertical => {
+                tags.push(span("baseline"));
+                inherited.vertical = false;
+            }
+            _ => {}
+        }
+        tags
+    }
+}
+
+/// Result of two adjacent punctuation characters, if they are merged.
+fn merge_punctuation(left: char, right: char) -> Option<char> {
+    match (left, right) {
+        ('!', '.') | (':', '!') | (';', '!') => Some('!'),
+        ('?', '.') | ('?', ':') | (':', '?') | (';', '?') => Some('?'),
+        (':', '.') => Some(':'),
+        (';', '.') | (';', ':') => Some(';'),
+        (left, right) if left == right && ".,;:!?".contains(left) => Some(left),
+        _ => None,
+    }
+}
+
+/// Moves periods and commas following a closing quote inside of it.
+fn move_punctuation_into_quotes(atoms: &mut [Atom]) {
+    for index in 0..atoms.len() {
+        if !matches!(atoms[index], Atom::CloseQuote(_)) {
+            continue;
+        }
+        let Some(next) = (index + 1..atoms.len()).find(|&i| !matches!(atoms[i], Atom::Tag(_)))
+        else {
+            continue;
+        };
+        let punctuation = match &mut atoms[next] {
+            Atom::Text(text) if text.starts_with(['.', ',']) => text.remove(0),
+            _ => continue,
+        };
+        if let Some(Atom::Text(text)) = atoms[..index]
+            .iter_mut()
+            .rev()
+            .find(|atom| !matches!(atom, Atom::Tag(_)))
+        {
+            match text.pop() {
+                Some(last) => match merge_punctuation(last, punctuation) {
+                    Some(merged) => text.push(merged),
+                    None if ".,;:!?".contains(last) && punctuation == ',' => text.push(last),
+                    None => {
+                        text.push(last);
+                        text.push(punctuation);
+                    }
+                },
+                None => text.push(punctuation),
+            }
+        }
+    }
+}
+
+/// Removes the second of two spaces that meet at the boundary of two texts.
+fn collapse_adjacent_spaces(atoms: &mut [Atom]) {
+    let mut previous_space = false;
+    for atom in atoms.iter_mut() {
+        match atom {
+            Atom::Tag(_) => {}
+            Atom::Text(text) => {
+                if previous_space && text.starts_with(' ') {
+                    text.remove(0);
+                }
+                if !text.is_empty() {
+                    previous_space = text.ends_with(' ');
+                }
+            }
+            Atom::Boundary(_) | Atom::CloseQuote(_) => previous_space = false,
+        }
+    }
+}
+
+fn merge_adjacent_punctuation(atoms: &mut [Atom]) {
+    let mut previous: Option<usize> = None;
+    for index in 0..atoms.len() {
+        match &atoms[index] {
+            Atom::Tag(_) => continue,
+            Atom::Boundary(_) | Atom::CloseQuote(_) => {
+                previous = None;
+                continue;
+            }
+            Atom::Text(_) => {}
+        }
+        if let Some(prev) = previous {
+            let (head, tail) = atoms.split_at_mut(index);
+            if let (Atom::Text(left), Atom::Text(right)) = (&mut head[prev], &mut tail[0]) {
+                if let (Some(l), Some(r)) = (left.chars().last(), right.chars().next()) {
+                    if let Some(merged) = merge_punctuation(l, r) {
+                        left.pop();
+                        left.push(merged);
+                        right.remove(0);
+                    }
+                }
+            }
+        }
+        if let Atom::Text(text) = &atoms[index] {
+            if !text.is_empty() {
+                previous = Some(index);
+            }
+        }
+    }
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&#38;"),
+            '<' => escaped.push_str("&#60;"),
+            '>' => escaped.push_str("&#62;"),
+            '\'' => escaped.push('’'),
+            c => escaped.push(c),
+        }
+    }
+    escaped.replace("  ", "\u{a0} ")
+}
+
+/// Writes the nodes as HTML, where `collapse_spaces` joins spaces that meet between two texts
+/// as it is done for bibliography entries.
+pub(crate) fn to_html(nodes: &[Inline], locale: &Locale, collapse_spaces: bool) -> String {
+    let mut flattener = Flattener {
+        locale,
+        atoms: Vec::new(),
+    };
+    flattener.flatten(nodes, &mut Inherited::default());
+    let mut atoms = flattener.atoms;
+    if locale.punctuation_in_quote {
+        move_punctuation_into_quotes(&mut atoms);
+    }
+    merge_adjacent_punctuation(&mut atoms);
+    if collapse_spaces {
+        collapse_adjacent_spaces(&mut atoms);
+    }
+
+    atoms
+        .into_iter()
+        .map(|atom| match atom {
+            Atom::Text(text) => escape(&text),
+            Atom::Tag(tag) | Atom::Boundary(tag) | Atom::CloseQuote(tag) => tag,
+        })
+        .collect()
+}
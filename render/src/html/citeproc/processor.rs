@@ -0,0 +1,799 @@
+//! Processing of citation clusters and the bibliography.
+//!
+//! The [`Processor`] keeps the state that spans all citations of a document: the order in which
+//! references were cited, their citation numbers and how they are disambiguated.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+use super::eval::{CiteState, Evaluator, GivenName, Mode};
+use super::locale::Locale;
+use super::output::{self, Inline};
+use super::reference::Reference;
+use super::style::{
+    Attrs, Collapse, GivennameRule, Position, SortKey, Style, StyleClass, StyleError, TermForm,
+};
+
+/// Delimiter that follows each delimiter between cites of a rendered cluster.
+pub(crate) const CITE_SEPARATOR: char = '\u{1f}';
+
+/// Text of a cite that renders nothing.
+const NO_PRINTED_FORM: &str = "[CSL STYLE ERROR: reference with no printed form.]";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UnknownReference(pub String);
+
+/// How a cited reference is rendered to distinguish it from other references.
+#[derive(Clone, Debug, Default)]
+struct Disambiguation {
+    names_use_first: Option<usize>,
+    given_name: GivenName,
+    given_name_primary_only: bool,
+    disambiguate: bool,
+    year_suffix: Option<String>,
+}
+
+/// A cite of a citation cluster.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CiteItem {
+    pub id: String,
+    pub locator: Option<String>,
+    /// Locator type, e.g. `page` or `chapter`.
+    pub label: Option<String>,
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    pub suppress_author: bool,
+    /// Only the names of the reference are rendered.
+    pub author_only: bool,
+}
+
+impl CiteItem {
+    /// Returns `true` if the cite is rendered with more than the style defines.
+    fn has_extras(&self) -> bool {
+        self.locator.is_some() || self.prefix.is_some() || self.suffix.is_some()
+    }
+}
+
+/// A rendered bibliography entry.
+pub(crate) struct BibliographyEntry {
+    pub id: String,
+    pub html: String,
+    /// Text of the first field if the style aligns the second field.
+    pub first_field: Option<String>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Processor {
+    style: Style,
+    locale: Locale,
+    lang: String,
+    references: HashMap<String, Reference>,
+    /// Ids of cited references in the order they were first cited.
+    cited: Vec<String>,
+    citation_numbers: HashMap<String, usize>,
+    disambiguation: HashMap<String, Disambiguation>,
+}
+
+impl Processor {
+    /// Creates a processor for the given style and locale, where `items` are the CSL-JSON items.
+    pub(crate) fn new(
+        style_text: &str,
+        locale_text: &str,
+        items: &[Value],
+    ) -> Result<Processor, StyleError> {
+        let style = Style::parse(style_text)?;
+        let lang = style
+            .default_locale
+            .clone()
+            .unwrap_or_else(|| "en-US".to_string());
+        let locale = Locale::new(locale_text, &style.locales, &lang);
+        let references = items
+            .iter()
+            .filter_map(Reference::from_value)
+            .map(|reference| (reference.id.clone(), reference))
+            .collect();
+        Ok(Processor {
+            style,
+            locale,
+            lang,
+            references,
+            cited: Vec::new(),
+            citation_numbers: HashMap::new(),
+            disambiguation: HashMap::new(),
+        })
+    }
+
+    pub(crate) fn is_note_style(&self) -> bool {
+        self.style.class == StyleClass::Note
+    }
+
+    /// Returns `true` if a reference with the given id is loaded.
+    pub(crate) fn has_reference(&self, id: &str) -> bool {
+        self.references.contains_key(id)
+    }
+
+    pub(crate) fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    pub(crate) fn style(&self) -> &Style {
+        &self.style
+    }
+
+    /// Renders the given citation clusters.
+    ///
+    /// The delimiter between two cites of a cluster is followed by [`CITE_SEPARATOR`].
+    pub(crate) fn process_citations(
+        &mut self,
+        clusters: &[Vec<CiteItem>],
+    ) -> Result<Vec<String>, UnknownReference> {
+        for CiteItem { id, .. } in clusters.iter().flatten() {
+            if !self.references.contains_key(id) {
+                return Err(UnknownReference(id.clone()));
+            }
+            if !self.cited.contains(id) {
+                self.cited.push(id.clone());
+            }
+        }
+        self.assign_citation_numbers();
+        self.disambiguate();
+
+        let mut first_notes: HashMap<String, usize> = HashMap::new();
+        let mut last_notes: HashMap<String, usize> = HashMap::new();
+        let mut previous: Vec<CiteItem> = Vec::new();
+        let mut rendered = Vec::new();
+        for (index, cluster) in clusters.iter().enumerate() {
+            let note = index + 1;
+            let items = self.sort_items(cluster);
+            let mut states = Vec::new();
+            for (position_in_cluster, item) in items.iter().enumerate() {
+                let id = &item.id;
+                let preceding = if position_in_cluster > 0 {
+                    Some(&items[position_in_cluster - 1])
+                } else if previous.len() == 1 {
+                    previous.first()
+                } else {
+                    None
+                };
+                let position = match preceding {
+                    _ if !first_notes.contains_key(id) => Position::First,
+                    Some(preceding) if &preceding.id == id => {
+                        match (&preceding.locator, &item.locator) {
+                            (None, None) => Position::Ibid,
+                            (_, None) => Position::Subsequent,
+                            (Some(before), Some(current)) if before == current => Position::Ibid,
+                            _ => Position::IbidWithLocator,
+                        }
+                    }
+                    _ => Position::Subsequent,
+                };
+                let mut state = self.cite_state(id);
+                state.position = position;
+                state.locator = item.locator.clone();
+                state.label = item.label.clone();
+                state.suppress_author = item.suppress_author;
+                state.near_note = last_notes
+                    .get(id)
+                    .is_some_and(|last| note - last <= self.style.citation.near_note_distance);
+                state.first_reference_note_number = first_notes.get(id).copied();
+                first_notes.entry(id.clone()).or_insert(note);
+                last_notes.insert(id.clone(), note);
+                states.push(state);
+            }
+            rendered.push(self.render_cluster(&items, &states));
+            previous = items;
+        }
+        Ok(rendered)
+    }
+
+    /// Renders only the names of the reference as they would appear in a first citation,
+    /// if it was the only cited reference.
+    /// The names are returned as plain text, so renderers must escape them for their output format.
+    ///
+    /// Cited references, citation numbers and disambiguation of the processor are restored afterwards.
+    pub(crate) fn author_only(&mut self, id: &str) -> Result<String, UnknownReference> {
+        if !self.references.contains_key(id) {
+            return Err(UnknownReference(id.to_string()));
+        }
+        let cited = std::mem::replace(&mut self.cited, vec![id.to_string()]);
+        let citation_numbers = std::mem::take(&mut self.citation_numbers);
+        let disambiguation = std::mem::take(&mut self.disambiguation);
+        self.assign_citation_numbers();
+        self.disambiguate();
+
+        let state = self.cite_state(id);
+        let nodes = self.render_cite(id, &state);
+        let names = first_names(&nodes).map(output::plain_text);
+
+        self.cited = cited;
+        self.citation_numbers = citation_numbers;
+        self.disambiguation = disambiguation;
+        Ok(match names {
+            Some(names) if !names.is_empty() => names,
+            _ => "[NO_PRINTED_FORM]".to_string(),
+        })
+    }
+
+    /// Renders the bibliography entries of the cited references with one of the given ids, or of
+    /// all cited references if no ids are given.
+    ///
+    /// Citation numbers and disambiguation stay the same as for the whole document.
+    pub(crate) fn bibliography_of(
+        &self,
+        ids: Option<&HashSet<String>>,
+    ) -> Option<Vec<BibliographyEntry>> {
+        let bibliography = self.style.bibliography.as_ref()?;
+        let layout = &bibliography.layout;
+        let cited: Vec<String> = self
+            .cited
+            .iter()
+            .filter(|id| ids.is_none_or(|ids| ids.contains(*id)))
+            .cloned()
+            .collect();
+        let mut entries = Vec::new();
+        let mut previous_names: Option<String> = None;
+        for id in self.sort_ids(&cited, &bibliography.sort, true) {
+            let reference = &self.references[&id];
+            let mut state = self.cite_state(&id);
+            state.names_use_first = None;
+            let evaluator = Evaluator::new(
+                &self.style,
+                &self.locale,
+                reference,
+                &state,
+                Mode::Bibliography,
+                &self.lang,
+            );
+
+            let mut first_field = None;
+            let mut nodes = if bibliography.second_field_align.is_some() {
+                let mut parts = evaluator.render_layout_parts(&layout.children).into_iter();
+                let mut nodes = Vec::new();
+                if let Some(first) = parts.next() {
+                    first_field = Some(output::plain_text(&first));
+                    nodes.push(Inline::Display("left-margin".to_string(), first));
+                }
+                let mut rest: Vec<Inline> = parts.flatten().collect();
+                if !layout.attrs.suffix.is_empty() {
+                    rest.push(Inline::Text(layout.attrs.suffix.clone()));
+                }
+                nodes.push(Inline::Display("right-inline".to_string(), rest));
+                nodes
+            } else {
+                let nodes = evaluator.render_layout(&layout.children);
+                wrap_layout(nodes, &layout.attrs, &layout.attrs.suffix)
+            };
+
+            // Only the rules that substitute whole name lists are supported.
+            let complete = matches!(
+                bibliography.subsequent_author_substitute_rule.as_str(),
+                "complete-all" | "complete-each"
+            );
+            if let Some(substitute) = bibliography
+                .subsequent_author_substitute
+                .as_ref()
+                .filter(|_| complete)
+            {
+                let names = first_names(&nodes).map(output::plain_text);
+                if names.is_some() && names == previous_names {
+                    replace_first_names(&mut nodes, substitute);
+                }
+                previous_names = names;
+            }
+
+            entries.push(BibliographyEntry {
+                id,
+                html: format!(
+                    "  <div class=\"csl-entry\">{}</div>\n",
+                    output::to_html(&nodes, &self.locale, true)
+                ),
+                first_field,
+            });
+        }
+        Some(entries)
+    }
+
+    fn cite_state(&self, id: &str) -> CiteState {
+        let disambiguation = self.disambiguation.get(id).cloned().unwrap_or_default();
+        CiteState {
+            citation_number: self.citation_numbers.get(id).copied().unwrap_or_default(),
+            year_suffix: disambiguation.year_suffix,
+            disambiguate: disambiguation.disambiguate,
+            names_use_first: disambiguation.names_use_first,
+            given_name: disambiguation.given_name,
+            given_name_primary_only: disambiguation.given_name_primary_only,
+            ..Default::default()
+        }
+    }
+
+    fn render_cite(&self, id: &str, state: &CiteState) -> Vec<Inline> {
+        let evaluator = Evaluator::new(
+            &self.style,
+            &self.locale,
+            &self.references[id],
+            state,
+            Mode::Citation,
+            &self.lang,
+        );
+        evaluator.render_layout(&self.style.citation.layout.children)
+    }
+
+    /// Renders a cite with the affixes of the given item.
+    fn render_item(&self, item: &CiteItem, state: &CiteState) -> Vec<Inline> {
+        let mut nodes = self.render_cite(&item.id, state);
+        if item.author_only {
+            nodes = first_names(&nodes)
+                .map(<[Inline]>::to_vec)
+                .unwrap_or_default();
+        }
+        if output::is_empty(&nodes) {
+            return nodes;
+        }
+        if let Some(prefix) = &item.prefix {
+            nodes.insert(0, Inline::Text(format!("{} ", prefix.trim_end())));
+        }
+        if let Some(suffix) = &item.suffix {
+            let suffix = suffix.trim_start();
+            let separator = if suffix.starts_with([',', ';', '.', ':']) {
+                ""
+            } else {
+                " "
+            };
+            nodes.push(Inline::Text(format!("{}{}", separator, suffix)));
+        }
+        nodes
+    }
+
+    /// Sorts the items of a cluster by the citation sort keys of the style.
+    fn sort_items(&self, items: &[CiteItem]) -> Vec<CiteItem> {
+        let ids: Vec<String> = items.iter().map(|item| item.id.clone()).collect();
+        let mut remaining: Vec<&CiteItem> = items.iter().collect();
+        self.sort_ids(&ids, &self.style.citation.sort, false)
+            .iter()
+            .filter_map(|id| {
+                let index = remaining.iter().position(|item| &item.id == id)?;
+                Some(remaining.remove(index).clone())
+            })
+            .collect()
+    }
+
+    fn render_cluster(&self, items: &[CiteItem], states: &[CiteState]) -> String {
+        let citation = &self.style.citation;
+        let layout_delimiter = citation.layout.delimiter.as_str();
+        let cites: Vec<Vec<Inline>> = items
+            .iter()
+            .zip(states)
+            .map(|(item, state)| self.render_item(item, state))
+            .collect();
+
+        let mut nodes = Vec::new();
+        match citation.collapse {
+            Some(Collapse::CitationNumber) => {
+                let numbers: Vec<usize> =
+                    states.iter().map(|state| state.citation_number).collect();
+                let range_delimiter = self
+                    .locale
+                    .term("citation-range-delimiter", TermForm::Long, false)
+                    .unwrap_or("–")
+                    .to_string();
+                let mut start = 0;
+                while start < cites.len() {
+                    let mut end = start;
+                    // Cites with locators or affixes are not part of a range.
+                    while end + 1 < cites.len()
+                        && numbers[end + 1] == numbers[end] + 1
+                        && !items[end].has_extras()
+                        && !items[end + 1].has_extras()
+                    {
+                        end += 1;
+                    }
+                    if !nodes.is_empty() {
+                        // Cites with extras are delimited like cites that are not collapsed.
+                        let separator =
+                            if items[start - 1].has_extras() || items[start].has_extras() {
+                                CITE_SEPARATOR.to_string()
+                            } else {
+                                String::new()
+                            };
+                        nodes.push(Inline::Text(format!("{}{}", layout_delimiter, separator)));
+                    }
+                    if end - start >= 2 {
+                        nodes.extend(cites[start].clone());
+                        nodes.push(Inline::Text(range_delimiter.clone()));
+                        nodes.extend(cites[end].clone());
+                    } else {
+                        for (offset, cite) in cites[start..=end].iter().enumerate() {
+                            if offset > 0 {
+                                nodes.push(Inline::Text(layout_delimiter.to_string()));
+                            }
+                            nodes.extend(cite.clone());
+                        }
+                    }
+                    start = end + 1;
+                }
+            }
+            collapse => {
+                let order = match collapse {
+                    Some(_) => group_by_author(&cites),
+                    None => (0..cites.len()).map(|index| (index, false)).collect(),
+                };
+                let mut last_collapsed = false;
+                for (position, (index, collapsed)) in order.into_iter().enumerate() {
+                    let mut cite = if collapsed {
+                        let state = CiteState {
+                            suppress_author: true,
+                            ..states[index].clone()
+                        };
+                        self.render_item(&items[index], &state)
+                    } else {
+                        cites[index].clone()
+                    };
+                    if output::is_empty(&cite) {
+                        if position > 0 {
+                            continue;
+                        }
+                        cite = vec![Inline::Text(NO_PRINTED_FORM.to_string())];
+                    }
+                    if !nodes.is_empty() {
+                        let delimiter = match &citation.after_collapse_delimiter {
+                            Some(after_collapse) if last_collapsed && !collapsed => after_collapse,
+                            Some(after_collapse)
+                                if !last_collapsed
+                                    && !collapsed
+                                    && collapse != Some(Collapse::YearSuffix) =>
+                            {
+                                after_collapse
+                            }
+                            Some(_) => layout_delimiter,
+                            None if collapsed => {
+                                citation.cite_group_delimiter.as_deref().unwrap_or(", ")
+                            }
+                            None => layout_delimiter,
+                        };
+                        nodes.push(Inline::Text(format!("{}{}", delimiter, CITE_SEPARATOR)));
+                    }
+                    nodes.extend(cite);
+                    last_collapsed = collapsed;
+                }
+            }
+        }
+
+        // Clusters that only show authors are not wrapped by the layout.
+        if items.iter().all(|item| item.author_only) {
+            return output::to_html(&nodes, &self.locale, false);
+        }
+        let attrs = &citation.layout.attrs;
+        // A suffix starting with terminal punctuation is reduced to that punctuation.
+        let suffix = match attrs.suffix.chars().next() {
+            Some(first) if ":.;!?".contains(first) => first.to_string(),
+            _ => attrs.suffix.clone(),
+        };
+        output::to_html(&wrap_layout(nodes, attrs, &suffix), &self.locale, false)
+    }
+
+    fn assign_citation_numbers(&mut self) {
+        self.citation_numbers = self
+            .cited
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (id.clone(), index + 1))
+            .collect();
+        if let Some(bibliography) = &self.style.bibliography {
+            if !bibliography.sort.is_empty() {
+                let sorted = self.sort_ids(&self.cited, &bibliography.sort, true);
+                self.citation_numbers = sorted
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, id)| (id, index + 1))
+                    .collect();
+            }
+        }
+    }
+
+    /// Sorts the ids by the given keys, keeping the original order of equal entries.
+    fn sort_ids(&self, ids: &[String], keys: &[SortKey], bibliography: bool) -> Vec<String> {
+        if keys.is_empty() {
+            return ids.to_vec();
+        }
+        let values: Vec<Vec<String>> = ids
+            .iter()
+            .map(|id| {
+                let state = self.cite_state(id);
+                keys.iter()
+                    .map(|key| {
+                        let mut evaluator = Evaluator::new(
+                            &self.style,
+                            &self.locale,
+                            &self.references[id],
+                            &state,
+                            Mode::Sort,
+                            &self.lang,
+                        );
+                        if bibliography {
+                            evaluator = evaluator.in_bibliography();
+                        }
+                        normalize_sort_key(&evaluator.sort_value(key))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut order: Vec<usize> = (0..ids.len()).collect();
+        order.sort_by(|&a, &b| {
+            for (index, key) in keys.iter().enumerate() {
+                let (left, right) = (&values[a][index], &values[b][index]);
+                let ordering = match (left.is_empty(), right.is_empty()) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    (false, false) if key.ascending => left.cmp(right),
+                    (false, false) => right.cmp(left),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        });
+        order.into_iter().map(|index| ids[index].clone()).collect()
+    }
+
+    fn disambiguation_text(&self, id: &str) -> String {
+        output::plain_text(&self.render_cite(id, &self.cite_state(id)))
+    }
+
+    /// Number of distinct first-position cites among the ids.
+    fn distinct_cites(&self, ids: &[String]) -> usize {
+        let mut texts: Vec<String> = ids.iter().map(|id| self.disambiguation_text(id)).collect();
+        texts.sort();
+        texts.dedup();
+        texts.len()
+    }
+
+    /// Applies `change` to the ids and keeps it if more cites become distinct.
+    fn try_disambiguation(
+        &mut self,
+        ids: &[String],
+        best: &mut usize,
+        change: impl Fn(&mut Disambiguation),
+    ) -> bool {
+        let saved: Vec<Disambiguation> = ids
+            .iter()
+            .map(|id| self.disambiguation[id].clone())
+            .collect();
+        for id in ids {
+            if let Some(disambiguation) = self.disambiguation.get_mut(id) {
+                change(disambiguation);
+            }
+        }
+        let distinct = self.distinct_cites(ids);
+        if distinct > *best {
+            *best = distinct;
+            return true;
+        }
+        for (id, disambiguation) in ids.iter().zip(saved) {
+            self.disambiguation.insert(id.clone(), disambiguation);
+        }
+        false
+    }
+
+    /// Finds cited references whose cites look the same and applies the disambiguation methods
+    /// of the style to them.
+    fn disambiguate(&mut self) {
+        self.disambiguation = self
+            .cited
+            .iter()
+            .map(|id| (id.clone(), Disambiguation::default()))
+            .collect();
+        let citation = self.style.citation.clone();
+        let bibliography_order = match &self.style.bibliography {
+            Some(bibliography) => self.sort_ids(&self.cited, &bibliography.sort, true),
+            None => self.cited.clone(),
+        };
+
+        for group in self.ambiguous_groups(&self.cited) {
+            let mut best = self.distinct_cites(&group);
+
+            if citation.disambiguate_add_names {
+                let most_names = group
+                    .iter()
+                    .flat_map(|id| self.references[id].names.values().map(Vec::len))
+                    .max()
+                    .unwrap_or(0);
+                for count in 2..=most_names {
+                    if best == group.len() {
+                        break;
+                    }
+                    self.try_disambiguation(&group, &mut best, |disambiguation| {
+                        disambiguation.names_use_first = Some(count)
+                    });
+                }
+            }
+
+            if citation.disambiguate_add_givenname && best < group.len() {
+                let (levels, primary_only) = match citation.givenname_rule {
+                    GivennameRule::AllNamesWithInitials => (&[GivenName::Initials][..], false),
+                    GivennameRule::PrimaryNameWithInitials => (&[GivenName::Initials][..], true),
+                    GivennameRule::PrimaryName => {
+                        (&[GivenName::Initials, GivenName::Full][..], true)
+                    }
+                    _ => (&[GivenName::Initials, GivenName::Full][..], false),
+                };
+                for &level in levels {
+                    if best < group.len() {
+                        self.try_disambiguation(&group, &mut best, |disambiguation| {
+                            disambiguation.given_name = level;
+                            disambiguation.given_name_primary_only = primary_only;
+                        });
+                    }
+                }
+            }
+
+            if best < group.len() {
+                self.try_disambiguation(&group, &mut best, |disambiguation| {
+                    disambiguation.disambiguate = true
+                });
+            }
+
+            if best < group.len() && citation.disambiguate_add_year_suffix {
+                for ambiguous in self.ambiguous_groups(&group) {
+                    let mut suffix_index = 0;
+                    for id in bibliography_order
+                        .iter()
+                        .filter(|id| ambiguous.contains(id))
+                    {
+                        if let Some(disambiguation) = self.disambiguation.get_mut(id) {
+                            disambiguation.year_suffix = Some(year_suffix(suffix_index));
+                            suffix_index += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Groups of ids whose cites have the same text, in order of their first cite.
+    fn ambiguous_groups(&self, ids: &[String]) -> Vec<Vec<String>> {
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+        for id in ids {
+            let text = self.disambiguation_text(id);
+            match groups.iter_mut().find(|(existing, _)| *existing == text) {
+                Some((_, group)) => group.push(id.clone()),
+                None => groups.push((text, vec![id.clone()])),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(_, group)| group)
+            .filter(|group| group.len() > 1)
+            .collect()
+    }
+}
+
+/// Orders cites so that cites with the same names follow each other, marking all but the first
+/// cite of such a group as collapsed.
+fn group_by_author(cites: &[Vec<Inline>]) -> Vec<(usize, bool)> {
+    let authors: Vec<Option<String>> = cites
+        .iter()
+        .map(|cite| first_names(cite).map(output::plain_text))
+        .collect();
+    let mut used = vec![false; cites.len()];
+    let mut order = Vec::new();
+    for index in 0..cites.len() {
+        if used[index] {
+            continue;
+        }
+        used[index] = true;
+        order.push((index, false));
+        let Some(author) = &authors[index] else {
+            continue;
+        };
+        for other in index + 1..cites.len() {
+            if !used[other] && authors[other].as_ref() == Some(author) {
+                used[other] = true;
+                order.push((other, true));
+            }
+        }
+    }
+    order
+}
+
+fn wrap_layout(nodes: Vec<Inline>, attrs: &Attrs, suffix: &str) -> Vec<Inline> {
+    if output::is_empty(&nodes) {
+        return nodes;
+    }
+    let mut nodes = if attrs.formatting.is_empty() {
+        nodes
+    } else {
+        vec![Inline::Fmt(attrs.formatting.clone(), nodes)]
+    };
+    if !attrs.prefix.is_empty() {
+        nodes.insert(0, Inline::Text(attrs.prefix.clone()));
+    }
+    if !suffix.is_empty() {
+        nodes.push(Inline::Text(suffix.to_string()));
+    }
+    nodes
+}
+
+fn first_names(nodes: &[Inline]) -> Option<&[Inline]> {
+    nodes.iter().find_map(|node| match node {
+        Inline::Names(children) => Some(children.as_slice()),
+        Inline::Fmt(_, children) | Inline::Quoted(children) | Inline::Display(_, children) => {
+            first_names(children)
+        }
+        Inline::Text(_) => None,
+    })
+}
+
+fn replace_first_names(nodes: &mut [Inline], substitute: &str) -> bool {
+    for node in nodes {
+        match node {
+            Inline::Names(children) => {
+                *children = vec![Inline::Text(substitute.to_string())];
+                return true;
+            }
+            Inline::Fmt(_, children) | Inline::Quoted(children) | Inline::Display(_, children) => {
+                if replace_first_names(children, substitute) {
+                    return true;
+                }
+            }
+            Inline::Text(_) => {}
+        }
+    }
+    false
+}
+
+/// Year-suffix for the given index: `a` to `z`, followed by `aa`, `ab`, …
+fn year_suffix(index: usize) -> String {
+    let letter = |offset: usize| char::from(b'a' + (offset % 26) as u8);
+    if index < 26 {
+        letter(index).to_string()
+    } else {
+        format!("{}{}", letter(index / 26 - 1), letter(index))
+    }
+}
+
+/// Makes sort keys compare case-insensitively and ignoring accents and leading articles.
+fn normalize_sort_key(value: &str) -> String {
+    let folded: String = value
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(fold_diacritic)
+        .filter(|c| {
+            !matches!(
+                c,
+                '“' | '”' | '‘' | '’' | '"' | '\'' | '[' | ']' | '(' | ')'
+            )
+        })
+        .collect();
+    let trimmed = folded.trim();
+    for article in ["a ", "an ", "the "] {
+        if let Some(rest) = trimmed.strip_prefix(article) {
+            return rest.to_string();
+        }
+    }
+    trimmed.to_string()
+}
+
+fn fold_diacritic(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'č' => 'c',
+        'ď' | 'đ' => 'd',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => 'e',
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' => 'i',
+        'ł' | 'ľ' => 'l',
+        'ñ' | 'ń' | 'ň' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => 'o',
+        'ř' => 'r',
+        'ś' | 'š' | 'ş' => 's',
+        'ť' | 'ţ' => 't',
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        c => c,
+    }
+}
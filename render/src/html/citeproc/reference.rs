@@ -0,0 +1,248 @@
+//! Bibliographic items in the form the processor evaluates them.
+//!
+//! A [`Reference`] is built from the CSL-JSON representation of a [`CslItem`](crate::csl_json::csl_types::CslItem),
+//! splitting its fields into ordinary, name and date variables.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Name {
+    pub family: String,
+    pub given: String,
+    pub literal: String,
+    pub non_dropping_particle: String,
+    pub dropping_particle: String,
+    pub suffix: String,
+    pub comma_suffix: bool,
+}
+
+impl Name {
+    fn from_value(value: &Value) -> Option<Name> {
+        let object = value.as_object()?;
+        let get = |key: &str| {
+            object
+                .get(key)
+                .map(value_to_string)
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        };
+        let mut name = Name {
+            family: get("family"),
+            given: get("given"),
+            literal: get("literal"),
+            non_dropping_particle: get("non-dropping-particle"),
+            dropping_particle: get("dropping-particle"),
+            suffix: get("suffix"),
+            comma_suffix: object
+                .get("comma-suffix")
+                .is_some_and(|value| value.as_bool() == Some(true) || value == "true"),
+        };
+        // Names without given name are usually institutions, whose family name is kept as is.
+        if name.literal.is_empty() && !name.given.is_empty() {
+            name.parse_particles();
+        }
+        Some(name)
+    }
+
+    /// Moves lowercase particles written as part of the given or family name into their own fields.
+    fn parse_particles(&mut self) {
+        if self.non_dropping_particle.is_empty() {
+            let words: Vec<&str> = self.family.split(' ').collect();
+            let count = words
+                .iter()
+                .take(words.len().saturating_sub(1))
+                .take_while(|word| is_particle(word))
+                .count();
+            if count > 0 {
+                self.non_dropping_particle = words[..count].join(" ");
+                self.family = words[count..].join(" ");
+            }
+        }
+        if self.dropping_particle.is_empty() {
+            let words: Vec<&str> = self.given.split(' ').collect();
+            let count = words
+                .iter()
+                .skip(1)
+                .rev()
+                .take_while(|word| is_particle(word))
+                .count();
+            if count > 0 {
+                let split = words.len() - count;
+                self.dropping_particle = words[split..].join(" ");
+                self.given = words[..split].join(" ");
+            }
+        }
+    }
+
+    pub(crate) fn is_literal(&self) -> bool {
+        !self.literal.is_empty()
+    }
+}
+
+fn is_particle(word: &str) -> bool {
+    word.chars().next().is_some_and(char::is_lowercase)
+}
+
+/// A single date: year, month and day, where a month from 13 to 16 denotes a season.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct SimpleDate {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct DateValue {
+    pub start: Option<SimpleDate>,
+    pub end: Option<SimpleDate>,
+    pub season: Option<String>,
+    pub circa: bool,
+    pub literal: Option<String>,
+}
+
+impl DateValue {
+    fn from_value(value: &Value) -> Option<DateValue> {
+        let object = value.as_object()?;
+        let mut date = DateValue {
+            circa: object
+                .get("circa")
+                .is_some_and(|value| !matches!(value, Value::Null | Value::Bool(false))),
+            season: object.get("season").map(value_to_string),
+            literal: object
+                .get("literal")
+                .map(value_to_string)
+                .filter(|literal| !literal.is_empty()),
+            ..Default::default()
+        };
+        if let Some(parts) = object.get("date-parts").and_then(Value::as_array) {
+            let mut dates = parts.iter().filter_map(parse_date_parts);
+            date.start = dates.next();
+            date.end = dates.next().filter(|end| Some(*end) != date.start);
+        } else if let Some(raw) = object.get("raw").map(value_to_string) {
+            let mut dates = raw.split('/').filter_map(parse_raw_date);
+            date.start = dates.next();
+            date.end = dates.next();
+        }
+        if date.start.is_none() && date.literal.is_none() {
+            return None;
+        }
+        Some(date)
+    }
+}
+
+fn parse_number(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(number) => number.as_f64().map(|number| number as i64),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn parse_date_parts(value: &Value) -> Option<SimpleDate> {
+    let parts = value.as_array()?;
+    let year = parse_number(parts.first()?)?;
+    let month = parts.get(1).and_then(parse_number).unwrap_or(0);
+    let day = parts.get(2).and_then(parse_number).unwrap_or(0);
+    Some(SimpleDate {
+        year,
+        month: month.clamp(0, 16) as u32,
+        day: if month > 0 {
+            day.clamp(0, 31) as u32
+        } else {
+            0
+        },
+    })
+}
+
+fn parse_raw_date(raw: &str) -> Option<SimpleDate> {
+    let mut parts = raw.trim().splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next().and_then(|part| part.parse().ok()).unwrap_or(0);
+    let day = parts.next().and_then(|part| part.parse().ok()).unwrap_or(0);
+    Some(SimpleDate { year, month, day })
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        // Numbers read into floating point fields of `CslItem` are written without fraction.
+        Value::Number(number) => match number.as_f64() {
+            Some(float) if number.is_f64() && float.fract() == 0.0 => (float as i64).to_string(),
+            _ => number.to_string(),
+        },
+        Value::Bool(flag) => flag.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Reference {
+    pub id: String,
+    pub kind: String,
+    pub strings: HashMap<String, String>,
+    pub names: HashMap<String, Vec<Name>>,
+    pub dates: HashMap<String, DateValue>,
+}
+
+impl Reference {
+    /// Builds the reference from the CSL-JSON object of an item.
+    pub(crate) fn from_value(value: &Value) -> Option<Reference> {
+        let object = value.as_object()?;
+        let mut reference = Reference {
+            id: object.get("id").map(value_to_string)?,
+            kind: object
+                .get("type")
+                .map(value_to_string)
+                .unwrap_or_else(|| "article".to_string()),
+            ..Default::default()
+        };
+        for (key, value) in object {
+            let key = match key.as_str() {
+                "id" | "type" => continue,
+                "journalAbbreviation" => "container-title-short",
+                "shortTitle" => "title-short",
+                key => key,
+            };
+            match value {
+                Value::Array(entries) => {
+                    let names: Vec<Name> = entries.iter().filter_map(Name::from_value).collect();
+                    if !names.is_empty() {
+                        reference.names.insert(key.to_string(), names);
+                    }
+                }
+                Value::Object(_) => {
+                    if let Some(date) = DateValue::from_value(value) {
+                        reference.dates.insert(key.to_string(), date);
+                    }
+                }
+                Value::Null => {}
+                _ => {
+                    let text = value_to_string(value);
+                    if !text.is_empty() {
+                        reference.strings.insert(key.to_string(), text);
+                    }
+                }
+            }
+        }
+        Some(reference)
+    }
+
+    /// Value of an ordinary variable, where `short` selects the short form if it exists.
+    pub(crate) fn string(&self, name: &str, short: bool) -> Option<&str> {
+        if short {
+            if let Some(value) = self.strings.get(&format!("{}-short", name)) {
+                return Some(value);
+            }
+        }
+        self.strings.get(name).map(String::as_str)
+    }
+
+    pub(crate) fn has_variable(&self, name: &str) -> bool {
+        self.strings.contains_key(name)
+            || self.names.contains_key(name)
+            || self.dates.contains_key(name)
+    }
+}
@@ -0,0 +1,798 @@
+//! Compiled form of a CSL style.
+//!
+//! The XML tree of the style is turned into rendering elements once, so that the processor does
+//! not have to look at attributes while rendering citations.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::xml::{self, XmlNode};
+
+#[derive(Debug)]
+pub(crate) struct StyleError(String);
+
+impl fmt::Display for StyleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum StyleClass {
+    InText,
+    Note,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DemoteParticle {
+    Never,
+    SortOnly,
+    DisplayAndSort,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TextCase {
+    Lowercase,
+    Uppercase,
+    CapitalizeFirst,
+    CapitalizeAll,
+    Sentence,
+    Title,
+}
+
+impl TextCase {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "lowercase" => Some(TextCase::Lowercase),
+            "uppercase" => Some(TextCase::Uppercase),
+            "capitalize-first" => Some(TextCase::CapitalizeFirst),
+            "capitalize-all" => Some(TextCase::CapitalizeAll),
+            "sentence" => Some(TextCase::Sentence),
+            "title" => Some(TextCase::Title),
+            _ => None,
+        }
+    }
+}
+
+/// Font and position attributes of a rendering element.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Formatting {
+    pub font_style: Option<String>,
+    pub font_variant: Option<String>,
+    pub font_weight: Option<String>,
+    pub text_decoration: Option<String>,
+    pub vertical_align: Option<String>,
+}
+
+impl Formatting {
+    fn parse(node: &XmlNode) -> Self {
+        let get = |name| node.get(name).map(str::to_string);
+        Formatting {
+            font_style: get("font-style"),
+            font_variant: get("font-variant"),
+            font_weight: get("font-weight"),
+            text_decoration: get("text-decoration"),
+            vertical_align: get("vertical-align"),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        *self == Formatting::default()
+    }
+}
+
+/// Attributes shared by all rendering elements.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Attrs {
+    pub formatting: Formatting,
+    pub prefix: String,
+    pub suffix: String,
+    pub display: Option<String>,
+    pub quotes: bool,
+    pub strip_periods: bool,
+    pub text_case: Option<TextCase>,
+}
+
+impl Attrs {
+    fn parse(node: &XmlNode) -> Self {
+        Attrs {
+            formatting: Formatting::parse(node),
+            prefix: node.attr("prefix").to_string(),
+            suffix: node.attr("suffix").to_string(),
+            display: node.get("display").map(str::to_string),
+            quotes: node.flag("quotes"),
+            strip_periods: node.flag("strip-periods"),
+            text_case: TextCase::parse(node.attr("text-case")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum TermForm {
+    Long,
+    Short,
+    Verb,
+    VerbShort,
+    Symbol,
+}
+
+impl TermForm {
+    pub(crate) fn parse(value: &str) -> Self {
+        match value {
+            "short" => TermForm::Short,
+            "verb" => TermForm::Verb,
+            "verb-short" => TermForm::VerbShort,
+            "symbol" => TermForm::Symbol,
+            _ => TermForm::Long,
+        }
+    }
+
+    /// Form that is used if a term does not exist in this form.
+    pub(crate) fn fallback(self) -> Option<TermForm> {
+        match self {
+            TermForm::Long => None,
+            TermForm::VerbShort => Some(TermForm::Verb),
+            TermForm::Symbol => Some(TermForm::Short),
+            TermForm::Short | TermForm::Verb => Some(TermForm::Long),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum TextSource {
+    Variable {
+        name: String,
+        short: bool,
+    },
+    Macro(String),
+    Term {
+        name: String,
+        form: TermForm,
+        plural: bool,
+    },
+    Value(String),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Text {
+    pub source: TextSource,
+    pub attrs: Attrs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum NumberForm {
+    Numeric,
+    Ordinal,
+    LongOrdinal,
+    Roman,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Number {
+    pub variable: String,
+    pub form: NumberForm,
+    pub attrs: Attrs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Plural {
+    Contextual,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Label {
+    pub variable: String,
+    pub form: TermForm,
+    pub plural: Plural,
+    pub attrs: Attrs,
+}
+
+/// Name options that are inherited from the style, citation and bibliography elements.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NameOptions {
+    pub and: Option<String>,
+    pub delimiter_precedes_et_al: Option<String>,
+    pub delimiter_precedes_last: Option<String>,
+    pub et_al_min: Option<usize>,
+    pub et_al_use_first: Option<usize>,
+    pub et_al_subsequent_min: Option<usize>,
+    pub et_al_subsequent_use_first: Option<usize>,
+    pub et_al_use_last: Option<bool>,
+    pub initialize: Option<bool>,
+    pub initialize_with: Option<String>,
+    pub name_as_sort_order: Option<String>,
+    pub sort_separator: Option<String>,
+    pub form: Option<String>,
+    pub name_delimiter: Option<String>,
+    pub names_delimiter: Option<String>,
+}
+
+impl NameOptions {
+    /// Options set on `cs:style`, `cs:citation` or `cs:bibliography`.
+    fn parse_inherited(node: &XmlNode) -> Self {
+        let mut options = Self::parse_common(node);
+        options.form = node.get("name-form").map(str::to_string);
+        options.name_delimiter = node.get("name-delimiter").map(str::to_string);
+        options.names_delimiter = node.get("names-delimiter").map(str::to_string);
+        options
+    }
+
+    /// Options set on `cs:name`.
+    fn parse_name(node: &XmlNode) -> Self {
+        let mut options = Self::parse_common(node);
+        options.form = node.get("form").map(str::to_string);
+        options.name_delimiter = node.get("delimiter").map(str::to_string);
+        options
+    }
+
+    fn parse_common(node: &XmlNode) -> Self {
+        let get = |name| node.get(name).map(str::to_string);
+        let number = |name| node.get(name).and_then(|value: &str| value.parse().ok());
+        NameOptions {
+            and: get("and"),
+            delimiter_precedes_et_al: get("delimiter-precedes-et-al"),
+            delimiter_precedes_last: get("delimiter-precedes-last"),
+            et_al_min: number("et-al-min"),
+            et_al_use_first: number("et-al-use-first"),
+            et_al_subsequent_min: number("et-al-subsequent-min"),
+            et_al_subsequent_use_first: number("et-al-subsequent-use-first"),
+            et_al_use_last: node.get("et-al-use-last").map(|value| value == "true"),
+            initialize: node.get("initialize").map(|value| value == "true"),
+            initialize_with: get("initialize-with"),
+            name_as_sort_order: get("name-as-sort-order"),
+            sort_separator: get("sort-separator"),
+            ..Default::default()
+        }
+    }
+
+    /// Options of `self`, overridden by every option that is set in `other`.
+    pub(crate) fn merge(&self, other: &NameOptions) -> NameOptions {
+        macro_rules! pick {
+            ($($field:ident),*) => {
+                NameOptions {
+                    $($field: other.$field.clone().or_else(|| self.$field.clone()),)*
+                }
+            };
+        }
+        pick!(
+            and,
+            delimiter_precedes_et_al,
+            delimiter_precedes_last,
+            et_al_min,
+            et_al_use_first,
+            et_al_subsequent_min,
+            et_al_subsequent_use_first,
+            et_al_use_last,
+            initialize,
+            initialize_with,
+            name_as_sort_order,
+            sort_separator,
+            form,
+            name_delimiter,
+            names_delimiter
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Name {
+    pub options: NameOptions,
+    pub attrs: Attrs,
+    pub family: Attrs,
+    pub given: Attrs,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct EtAl {
+    pub term: String,
+    pub attrs: Attrs,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Names {
+    pub variables: Vec<String>,
+    /// `None` if the element has no `cs:name` child, which matters for substitutions.
+    pub name: Option<Name>,
+    pub et_al: Option<EtAl>,
+    pub label: Option<Label>,
+    pub label_before_name: bool,
+    pub substitute: Option<Vec<Element>>,
+    pub delimiter: Option<String>,
+    pub attrs: Attrs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum DatePartName {
+    Day,
+    Month,
+    Year,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct DatePart {
+    pub name: DatePartName,
+    pub form: Option<String>,
+    pub range_delimiter: Option<String>,
+    pub attrs: Attrs,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Date {
+    pub variable: String,
+    /// Localized date form (`text` or `numeric`).
+    pub form: Option<String>,
+    /// Date parts shown for localized dates.
+    pub date_parts: DatePartName,
+    pub parts: Vec<DatePart>,
+    pub delimiter: String,
+    pub attrs: Attrs,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Group {
+    pub children: Vec<Element>,
+    pub delimiter: String,
+    pub attrs: Attrs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Match {
+    All,
+    Any,
+    None,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Position {
+    First,
+    Subsequent,
+    Ibid,
+    IbidWithLocator,
+    NearNote,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Condition {
+    Type(String),
+    Variable(String),
+    IsNumeric(String),
+    IsUncertainDate(String),
+    Locator(String),
+    Position(Position),
+    Disambiguate(bool),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Branch {
+    /// `None` for `cs:else`.
+    pub conditions: Option<(Match, Vec<Condition>)>,
+    pub children: Vec<Element>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Element {
+    Text(Text),
+    Number(Number),
+    Label(Label),
+    Names(Box<Names>),
+    Date(Date),
+    Group(Group),
+    Choose(Vec<Branch>),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Layout {
+    pub children: Vec<Element>,
+    pub delimiter: String,
+    pub attrs: Attrs,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum SortSource {
+    Variable(String),
+    Macro(String),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SortKey {
+    pub source: SortSource,
+    pub ascending: bool,
+    pub names_min: Option<usize>,
+    pub names_use_first: Option<usize>,
+    pub names_use_last: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Collapse {
+    CitationNumber,
+    Year,
+    YearSuffix,
+    YearSuffixRanged,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum GivennameRule {
+    AllNames,
+    AllNamesWithInitials,
+    PrimaryName,
+    PrimaryNameWithInitials,
+    ByCite,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Citation {
+    pub layout: Layout,
+    pub sort: Vec<SortKey>,
+    pub name_options: NameOptions,
+    pub disambiguate_add_names: bool,
+    pub disambiguate_add_givenname: bool,
+    pub givenname_rule: GivennameRule,
+    pub disambiguate_add_year_suffix: bool,
+    pub collapse: Option<Collapse>,
+    pub cite_group_delimiter: Option<String>,
+    pub after_collapse_delimiter: Option<String>,
+    pub near_note_distance: usize,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Bibliography {
+    pub layout: Layout,
+    pub sort: Vec<SortKey>,
+    pub name_options: NameOptions,
+    pub hanging_indent: bool,
+    pub second_field_align: Option<String>,
+    pub line_spacing: u32,
+    pub entry_spacing: u32,
+    pub subsequent_author_substitute: Option<String>,
+    pub subsequent_author_substitute_rule: String,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Style {
+    pub class: StyleClass,
+    pub default_locale: Option<String>,
+    pub initialize_with_hyphen: bool,
+    pub demote_non_dropping_particle: DemoteParticle,
+    pub page_range_format: Option<String>,
+    pub name_options: NameOptions,
+    pub macros: HashMap<String, Vec<Element>>,
+    pub citation: Citation,
+    pub bibliography: Option<Bibliography>,
+    /// `cs:locale` sections that override terms of the locale file.
+    pub locales: Vec<XmlNode>,
+    /// `true` if the year-suffix is rendered explicitly instead of being attached to the year.
+    pub renders_year_suffix: bool,
+}
+
+impl Style {
+    pub(crate) fn parse(text: &str) -> Result<Style, StyleError> {
+        let root = xml::parse(text).map_err(|err| StyleError(err.to_string()))?;
+        if root.name != "style" {
+            return Err(StyleError(format!(
+                "Expected a 'style' root element, found '{}'.",
+                root.name
+            )));
+        }
+
+        let mut macros = HashMap::new();
+        for node in root.children_named("macro") {
+            macros.insert(node.attr("name").to_string(), parse_children(node)?);
+        }
+
+        let citation_node = root
+            .child("citation")
+            .ok_or_else(|| StyleError("The style has no 'citation' element.".to_string()))?;
+        let citation = parse_citation(citation_node)?;
+        let bibliography = root
+            .child("bibliography")
+            .map(parse_bibliography)
+            .transpose()?;
+
+        let demote_non_dropping_particle = match root.attr("demote-non-dropping-particle") {
+            "never" => DemoteParticle::Never,
+            "sort-only" => DemoteParticle::SortOnly,
+            _ => DemoteParticle::DisplayAndSort,
+        };
+
+        Ok(Style {
+            class: if root.attr("class") == "note" {
+                StyleClass::Note
+            } else {
+                StyleClass::InText
+            },
+            default_locale: root.get("default-locale").map(str::to_string),
+            initialize_with_hyphen: root.attr("initialize-with-hyphen") != "false",
+            demote_non_dropping_particle,
+            page_range_format: root.get("page-range-format").map(str::to_string),
+            name_options: NameOptions::parse_inherited(&root),
+            renders_year_suffix: text.contains("variable=\"year-suffix\""),
+            macros,
+            citation,
+            bibliography,
+            locales: root.children_named("locale").cloned().collect(),
+        })
+    }
+}
+
+fn parse_layout(parent: &XmlNode) -> Result<Layout, StyleError> {
+    let node = parent
+        .child("layout")
+        .ok_or_else(|| StyleError(format!("The '{}' element has no layout.", parent.name)))?;
+    Ok(Layout {
+        children: parse_children(node)?,
+        delimiter: node.attr("delimiter").to_string(),
+        attrs: Attrs::parse(node),
+    })
+}
+
+fn parse_sort(parent: &XmlNode) -> Vec<SortKey> {
+    let Some(sort) = parent.child("sort") else {
+        return Vec::new();
+    };
+    sort.children_named("key")
+        .map(|key| SortKey {
+            source: match key.get("macro") {
+                Some(name) => SortSource::Macro(name.to_string()),
+                None => SortSource::Variable(key.attr("variable").to_string()),
+            },
+            ascending: key.attr("sort") != "descending",
+            names_min: key.get("names-min").and_then(|value| value.parse().ok()),
+            names_use_first: key
+                .get("names-use-first")
+                .and_then(|value| value.parse().ok()),
+            names_use_last: key.get("names-use-last").map(|value| value == "true"),
+        })
+        .collect()
+}
+
+fn parse_citation(node: &XmlNode) -> Result<Citation, StyleError> {
+    let givenname_rule = match node.attr("givenname-disambiguation-rule") {
+        "all-names" => GivennameRule::AllNames,
+        "all-names-with-initials" => GivennameRule::AllNamesWithInitials,
+        "primary-name" => GivennameRule::PrimaryName,
+        "primary-name-with-initials" => GivennameRule::PrimaryNameWithInitials,
+        _ => GivennameRule::ByCite,
+    };
+    let collapse = match node.attr("collapse") {
+        "citation-number" => Some(Collapse::CitationNumber),
+        "year" => Some(Collapse::Year),
+        "year-suffix" => Some(Collapse::YearSuffix),
+        "year-suffix-ranged" => Some(Collapse::YearSuffixRanged),
+        _ => None,
+    };
+    Ok(Citation {
+        layout: parse_layout(node)?,
+        sort: parse_sort(node),
+        name_options: NameOptions::parse_inherited(node),
+        disambiguate_add_names: node.flag("disambiguate-add-names"),
+        disambiguate_add_givenname: node.flag("disambiguate-add-givenname"),
+        givenname_rule,
+        disambiguate_add_year_suffix: node.flag("disambiguate-add-year-suffix"),
+        collapse,
+        cite_group_delimiter: node.get("cite-group-delimiter").map(str::to_string),
+        after_collapse_delimiter: node.get("after-collapse-delimiter").map(str::to_string),
+        near_note_distance: node
+            .get("near-note-distance")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(5),
+    })
+}
+
+fn parse_bibliography(node: &XmlNode) -> Result<Bibliography, StyleError> {
+    Ok(Bibliography {
+        layout: parse_layout(node)?,
+        sort: parse_sort(node),
+        name_options: NameOptions::parse_inherited(node),
+        hanging_indent: node.flag("hanging-indent"),
+        second_field_align: node.get("second-field-align").map(str::to_string),
+        line_spacing: node
+            .get("line-spacing")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(1),
+        entry_spacing: node
+            .get("entry-spacing")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(1),
+        subsequent_author_substitute: node.get("subsequent-author-substitute").map(str::to_string),
+        subsequent_author_substitute_rule: node
+            .get("subsequent-author-substitute-rule")
+            .unwrap_or("complete-all")
+            .to_string(),
+    })
+}
+
+fn parse_children(node: &XmlNode) -> Result<Vec<Element>, StyleError> {
+    node.children
+        .iter()
+        .filter_map(|child| parse_element(child).transpose())
+        .collect()
+}
+
+fn parse_element(node: &XmlNode) -> Result<Option<Element>, StyleError> {
+    let attrs = Attrs::parse(node);
+    let element = match node.name.as_str() {
+        "text" => {
+            let source = if let Some(name) = node.get("variable") {
+                TextSource::Variable {
+                    name: name.to_string(),
+                    short: node.attr("form") == "short",
+                }
+            } else if let Some(name) = node.get("macro") {
+                TextSource::Macro(name.to_string())
+            } else if let Some(name) = node.get("term") {
+                TextSource::Term {
+                    name: name.to_string(),
+                    form: TermForm::parse(node.attr("form")),
+                    plural: node.flag("plural"),
+                }
+            } else {
+                TextSource::Value(node.attr("value").to_string())
+            };
+            Element::Text(Text { source, attrs })
+        }
+        "number" => Element::Number(Number {
+            variable: node.attr("variable").to_string(),
+            form: match node.attr("form") {
+                "ordinal" => NumberForm::Ordinal,
+                "long-ordinal" => NumberForm::LongOrdinal,
+                "roman" => NumberForm::Roman,
+                _ => NumberForm::Numeric,
+            },
+            attrs,
+        }),
+        "label" => Element::Label(parse_label(node)),
+        "names" => Element::Names(Box::new(parse_names(node)?)),
+        "date" => Element::Date(parse_date(node)),
+        "group" => Element::Group(Group {
+            children: parse_children(node)?,
+            delimiter: node.attr("delimiter").to_string(),
+            attrs,
+        }),
+        "choose" => Element::Choose(parse_choose(node)?),
+        _ => return Ok(None),
+    };
+    Ok(Some(element))
+}
+
+fn parse_label(node: &XmlNode) -> Label {
+    Label {
+        variable: node.attr("variable").to_string(),
+        form: TermForm::parse(node.attr("form")),
+        plural: match node.attr("plural") {
+            "always" => Plural::Always,
+            "never" => Plural::Never,
+            _ => Plural::Contextual,
+        },
+        attrs: Attrs::parse(node),
+    }
+}
+
+fn parse_names(node: &XmlNode) -> Result<Names, StyleError> {
+    let mut names = Names {
+        variables: node
+            .attr("variable")
+            .split_whitespace()
+            .map(str::to_string)
+            .collect(),
+        name: None,
+        et_al: None,
+        label: None,
+        label_before_name: false,
+        substitute: None,
+        delimiter: node.get("delimiter").map(str::to_string),
+        attrs: Attrs::parse(node),
+    };
+    for child in &node.children {
+        match child.name.as_str() {
+            "name" => {
+                let mut name = Name {
+                    options: NameOptions::parse_name(child),
+                    attrs: Attrs::parse(child),
+                    ..Default::default()
+                };
+                for part in child.children_named("name-part") {
+                    match part.attr("name") {
+                        "family" => name.family = Attrs::parse(part),
+                        "given" => name.given = Attrs::parse(part),
+                        _ => {}
+                    }
+                }
+                names.name = Some(name);
+            }
+            "et-al" => {
+                names.et_al = Some(EtAl {
+                    term: child.get("term").unwrap_or("et-al").to_string(),
+                    attrs: Attrs::parse(child),
+                })
+            }
+            "label" => {
+                names.label_before_name = names.name.is_none();
+                names.label = Some(parse_label(child));
+            }
+            "substitute" => names.substitute = Some(parse_children(child)?),
+            _ => {}
+        }
+    }
+    Ok(names)
+}
+
+pub(crate) fn parse_date(node: &XmlNode) -> Date {
+    let parts = node
+        .children_named("date-part")
+        .filter_map(|part| {
+            let name = match part.attr("name") {
+                "day" => DatePartName::Day,
+                "month" => DatePartName::Month,
+                "year" => DatePartName::Year,
+                _ => return None,
+            };
+            Some(DatePart {
+                name,
+                form: part.get("form").map(str::to_string),
+                range_delimiter: part.get("range-delimiter").map(str::to_string),
+                attrs: Attrs::parse(part),
+            })
+        })
+        .collect();
+    Date {
+        variable: node.attr("variable").to_string(),
+        form: node.get("form").map(str::to_string),
+        date_parts: match node.attr("date-parts") {
+            "year" => DatePartName::Year,
+            "year-month" => DatePartName::Month,
+            _ => DatePartName::Day,
+        },
+        parts,
+        delimiter: node.attr("delimiter").to_string(),
+        attrs: Attrs::parse(node),
+    }
+}
+
+fn parse_choose(node: &XmlNode) -> Result<Vec<Branch>, StyleError> {
+    let mut branches = Vec::new();
+    for child in &node.children {
+        let conditions = match child.name.as_str() {
+            "if" | "else-if" => Some(parse_conditions(child)),
+            "else" => None,
+            _ => continue,
+        };
+        branches.push(Branch {
+            conditions,
+            children: parse_children(child)?,
+        });
+    }
+    Ok(branches)
+}
+
+fn parse_conditions(node: &XmlNode) -> (Match, Vec<Condition>) {
+    let mode = match node.attr("match") {
+        "any" => Match::Any,
+        "none" => Match::None,
+        _ => Match::All,
+    };
+    let mut conditions = Vec::new();
+    for (name, value) in &node.attrs {
+        for value in value.split_whitespace() {
+            let condition = match name.as_str() {
+                "type" => Condition::Type(value.to_string()),
+                "variable" => Condition::Variable(value.to_string()),
+                "is-numeric" => Condition::IsNumeric(value.to_string()),
+                "is-uncertain-date" => Condition::IsUncertainDate(value.to_string()),
+                "locator" => Condition::Locator(value.to_string()),
+                "position" => Condition::Position(match value {
+                    "subsequent" => Position::Subsequent,
+                    "ibid" => Position::Ibid,
+                    "ibid-with-locator" => Position::IbidWithLocator,
+                    "near-note" => Position::NearNote,
+                    _ => Position::First,
+                }),
+                "disambiguate" => Condition::Disambiguate(value == "true"),
+                _ => continue,
+            };
+            conditions.push(condition);
+        }
+    }
+    (mode, conditions)
+}
@@ -0,0 +1,272 @@
+//! Validation of the cited ids against the loaded references.
+
+use std::collections::HashSet;
+
+use logid::{
+    log,
+    logging::event_entry::AddonKind,
+    lsp_types::{Diagnostic, DiagnosticSeverity, Position as LspPosition, Range},
+};
+use unimarkup_commons::{config::preamble::element_kind_info, lexer::position::Position};
+use unimarkup_inline::element::textbox::citation::CitationEntry;
+use unimarkup_parser::document::Document;
+
+use crate::csl_json::csl_types::CslData;
+use crate::html::citeproc::is_processable;
+use crate::log_id::{CiteError, GeneralWarning};
+
+/// Problem found while validating the citations of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CitationIssue {
+    /// A cited id that is not defined in the references.
+    UnknownId {
+        id: String,
+        start: Position,
+        end: Position,
+        /// Defined id that is close to the unknown one.
+        suggestion: Option<String>,
+    },
+    /// A cited reference without issued date, which the citation processor cannot render.
+    UndatedReference {
+        id: String,
+        start: Position,
+        end: Position,
+    },
+    /// A reference that is never cited.
+    UnusedReference { id: String },
+}
+
+/// Returns all issues of the given citations, in order of appearance.
+/// Unused references are only reported if `unused` is `true`, sorted by their id.
+pub fn check_citations(
+    citations: &[Vec<CitationEntry>],
+    csl_data: &CslData,
+    unused: bool,
+) -> Vec<CitationIssue> {
+    let ids: Vec<String> = csl_data
+        .items
+        .iter()
+        .map(|item| item.id.to_string())
+        .collect();
+    let known: HashSet<&str> = ids.iter().map(String::as_str).collect();
+    let processable: HashSet<String> = csl_data
+        .items
+        .iter()
+        .filter(|item| is_processable(item))
+        .map(|item| item.id.to_string())
+        .collect();
+    let mut issues = Vec::new();
+
+    for entry in citations.iter().flatten() {
+        if !known.contains(entry.id()) {
+            issues.push(CitationIssue::UnknownId {
+                id: entry.id().to_string(),
+                start: entry.start(),
+                end: entry.end(),
+                suggestion: closest_id(entry.id(), &ids).map(str::to_string),
+            });
+        } else if !processable.contains(entry.id()) {
+            issues.push(CitationIssue::UndatedReference {
+                id: entry.id().to_string(),
+                start: entry.start(),
+                end: entry.end(),
+            });
+        }
+    }
+
+    if unused {
+        let cited: HashSet<&str> = citations.iter().flatten().map(|entry| entry.id()).collect();
+        let mut unused_ids: Vec<&String> = ids
+            .iter()
+            .filter(|id| !cited.contains(id.as_str()))
+            .collect();
+        unused_ids.sort();
+        unused_ids.dedup();
+        issues.extend(
+            unused_ids
+                .into_iter()
+                .map(|id| CitationIssue::UnusedReference { id: id.clone() }),
+        );
+    }
+
+    issues
+}
+
+/// Logs all issues of the citations of the given document.
+pub fn log_citation_issues(doc: &Document, csl_data: &CslData) {
+    let unused = doc.config.preamble.cite.warn_unused_references;
+    for issue in check_citations(&doc.citations, csl_data, unused) {
+        match issue {
+            CitationIssue::UnknownId {
+                id,
+                start,
+                end,
+                suggestion,
+            } => {
+                let mut msg = format!("Citation id '{}' is not defined in the references.", id);
+                if let Some(suggestion) = suggestion {
+                    msg.push_str(&format!(" Did you mean '{}'?", suggestion));
+                }
+
+                log_cite_error(CiteError::UnknownCitationId, msg, start, end);
+            }
+            CitationIssue::UndatedReference { id, start, end } => {
+                let msg = format!(
+                    "Reference '{}' has no issued date, so its citation cannot be rendered.",
+                    id
+                );
+
+                log_cite_error(CiteError::UndatedReference, msg, start, end);
+            }
+            CitationIssue::UnusedReference { id } => {
+                log!(
+                    GeneralWarning::UnusedReference,
+                    format!("Reference '{}' is never cited.", id)
+                );
+            }
+        }
+    }
+}
+
+/// Logs the given error of a cite between `start` and `end`, with a diagnostic for editors.
+fn log_cite_error(error: CiteError, msg: String, start: Position, end: Position) {
+    log!(
+        error,
+        format!("{} (line {}, column {})", msg, start.line, start.col_utf8),
+        add: AddonKind::Diagnostic(Diagnostic {
+            range: Range::new(lsp_position(start), lsp_position(end)),
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("unimarkup".to_string()),
+            message: msg,
+            ..Default::default()
+        }),
+        add: element_kind_info("Citation")
+    );
+}
+
+/// Converts the 1-based position into the 0-based position used by LSP diagnostics.
+pub(crate) fn lsp_position(position: Position) -> LspPosition {
+    LspPosition::new(
+        position.line.saturating_sub(1) as u32,
+        position.col_utf16.saturating_sub(1) as u32,
+    )
+}
+
+/// Returns the defined id with the smallest edit distance to the given id,
+/// if the distance is small enough for a typo.
+fn closest_id<'a>(id: &str, ids: &'a [String]) -> Option<&'a str> {
+    let max_distance = (id.chars().count() / 3).max(1);
+    let id = id.to_lowercase();
+    ids.iter()
+        .map(|candidate| (edit_distance(&id, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// Levenshtein distance between the two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unimarkup_commons::config::Config;
+
+    fn csl_data(ids: &[&str]) -> CslData {
+        let items: Vec<serde_json::Value> = ids
+            .iter()
+            .map(|id| {
+                serde_json::json!({ "id": id, "type": "book", "issued": { "date-parts": [[2020]] } })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({ "items": items })).unwrap()
+    }
+
+    #[test]
+    fn unknown_id_with_suggestion() {
+        let doc = unimarkup_parser::parse_unimarkup(
+            "Text [&&smith2020] and [&&smtih2020; doe2018]",
+            Config::default(),
+        );
+
+        let issues = check_citations(&doc.citations, &csl_data(&["smith2020", "lee2010"]), false);
+
+        assert_eq!(issues.len(), 2);
+        let CitationIssue::UnknownId {
+            id,
+            start,
+            end,
+            suggestion,
+        } = &issues[0]
+        else {
+            panic!("Expected an unknown id, got '{:?}'.", issues[0]);
+        };
+        assert_eq!(id, "smtih2020");
+        assert_eq!((start.line, start.col_utf8), (1, 27));
+        assert_eq!((end.line, end.col_utf8), (1, 36));
+        assert_eq!(suggestion.as_deref(), Some("smith2020"));
+        assert!(matches!(
+            &issues[1],
+            CitationIssue::UnknownId { id, suggestion: None, .. } if id == "doe2018"
+        ));
+    }
+
+    #[test]
+    fn unused_references() {
+        let doc = unimarkup_parser::parse_unimarkup("[&&lee2010]", Config::default());
+        let csl_data = csl_data(&["smith2020", "lee2010", "berg2015"]);
+
+        assert!(check_citations(&doc.citations, &csl_data, false).is_empty());
+        assert_eq!(
+            check_citations(&doc.citations, &csl_data, true),
+            vec![
+                CitationIssue::UnusedReference {
+                    id: "berg2015".to_string()
+                },
+                CitationIssue::UnusedReference {
+                    id: "smith2020".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn undated_reference() {
+        let doc = unimarkup_parser::parse_unimarkup("[&&lee2010; undated]", Config::default());
+        let mut csl_data = csl_data(&["lee2010", "undated"]);
+        csl_data.items[1].issued = None;
+
+        let issues = check_citations(&doc.citations, &csl_data, false);
+
+        assert!(matches!(
+            issues.as_slice(),
+            [CitationIssue::UndatedReference { id, .. }] if id == "undated"
+        ));
+    }
+
+    #[test]
+    fn distance_of_ids() {
+        assert_eq!(edit_distance("smith", "smith"), 0);
+        assert_eq!(edit_distance("smith", "smtih"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(
+            closest_id("Lee2010", &["lee2010".to_string()]),
+            Some("lee2010")
+        );
+        assert_eq!(closest_id("knuth", &["lee2010".to_string()]), None);
+    }
+}
@@ -0,0 +1,101 @@
+//! Element tree for CSL styles and locales.
+//!
+//! Only the parts of the XML document the processor needs are kept: element names, attributes and
+//! the text content of leaf elements. Newlines inside text and attributes are replaced by spaces.
+
+use std::fmt;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct XmlNode {
+    pub name: String,
+    /// Attributes in document order.
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<XmlNode>,
+    /// Text content of a leaf element.
+    pub text: String,
+}
+
+#[derive(Debug)]
+pub(crate) struct XmlParseError(String);
+
+impl fmt::Display for XmlParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+pub(crate) fn parse(text: &str) -> Result<XmlNode, XmlParseError> {
+    let doc = roxmltree::Document::parse(text).map_err(|err| XmlParseError(err.to_string()))?;
+    Ok(convert(doc.root_element()))
+}
+
+fn normalize_space(text: &str) -> String {
+    text.replace("\r\n", " ").replace(['\n', '\r'], " ")
+}
+
+fn convert(element: roxmltree::Node) -> XmlNode {
+    let attrs = element
+        .attributes()
+        .map(|attr| {
+            let key = if attr.namespace() == Some(XML_NAMESPACE) {
+                format!("xml:{}", attr.name())
+            } else {
+                attr.name().to_string()
+            };
+            (key, normalize_space(attr.value()))
+        })
+        .collect();
+
+    let children: Vec<XmlNode> = element
+        .children()
+        .filter(|child| child.is_element())
+        .map(convert)
+        .collect();
+    let text = if children.is_empty() {
+        normalize_space(
+            &element
+                .children()
+                .filter_map(|child| child.text())
+                .collect::<String>(),
+        )
+    } else {
+        String::new()
+    };
+
+    XmlNode {
+        name: element.tag_name().name().to_string(),
+        attrs,
+        children,
+        text,
+    }
+}
+
+impl XmlNode {
+    /// Value of the given attribute, if it is set.
+    pub(crate) fn get(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Value of the given attribute, or `""` if it is not set.
+    pub(crate) fn attr(&self, name: &str) -> &str {
+        self.get(name).unwrap_or_default()
+    }
+
+    /// `true` if the given attribute is set to `"true"`.
+    pub(crate) fn flag(&self, name: &str) -> bool {
+        self.get(name) == Some("true")
+    }
+
+    pub(crate) fn child(&self, name: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|node| node.name == name)
+    }
+
+    pub(crate) fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlNode> {
+        self.children.iter().filter(move |node| node.name == name)
+    }
+}
@@ -0,0 +1,88 @@
+//! Contains escaping of text and attribute values, and sanitising of URLs for the HTML output.
+
+/// URL schemes that may execute code when the URL is opened.
+const UNSAFE_SCHEMES: [&str; 3] = ["javascript", "vbscript", "data"];
+
+/// Escapes the given text, so it is shown as is in a HTML text node.
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Escapes the given value, so it may be used as quoted attribute value.
+pub fn escape_attribute(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Returns `true` if the given URL has no scheme that may execute code, like `javascript:`.
+///
+/// **Note:** Unsafe URLs are only rendered if `allow_unsafe` is set in the preamble.
+pub fn is_safe_url(url: &str) -> bool {
+    // Note: Browsers ignore whitespace and control characters in schemes, e.g. `java\tscript:`.
+    let normalized = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    match normalized.split_once(':') {
+        Some((scheme, _)) => !UNSAFE_SCHEMES.contains(&scheme),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn escape_script_in_text() {
+        assert_eq!(
+            escape_text("a < b && <script>"),
+            "a &lt; b &amp;&amp; &lt;script&gt;"
+        );
+    }
+
+    #[test]
+    fn escape_quotes_in_attribute() {
+        assert_eq!(
+            escape_attribute("x' onclick='alert(1)\""),
+            "x&#39; onclick=&#39;alert(1)&quot;"
+        );
+    }
+
+    #[test]
+    fn unsafe_urls() {
+        assert!(!is_safe_url("javascript:alert(1)"));
+        assert!(!is_safe_url(" JavaScript:alert(1)"));
+        assert!(!is_safe_url("java\tscript:alert(1)"));
+        assert!(!is_safe_url("data:text/html;base64,PHNjcmlwdD4="));
+        assert!(is_safe_url("https://unimarkup.org"));
+        assert!(is_safe_url("mailto:info@unimarkup.org"));
+        assert!(is_safe_url("#intro"));
+        assert!(is_safe_url("docs/intro.html"));
+    }
+}
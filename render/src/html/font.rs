@@ -0,0 +1,210 @@
+//! Contains the [`FontFace`] struct used to add configured fonts to the [`HtmlHead`](super::HtmlHead).
+
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use logid::log;
+
+use crate::log_id::GeneralWarning;
+
+/// Generic font family that is used as fallback after all configured fonts.
+const FALLBACK_FONT_FAMILY: &str = "sans-serif";
+
+/// Defines how a font file is referenced in the `@font-face` rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontSource {
+    /// The font file content is embedded as base64 data URL.
+    Embedded(Vec<u8>),
+    /// The font file is linked using its path.
+    Linked(PathBuf),
+}
+
+/// Represents one `@font-face` rule for a configured font file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontFace {
+    pub family: String,
+    pub weight: &'static str,
+    pub style: &'static str,
+    pub format: &'static str,
+    pub source: FontSource,
+}
+
+impl FontFace {
+    /// Creates a [`FontFace`] for the given font file.
+    ///
+    /// The font family, weight and style are derived from the file name.
+    /// e.g. `OpenSans-BoldItalic.ttf` results in family `OpenSans` with bold weight and italic style.
+    ///
+    /// Returns `None` if the font file could not be read for embedding.
+    pub fn new(path: &Path, embed: bool) -> Option<Self> {
+        let stem = path.file_stem()?.to_string_lossy();
+        let (family, variant) = match stem.split_once('-') {
+            Some((family, variant)) => (family.to_string(), variant.to_lowercase()),
+            None => (stem.to_string(), String::new()),
+        };
+
+        let source = if embed {
+            match std::fs::read(path) {
+                Ok(data) => FontSource::Embedded(data),
+                Err(err) => {
+                    log!(
+                        GeneralWarning::FileRead,
+                        format!("Could not read font file {:?}: {}", path, err)
+                    );
+                    return None;
+                }
+            }
+        } else {
+            FontSource::Linked(path.to_path_buf())
+        };
+
+        Some(FontFace {
+            family,
+            weight: font_weight(&variant),
+            style: if variant.contains("italic") || variant.contains("oblique") {
+                "italic"
+            } else {
+                "normal"
+            },
+            format: font_format(path),
+            source,
+        })
+    }
+
+    /// Returns the CSS `@font-face` rule for this font.
+    ///
+    /// If `absolute_links` is `true`, linked fonts are referenced with absolute `file://` URLs.
+    /// This is needed if the HTML file is loaded from another location (e.g. to render PDFs).
+    pub fn to_css(&self, absolute_links: bool) -> String {
+        let url = match &self.source {
+            FontSource::Embedded(data) => format!(
+                "data:{};base64,{}",
+                font_mime(self.format),
+                base64::engine::general_purpose::STANDARD.encode(data)
+            ),
+            FontSource::Linked(path) if absolute_links => {
+                let abs_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+                format!("file://{}", abs_path.to_string_lossy())
+            }
+            FontSource::Linked(path) => path.to_string_lossy().replace('\\', "/"),
+        };
+
+        format!(
+            "@font-face{{font-family:'{}';src:url('{}') format('{}');font-weight:{};font-style:{};}}",
+            self.family, url, self.format, self.weight, self.style
+        )
+    }
+}
+
+/// Returns the CSS rules for the given fonts, including the `font-family` for the document body.
+pub fn fonts_css(fonts: &[FontFace], absolute_links: bool) -> String {
+    let mut css = String::new();
+    let mut families: Vec<&str> = Vec::new();
+
+    for font in fonts {
+        css.push_str(&font.to_css(absolute_links));
+
+        if !families.contains(&font.family.as_str()) {
+            families.push(&font.family);
+        }
+    }
+
+    if !families.is_empty() {
+        let family_list = families
+            .iter()
+            .map(|family| format!("'{}'", family))
+            .collect::<Vec<_>>()
+            .join(",");
+        css.push_str(&format!(
+            "body{{font-family:{},{};}}",
+            family_list, FALLBACK_FONT_FAMILY
+        ));
+    }
+
+    css
+}
+
+fn font_weight(variant: &str) -> &'static str {
+    if variant.contains("extrabold") || variant.contains("black") {
+        "800"
+    } else if variant.contains("semibold") {
+        "600"
+    } else if variant.contains("bold") {
+        "700"
+    } else if variant.contains("medium") {
+        "500"
+    } else if variant.contains("light") || variant.contains("thin") {
+        "300"
+    } else {
+        "400"
+    }
+}
+
+fn font_format(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .as_deref()
+    {
+        Some("otf") => "opentype",
+        Some("woff") => "woff",
+        Some("woff2") => "woff2",
+        _ => "truetype",
+    }
+}
+
+fn font_mime(format: &str) -> &'static str {
+    match format {
+        "opentype" => "font/otf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        _ => "font/ttf",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn font_face_from_file_name() {
+        let font = FontFace::new(&PathBuf::from("fonts/OpenSans-BoldItalic.woff2"), false).unwrap();
+
+        assert_eq!(font.family, "OpenSans");
+        assert_eq!(font.weight, "700");
+        assert_eq!(font.style, "italic");
+        assert_eq!(
+            font.to_css(false),
+            "@font-face{font-family:'OpenSans';src:url('fonts/OpenSans-BoldItalic.woff2') format('woff2');font-weight:700;font-style:italic;}"
+        );
+    }
+
+    #[test]
+    fn body_uses_fonts() {
+        let fonts = vec![
+            FontFace::new(&PathBuf::from("Serif-Regular.ttf"), false).unwrap(),
+            FontFace::new(&PathBuf::from("Serif-Bold.ttf"), false).unwrap(),
+        ];
+
+        let css = fonts_css(&fonts, false);
+
+        assert!(css.ends_with("body{font-family:'Serif',sans-serif;}"));
+    }
+
+    #[test]
+    fn embedded_font() {
+        let font = FontFace {
+            family: "Embedded".to_string(),
+            weight: "400",
+            style: "normal",
+            format: "truetype",
+            source: FontSource::Embedded(b"font".to_vec()),
+        };
+
+        assert!(font
+            .to_css(false)
+            .contains("url('data:font/ttf;base64,Zm9udA==')"));
+    }
+}
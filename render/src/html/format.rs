@@ -0,0 +1,320 @@
+//! Contains pretty-printing of [`HtmlElements`] and minification of inlined styles and scripts.
+
+use std::fmt::Write;
+
+use super::{tag::HtmlTag, HtmlContent, HtmlElement, HtmlElements};
+
+/// Indentation per nesting depth in pretty-printed HTML.
+const INDENT: &str = "  ";
+
+/// Characters in CSS that need no whitespace around them.
+const CSS_SEPARATORS: [char; 5] = ['{', '}', ';', ',', '>'];
+
+/// Writes the given elements pretty-printed at the given nesting depth.
+///
+/// Block elements are written on their own lines, and consecutive inline elements share one line.
+/// Attributes are sorted by name to get a stable output.
+pub(crate) fn write_pretty(elements: &HtmlElements, depth: usize, out: &mut String) {
+    let mut line_open = false;
+
+    for element in elements.iter() {
+        if element.tag.is_block() {
+            if line_open {
+                out.push('\n');
+                line_open = false;
+            }
+            write_block(element, depth, out);
+        } else {
+            if !line_open {
+                out.push_str(&INDENT.repeat(depth));
+                line_open = true;
+            }
+            write_inline(element, out);
+        }
+    }
+
+    if line_open {
+        out.push('\n');
+    }
+}
+
+fn write_block(element: &HtmlElement, depth: usize, out: &mut String) {
+    out.push_str(&INDENT.repeat(depth));
+    write_open_tag(element, out);
+
+    match &element.content {
+        None => out.push_str("/>\n"),
+        // Note: Whitespace inside `<pre>` is part of the content, so it is never indented.
+        Some(HtmlContent::Elements(children))
+            if element.tag != HtmlTag::Pre && children.iter().any(|c| c.tag.is_block()) =>
+        {
+            out.push_str(">\n");
+            write_pretty(children, depth + 1, out);
+            let _ = writeln!(out, "{}</{}>", INDENT.repeat(depth), element.tag.as_str());
+        }
+        Some(content) => {
+            out.push('>');
+            write_content(content, out);
+            let _ = writeln!(out, "</{}>", element.tag.as_str());
+        }
+    }
+}
+
+fn write_inline(element: &HtmlElement, out: &mut String) {
+    if element.tag == HtmlTag::PlainContent {
+        if let Some(content) = &element.content {
+            write_content(content, out);
+        }
+        return;
+    }
+
+    write_open_tag(element, out);
+    match &element.content {
+        None => out.push_str("/>"),
+        Some(content) => {
+            out.push('>');
+            write_content(content, out);
+            let _ = write!(out, "</{}>", element.tag.as_str());
+        }
+    }
+}
+
+fn write_content(content: &HtmlContent, out: &mut String) {
+    match content {
+        HtmlContent::Raw(raw) => out.push_str(raw),
+        HtmlContent::Elements(children) => children.iter().for_each(|c| write_inline(c, out)),
+    }
+}
+
+fn write_open_tag(element: &HtmlElement, out: &mut String) {
+    let mut attributes: Vec<_> = element.attributes.iter().collect();
+    attributes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let _ = write!(out, "<{}", element.tag.as_str());
+    for attribute in attributes {
+        let _ = write!(out, " {}", attribute);
+    }
+}
+
+/// Removes comments and unneeded whitespace from the given CSS.
+/// Quoted strings are kept as is.
+///
+/// **Note:** Whitespace before `:` is only removed in declarations,
+/// because it separates a descendant from its pseudo-class in selectors, e.g. `a :hover`.
+pub(crate) fn minify_css(css: &str) -> String {
+    let mut minified = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    let mut pending_space = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                read_block_comment(&mut chars);
+                pending_space = true;
+            }
+            c if c.is_whitespace() => pending_space = true,
+            c => {
+                let after_separator = minified
+                    .chars()
+                    .last()
+                    .is_none_or(|last| CSS_SEPARATORS.contains(&last) || last == ':');
+                let before_separator =
+                    CSS_SEPARATORS.contains(&c) || (c == ':' && in_css_declaration(chars.clone()));
+                if pending_space && !after_separator && !before_separator {
+                    minified.push(' ');
+                }
+                pending_space = false;
+
+                if c == '}' && minified.ends_with(';') {
+                    minified.pop();
+                }
+                minified.push(c);
+
+                if c == '"' || c == '\'' {
+                    push_quoted(&mut chars, c, &mut minified);
+                }
+            }
+        }
+    }
+
+    minified
+}
+
+/// Returns `true` if the remaining CSS ends the current statement like a declaration,
+/// and not like a selector that is followed by a block.
+fn in_css_declaration(rest: impl Iterator<Item = char>) -> bool {
+    for c in rest {
+        match c {
+            '{' => return false,
+            ';' | '}' => return true,
+            _ => {}
+        }
+    }
+    true
+}
+
+/// Removes comments and unneeded whitespace from the given script.
+/// String, template and regular expression literals are kept as is.
+/// License comments starting with `/*!` or containing `@license` are kept.
+///
+/// **Note:** Line breaks are kept, because removing them may change the meaning of scripts
+/// that rely on automatic semicolon insertion.
+pub(crate) fn minify_js(js: &str) -> String {
+    let mut minified = String::with_capacity(js.len());
+    let mut chars = js.chars().peekable();
+    // Whitespace that is written before the next token, a line break taking precedence over spaces.
+    let mut pending: Option<char> = None;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                // Note: The line break is kept, because it may end the statement.
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let comment = read_block_comment(&mut chars);
+                // Note: License comments are kept as they must be retained in copies of bundled scripts.
+                if comment.starts_with('!') || comment.contains("@license") {
+                    push_pending(&mut minified, pending.take());
+                    minified.push_str(&format!("/*{}*/", comment));
+                }
+                pending =
+                    merge_whitespace(pending, if comment.contains('\n') { '\n' } else { ' ' });
+            }
+            c if c.is_whitespace() => pending = merge_whitespace(pending, c),
+            c => {
+                push_pending(&mut minified, pending.take());
+                let regex = c == '/' && js_regex_allowed(&minified);
+                minified.push(c);
+                match c {
+                    '"' | '\'' | '`' => push_quoted(&mut chars, c, &mut minified),
+                    '/' if regex => push_js_regex(&mut chars, &mut minified),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    minified
+}
+
+/// Adds the given whitespace character to the pending whitespace between two tokens.
+fn merge_whitespace(pending: Option<char>, c: char) -> Option<char> {
+    if c == '\n' || pending == Some('\n') {
+        Some('\n')
+    } else {
+        Some(' ')
+    }
+}
+
+/// Writes the pending whitespace, unless it would be leading whitespace.
+fn push_pending(minified: &mut String, pending: Option<char>) {
+    if let Some(whitespace) = pending.filter(|_| !minified.is_empty()) {
+        minified.push(whitespace);
+    }
+}
+
+/// Returns `true` if a `/` after the given script starts a regular expression instead of a division.
+fn js_regex_allowed(script: &str) -> bool {
+    const KEYWORDS: [&str; 10] = [
+        "return", "typeof", "case", "do", "else", "in", "of", "void", "delete", "throw",
+    ];
+
+    let script = script.trim_end();
+    match script.chars().last() {
+        None => true,
+        Some(c) if c.is_alphanumeric() || c == '_' || c == '$' => {
+            let before_word =
+                script.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_' || c == '$');
+            KEYWORDS.contains(&&script[before_word.len()..])
+        }
+        Some(c) => !matches!(c, ')' | ']' | '}' | '"' | '\'' | '`'),
+    }
+}
+
+/// Consumes the rest of a `/* */` comment whose start was already consumed.
+/// Returns the content of the comment without its delimiters.
+fn read_block_comment(chars: &mut impl Iterator<Item = char>) -> String {
+    let mut comment = String::new();
+    for c in chars {
+        if c == '/' && comment.ends_with('*') {
+            comment.pop();
+            break;
+        }
+        comment.push(c);
+    }
+    comment
+}
+
+/// Pushes the rest of a literal whose opening `quote` was already pushed, including the closing quote.
+fn push_quoted(chars: &mut impl Iterator<Item = char>, quote: char, out: &mut String) {
+    while let Some(c) = chars.next() {
+        out.push(c);
+        if c == '\\' {
+            out.extend(chars.next());
+        } else if c == quote {
+            break;
+        }
+    }
+}
+
+/// Pushes the rest of a regular expression literal whose opening `/` was already pushed.
+fn push_js_regex(chars: &mut impl Iterator<Item = char>, out: &mut String) {
+    let mut in_class = false;
+    while let Some(c) = chars.next() {
+        out.push(c);
+        match c {
+            '\\' => out.extend(chars.next()),
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => break,
+            '\n' => break,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn minify_css_keeps_strings() {
+        let css = "/* theme */\n.toc a::after {\n  content: ' page  ' target-counter(attr(href), page);\n  color : #333;\n}\n\na :hover, b > c { margin: 0 auto; }\n";
+
+        assert_eq!(
+            minify_css(css),
+            ".toc a::after{content:' page  ' target-counter(attr(href),page);color:#333}a :hover,b>c{margin:0 auto}"
+        );
+    }
+
+    #[test]
+    fn minify_js_removes_comments() {
+        let js = "/*! MIT */\n// setup\nlet url = 'http://example.com'; /* inline */ let re = /\\/\\*[/]/g;\n/*\n * block\n */\nlet t = `a // b`; // trailing\nlet c = a / b / c;\n";
+
+        assert_eq!(
+            minify_js(js),
+            "/*! MIT */\nlet url = 'http://example.com'; let re = /\\/\\*[/]/g;\nlet t = `a // b`;\nlet c = a / b / c;"
+        );
+    }
+
+    #[test]
+    fn minify_js_keeps_multiline_literals() {
+        let js = "let html = `\n  <p>\n\n    text\n  </p>`;\n    let s = 'a\\\n    b';\n";
+
+        assert_eq!(
+            minify_js(js),
+            "let html = `\n  <p>\n\n    text\n  </p>`;\nlet s = 'a\\\n    b';"
+        );
+    }
+
+    #[test]
+    fn minify_js_keeps_line_breaks() {
+        assert_eq!(
+            minify_js("  let a = 1\n\n  let b = a\n"),
+            "let a = 1\nlet b = a"
+        );
+    }
+}
@@ -1,28 +1,37 @@
 //! Provides access to syntax and theme sets and syntax highlighting in general
 
+use logid::log;
 use once_cell::sync::Lazy;
-use syntect::html::{ClassStyle, ClassedHTMLGenerator};
+use syntect::highlighting::ThemeSet;
+use syntect::html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator};
 use syntect::parsing::{SyntaxReference, SyntaxSet};
 use syntect::util::LinesWithEndings;
 
+use crate::log_id::GeneralWarning;
+
 /// Constant to get syntax highlighting for a plain text
 pub const PLAIN_SYNTAX: &str = "plain";
 
 /// Static reference to the syntax set containing all supported syntaxes
 pub static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
 
+/// Static reference to the theme set containing all themes bundled with syntect
+pub static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+/// Class style of highlighted content.
+///
+/// **Note:** The prefix must be in sync with the bundled stylesheets.
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed {
+    prefix: "highlighted_",
+};
+
 /// Highlight content for the given language.
 ///
 /// **Note:** This only adds CSS classes. To see the highlighted content, set `syntax_highlighting_used = true` in the created `HtmlHead`.
 pub fn highlight_content(content: &str, language: &str) -> Option<String> {
     let syntax = get_syntax(language);
-    let mut html_generator = ClassedHTMLGenerator::new_with_class_style(
-        syntax,
-        &SYNTAX_SET,
-        ClassStyle::SpacedPrefixed {
-            prefix: "highlighted_", // Note: Prefix must be in sync with generated stylesheet
-        },
-    );
+    let mut html_generator =
+        ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAX_SET, CLASS_STYLE);
     for line in LinesWithEndings::from(content) {
         html_generator
             .parse_html_for_line_which_includes_newline(line)
@@ -30,10 +39,39 @@ pub fn highlight_content(content: &str, language: &str) -> Option<String> {
     }
     let highlighted_html = html_generator.finalize();
 
-    // Note: Replace must be in sync with generated stylesheet
+    // Note: Replace must be in sync with generated stylesheets
     Some(highlighted_html.replace("highlighted_c++", "highlighted_cpp"))
 }
 
+/// Generates the stylesheet for highlighted content from the given theme.
+///
+/// The theme is either the name of a theme bundled with syntect (e.g. `InspiredGitHub`), or the path to a `.tmTheme` file.
+/// Returns `None` if the theme could not be loaded.
+pub fn theme_css(theme: &str) -> Option<String> {
+    let loaded;
+    let theme = match THEME_SET.themes.get(theme) {
+        Some(bundled) => bundled,
+        None => match ThemeSet::get_theme(theme) {
+            Ok(from_file) => {
+                loaded = from_file;
+                &loaded
+            }
+            Err(err) => {
+                log!(
+                    GeneralWarning::FileRead,
+                    format!("Could not load highlight theme '{}': {}", theme, err)
+                );
+                return None;
+            }
+        },
+    };
+
+    let css = css_for_theme_with_class_style(theme, CLASS_STYLE).ok()?;
+
+    // Note: Replace must be in sync with highlighted content
+    Some(css.replace("highlighted_c++", "highlighted_cpp"))
+}
+
 /// Get syntax for given language.
 /// Falls back to plain text if language is not found.
 fn get_syntax(language: &str) -> &'static SyntaxReference {
@@ -2,12 +2,28 @@
 
 use crate::render::OutputFormat;
 
-use self::tag::HtmlTag;
+use unimarkup_commons::config::output::HtmlFormat;
+
+use self::{
+    escape::{escape_attribute, escape_text},
+    font::{fonts_css, FontFace},
+    format::{minify_css, minify_js, write_pretty},
+    style::Stylesheet,
+    tag::HtmlTag,
+    template::HtmlTemplate,
+};
 
 pub(crate) mod citeproc;
+pub mod escape;
+pub mod font;
+mod format;
 pub mod highlight;
 pub mod render;
+pub mod style;
 pub mod tag;
+pub mod template;
+
+pub use citeproc::{bundled_style_ids, load_style};
 
 #[derive(Debug, Default)]
 pub struct HtmlAttribute {
@@ -22,7 +38,18 @@ pub struct HtmlAttribute {
 pub struct HtmlElement {
     pub tag: HtmlTag,
     pub attributes: HtmlAttributes,
-    pub content: Option<String>,
+    /// Content of the element.
+    /// Elements without content are displayed as void elements, e.g. `<br/>`.
+    pub content: Option<HtmlContent>,
+}
+
+/// Content of a [`HtmlElement`].
+#[derive(Debug)]
+pub enum HtmlContent {
+    /// Escaped text or already rendered HTML that is written as is.
+    Raw(String),
+    /// Nested elements that may still be transformed before serialisation.
+    Elements(HtmlElements),
 }
 
 #[derive(Debug, Default)]
@@ -34,17 +61,183 @@ pub struct HtmlElements(Vec<HtmlElement>);
 #[derive(Debug, Default)]
 pub struct HtmlHead {
     pub elements: HtmlElements,
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub description: Option<String>,
     pub syntax_highlighting_used: bool,
     pub paged_js_used: bool,
-    pub styles: HtmlAttributes, //TODO: replace with CSS struct
+    pub toc_used: bool,
+    /// Stylesheet for highlighted content that replaces the bundled one.
+    pub highlight_css: Option<String>,
+    /// Theme and configured stylesheets in the order they are added to the head.
+    pub styles: Vec<Stylesheet>,
+    pub fonts: Vec<FontFace>,
+}
+
+/// Styles and scripts needed by rendered HTML content, which are otherwise written into the `<head>` element.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HtmlAssets {
+    /// Content of `<style>` elements, e.g. the CSS for syntax highlighting.
+    pub styles: Vec<String>,
+    /// URLs of linked stylesheets that are added after the `<style>` elements.
+    pub stylesheets: Vec<String>,
+    /// Content of `<script>` elements, e.g. the paged.js polyfill.
+    pub scripts: Vec<String>,
 }
 
 impl HtmlHead {
+    /// Returns the styles and scripts of this head in the order they are written into the `<head>` element.
+    pub fn assets(&self) -> HtmlAssets {
+        let mut assets = HtmlAssets::default();
+
+        if self.paged_js_used {
+            assets
+                .scripts
+                .push(include_str!("paged.polyfill.min.js").to_string());
+        }
+
+        if self.syntax_highlighting_used {
+            let highlighting = match &self.highlight_css {
+                Some(css) => css,
+                None if self.paged_js_used => {
+                    include_str!("../../styles/syntax_highlighting_paged_js.css")
+                }
+                None => include_str!("../../styles/syntax_highlighting.css"),
+            };
+            assets.styles.push(highlighting.to_string());
+        }
+
+        if self.toc_used && self.paged_js_used {
+            // Note: paged.js resolves the page numbers of the linked headings.
+            assets
+                .styles
+                .push(include_str!("../../styles/toc_paged_js.css").to_string());
+        }
+
+        if !self.fonts.is_empty() {
+            // Note: paged.js is used to render PDFs from a temporary file, so linked fonts need absolute paths.
+            assets
+                .styles
+                .push(fonts_css(&self.fonts, self.paged_js_used));
+        }
+
+        // Note: Theme and configured stylesheets are added last, so their rules take precedence over bundled styles.
+        for stylesheet in &self.styles {
+            match stylesheet {
+                Stylesheet::Inline(css) => assets.styles.push(css.clone()),
+                // Note: paged.js is used to render PDFs from a temporary file, so linked stylesheets need absolute paths.
+                linked => assets.stylesheets.extend(linked.link(self.paged_js_used)),
+            }
+        }
+
+        assets
+    }
+
+    /// Returns the content of the `<head>` element without the surrounding tags.
+    pub fn content(&self) -> String {
+        self.to_elements(false).to_string()
+    }
+
+    /// Returns the elements of the `<head>` element.
+    /// Inlined styles and scripts are minified if `minify` is `true`.
+    pub fn to_elements(&self, minify: bool) -> HtmlElements {
+        let mut elements = Vec::new();
+
+        if let Some(title) = &self.title {
+            elements.push(HtmlElement {
+                tag: HtmlTag::Title,
+                attributes: HtmlAttributes::default(),
+                content: Some(escape_text(title).into()),
+            });
+        }
+        let metas = self
+            .authors
+            .iter()
+            .map(|author| ("author", author))
+            .chain(self.description.iter().map(|desc| ("description", desc)));
+        for (name, value) in metas {
+            elements.push(HtmlElement {
+                tag: HtmlTag::Meta,
+                attributes: HtmlAttributes::from(vec![
+                    HtmlAttribute {
+                        name: "name".to_string(),
+                        value: Some(name.to_string()),
+                    },
+                    HtmlAttribute {
+                        name: "content".to_string(),
+                        value: Some(value.clone()),
+                    },
+                ]),
+                content: None,
+            });
+        }
+
+        if !self.elements.is_empty() {
+            elements.push(HtmlElement {
+                tag: HtmlTag::PlainContent,
+                attributes: HtmlAttributes::default(),
+                content: Some(self.elements.to_string().into()),
+            });
+        }
+
+        let assets = self.assets();
+        for script in assets.scripts {
+            elements.push(HtmlElement {
+                tag: HtmlTag::Script,
+                attributes: HtmlAttributes::default(),
+                content: Some(if minify { minify_js(&script) } else { script }.into()),
+            });
+        }
+        for style in assets.styles {
+            elements.push(HtmlElement {
+                tag: HtmlTag::Style,
+                attributes: HtmlAttributes::default(),
+                content: Some(if minify { minify_css(&style) } else { style }.into()),
+            });
+        }
+        for stylesheet in assets.stylesheets {
+            elements.push(HtmlElement {
+                tag: HtmlTag::Link,
+                attributes: HtmlAttributes::from(vec![
+                    HtmlAttribute {
+                        name: "rel".to_string(),
+                        value: Some("stylesheet".to_string()),
+                    },
+                    HtmlAttribute {
+                        name: "href".to_string(),
+                        value: Some(stylesheet),
+                    },
+                ]),
+                content: None,
+            });
+        }
+
+        //TODO: write other head styles (try to use LightningCss optimizations)
+
+        HtmlElements(elements)
+    }
+
     fn merge(&mut self, mut other: Self) {
         self.elements.append(&mut other.elements);
-        self.styles.append(&mut other.styles);
+        self.title = self.title.take().or(other.title);
+        append_unique(&mut self.authors, other.authors);
+        self.description = self.description.take().or(other.description);
+        self.highlight_css = self.highlight_css.take().or(other.highlight_css);
+        append_unique(&mut self.styles, other.styles);
         self.syntax_highlighting_used |= other.syntax_highlighting_used;
         self.paged_js_used |= other.paged_js_used;
+        self.toc_used |= other.toc_used;
+        append_unique(&mut self.fonts, other.fonts);
+    }
+}
+
+/// Appends the entries of `other` that are not already in `entries`.
+/// Heads of rendered blocks are created from the same config, so their entries would otherwise be repeated.
+fn append_unique<T: PartialEq>(entries: &mut Vec<T>, other: Vec<T>) {
+    for entry in other {
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
     }
 }
 
@@ -58,6 +251,25 @@ pub struct Html {
     pub head: HtmlHead,
     pub body: HtmlBody,
     pub lang: String,
+    /// Template used to write the document shell. The default shell is used if not set.
+    pub template: Option<HtmlTemplate>,
+    /// Layout of the written HTML.
+    pub format: HtmlFormat,
+}
+
+/// Rendered HTML content without the `<html>`, `<head>` and `<body>` wrapper,
+/// e.g. to embed it into an existing page.
+///
+/// **Note:** The [`Display`](std::fmt::Display) implementation only writes the content.
+/// The [`HtmlAssets`] must be included by the embedding page.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HtmlFragment {
+    /// The rendered body elements.
+    pub content: String,
+    /// Styles and scripts the content needs.
+    pub assets: HtmlAssets,
+    /// Language of the content.
+    pub lang: String,
 }
 
 impl Html {
@@ -87,27 +299,106 @@ impl Html {
         let mut html = Html::with_body(HtmlBody::from(HtmlElement {
             tag: outer_tag,
             attributes: outer_attributes,
-            content: Some(inner.body.elements.to_string()),
+            content: Some(inner.body.elements.into()),
         }));
         html.head.merge(inner.head);
 
         html
     }
+
+    /// Returns the content of the `<head>` element without the surrounding tags in the set [`HtmlFormat`].
+    pub fn head_content(&self) -> String {
+        self.head_content_at(0)
+    }
+
+    /// Returns the body elements in the set [`HtmlFormat`].
+    pub fn body_content(&self) -> String {
+        self.body_content_at(0)
+    }
+
+    fn head_content_at(&self, depth: usize) -> String {
+        match self.format {
+            HtmlFormat::Compact => self.head.content(),
+            HtmlFormat::Pretty => {
+                let mut content = String::new();
+                write_pretty(&self.head.to_elements(false), depth, &mut content);
+                content
+            }
+            HtmlFormat::Minified => self.head.to_elements(true).to_string(),
+        }
+    }
+
+    fn body_content_at(&self, depth: usize) -> String {
+        match self.format {
+            HtmlFormat::Compact | HtmlFormat::Minified => self.body.elements.to_string(),
+            HtmlFormat::Pretty => {
+                let mut content = String::new();
+                write_pretty(&self.body.elements, depth, &mut content);
+                content
+            }
+        }
+    }
+
+    /// Returns the body elements of this HTML as [`HtmlFragment`] together with the assets of the head.
+    pub fn fragment(&self) -> HtmlFragment {
+        HtmlFragment {
+            content: self.body_content(),
+            assets: self.head.assets(),
+            lang: self.lang.clone(),
+        }
+    }
 }
 
 impl OutputFormat for Html {
     fn new(context: &crate::render::Context) -> Self {
+        let metadata = &context.get_config().preamble.metadata;
+        let styling = &context.get_config().preamble.styling;
+        let mut font_paths: Vec<_> = metadata.fonts.iter().collect();
+        // Note: Fonts are stored in a `HashSet`, so they are sorted to get a stable output.
+        font_paths.sort();
+
+        let mut authors: Vec<_> = metadata.authors.iter().cloned().collect();
+        // Note: Authors are stored in a `HashSet`, so they are sorted to get a stable output.
+        authors.sort();
+
         Html {
             head: HtmlHead {
                 elements: HtmlElements(Vec::new()),
+                title: metadata.title.clone(),
+                authors,
+                description: metadata.description.clone(),
                 syntax_highlighting_used: false,
                 paged_js_used: false,
-                styles: HtmlAttributes(Vec::new()),
+                toc_used: false,
+                highlight_css: styling
+                    .highlight_theme
+                    .as_deref()
+                    .and_then(highlight::theme_css),
+                styles: styling
+                    .theme
+                    .map(Stylesheet::theme)
+                    .into_iter()
+                    .chain(
+                        styling
+                            .stylesheets
+                            .iter()
+                            .filter_map(|path| Stylesheet::new(path, !styling.link_stylesheets)),
+                    )
+                    .collect(),
+                fonts: font_paths
+                    .into_iter()
+                    .filter_map(|path| FontFace::new(path, !metadata.link_fonts))
+                    .collect(),
             },
             body: HtmlBody {
                 elements: HtmlElements(Vec::new()),
             },
             lang: context.get_lang().to_string(),
+            template: context
+                .html_template
+                .clone()
+                .map(|template| template.with_sections(context)),
+            format: context.get_config().output.html_format,
         }
     }
 
@@ -122,11 +413,32 @@ impl OutputFormat for Html {
 
 impl std::fmt::Display for Html {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "<!DOCTYPE HTML><html lang=\"{}\">{}{}</html>",
-            self.lang, self.head, self.body
-        )
+        if let Some(template) = &self.template {
+            return write!(f, "{}", template.fill(self));
+        }
+
+        match self.format {
+            HtmlFormat::Pretty => write!(
+                f,
+                "<!DOCTYPE HTML>\n<html lang=\"{}\">\n  <head>\n{}  </head>\n  <body>\n{}  </body>\n</html>\n",
+                self.lang,
+                self.head_content_at(2),
+                self.body_content_at(2)
+            ),
+            HtmlFormat::Compact | HtmlFormat::Minified => write!(
+                f,
+                "<!DOCTYPE HTML><html lang=\"{}\"><head>{}</head><body>{}</body></html>",
+                self.lang,
+                self.head_content(),
+                self.body_content()
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for HtmlFragment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.content)
     }
 }
 
@@ -154,6 +466,90 @@ impl std::fmt::Display for HtmlElement {
     }
 }
 
+impl HtmlElement {
+    /// Returns the nested elements, or `None` if this element has raw or no content.
+    pub fn children(&self) -> Option<&HtmlElements> {
+        match &self.content {
+            Some(HtmlContent::Elements(elements)) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// Returns the nested elements as mutable reference, or `None` if this element has raw or no content.
+    pub fn children_mut(&mut self) -> Option<&mut HtmlElements> {
+        match &mut self.content {
+            Some(HtmlContent::Elements(elements)) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the attribute with the given name, if it is set with a value.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+            .and_then(|attribute| attribute.value.as_deref())
+    }
+}
+
+impl HtmlElements {
+    /// Calls `f` for every element of this tree in document order.
+    /// Parents are visited before their children.
+    pub fn visit<'a>(&'a self, f: &mut impl FnMut(&'a HtmlElement)) {
+        for element in &self.0 {
+            f(element);
+            if let Some(children) = element.children() {
+                children.visit(f);
+            }
+        }
+    }
+
+    /// Calls `f` for every element of this tree in document order, allowing to transform the elements.
+    /// Parents are visited before their children, so children added by `f` are visited as well.
+    pub fn visit_mut(&mut self, f: &mut impl FnMut(&mut HtmlElement)) {
+        for element in &mut self.0 {
+            f(element);
+            if let Some(children) = element.children_mut() {
+                children.visit_mut(f);
+            }
+        }
+    }
+
+    /// Returns the `id` attributes of all elements of this tree in document order.
+    pub fn ids(&self) -> Vec<&str> {
+        let mut ids = Vec::new();
+        self.visit(&mut |element| ids.extend(element.attribute("id")));
+        ids
+    }
+}
+
+impl std::fmt::Display for HtmlContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HtmlContent::Raw(raw) => write!(f, "{}", raw),
+            HtmlContent::Elements(elements) => write!(f, "{}", elements),
+        }
+    }
+}
+
+impl From<String> for HtmlContent {
+    fn from(value: String) -> Self {
+        HtmlContent::Raw(value)
+    }
+}
+
+impl From<HtmlElements> for HtmlContent {
+    fn from(value: HtmlElements) -> Self {
+        HtmlContent::Elements(value)
+    }
+}
+
+impl From<Vec<HtmlElement>> for HtmlContent {
+    fn from(value: Vec<HtmlElement>) -> Self {
+        HtmlContent::Elements(HtmlElements(value))
+    }
+}
+
 impl From<Vec<HtmlElement>> for HtmlElements {
     fn from(value: Vec<HtmlElement>) -> Self {
         HtmlElements(value)
@@ -185,25 +581,7 @@ impl std::ops::DerefMut for HtmlElements {
 
 impl std::fmt::Display for HtmlHead {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "<head>{}", self.elements)?;
-        let highlighting = if self.paged_js_used {
-            let _ = write!(
-                f,
-                "<script>{}</script>",
-                include_str!("paged.polyfill.min.js")
-            );
-            include_str!("../../styles/syntax_highlighting_paged_js.css")
-        } else {
-            include_str!("../../styles/syntax_highlighting.css")
-        };
-        if self.syntax_highlighting_used {
-            write!(f, "<style>{}</style>", highlighting)?;
-        }
-
-        //TODO: write other head styles (try to use LightningCss optimizations)
-
-        write!(f, "</head>")?;
-        Ok(())
+        write!(f, "<head>{}</head>", self.content())
     }
 }
 
@@ -233,7 +611,7 @@ impl From<HtmlElement> for HtmlBody {
 impl std::fmt::Display for HtmlAttribute {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.value {
-            Some(value) => write!(f, "{}='{}'", self.name, value),
+            Some(value) => write!(f, "{}='{}'", self.name, escape_attribute(value)),
             None => write!(f, "{}", self.name),
         }
     }
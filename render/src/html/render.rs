@@ -1,6 +1,6 @@
-use crate::html::citeproc::CiteprocWrapper;
-use crate::log_id::RenderError;
-use serde_json::Value;
+use crate::log_id::{GeneralWarning, RenderError};
+use logid::log;
+use unimarkup_commons::config::preamble::element_kind_info;
 use unimarkup_commons::lexer::{span::Span, symbol::SymbolKind, token::TokenKind};
 use unimarkup_inline::element::substitution::DistinctReference;
 use unimarkup_inline::element::{
@@ -9,15 +9,24 @@ use unimarkup_inline::element::{
         Bold, Highlight, Italic, Math, Overline, Quote, Strikethrough, Subscript, Superscript,
         Underline, Verbatim,
     },
-    textbox::{citation::Citation, hyperlink::Hyperlink, TextBox},
+    textbox::{citation::Citation, hyperlink::Hyperlink, reference::InternalReference, TextBox},
     Inline, InlineElement,
 };
-use unimarkup_parser::elements::indents::{BulletList, BulletListEntry};
+use unimarkup_parser::{
+    document::OutlineEntry,
+    elements::{
+        atomic::{Bibliography, Heading, TableOfContents},
+        indents::{BulletList, BulletListEntry},
+    },
+};
 
 use crate::render::{Context, OutputFormat, Renderer};
 
 use super::{
-    highlight, tag::HtmlTag, Html, HtmlAttribute, HtmlAttributes, HtmlBody, HtmlElement, HtmlHead,
+    escape::{escape_text, is_safe_url},
+    highlight,
+    tag::HtmlTag,
+    Html, HtmlAttribute, HtmlAttributes, HtmlBody, HtmlElement, HtmlHead,
 };
 
 #[derive(Debug, Default)]
@@ -35,6 +44,73 @@ impl HtmlRenderer {
     }
 }
 
+impl HtmlRenderer {
+    /// Renders the given content of the heading, prefixed with the heading number if it has one.
+    fn render_numbered(
+        &mut self,
+        heading: &Heading,
+        content: &[Inline],
+        context: &Context,
+    ) -> Result<Html, RenderError> {
+        let mut html = Html::default();
+        if let Some(number) = context.heading_number(heading) {
+            html.append(Html::with_body(HtmlBody::from(vec![
+                HtmlElement {
+                    tag: HtmlTag::Span,
+                    attributes: HtmlAttributes::from(vec![HtmlAttribute {
+                        name: "class".to_string(),
+                        value: Some("heading-number".to_string()),
+                    }]),
+                    content: Some(number.to_string().into()),
+                },
+                HtmlElement {
+                    tag: HtmlTag::PlainContent,
+                    attributes: HtmlAttributes::default(),
+                    content: Some(" ".to_string().into()),
+                },
+            ])))?;
+        }
+        html.append(self.render_inlines(content, context)?)?;
+
+        Ok(html)
+    }
+
+    /// Renders the given outline entries as nested lists linking to their headings.
+    fn render_outline(
+        &mut self,
+        outline: &[OutlineEntry],
+        context: &Context,
+    ) -> Result<Html, RenderError> {
+        let mut items = Html::default();
+        for entry in outline {
+            // Citations are only rendered at the heading, because rendered citations are taken in document flow.
+            let content: Vec<Inline> = entry
+                .heading
+                .content
+                .iter()
+                .filter(|inline| !matches!(inline, Inline::Citation(_)))
+                .cloned()
+                .collect();
+            let link = Html::nested(
+                HtmlTag::A,
+                HtmlAttributes::from(vec![HtmlAttribute {
+                    name: "href".to_string(),
+                    value: Some(format!("#{}", entry.heading.id)),
+                }]),
+                self.render_numbered(entry.heading, &content, context)?,
+            );
+
+            let mut item = link;
+            if !entry.children.is_empty() {
+                item.append(self.render_outline(&entry.children, context)?)?;
+            }
+            items.append(Html::nested(HtmlTag::Li, HtmlAttributes::default(), item))?;
+        }
+
+        Ok(Html::nested(HtmlTag::Ul, HtmlAttributes::default(), items))
+    }
+}
+
 impl Renderer<Html> for HtmlRenderer {
     fn get_target(&mut self) -> Result<Html, crate::log_id::RenderError> {
         let html = Html::with_head(HtmlHead {
@@ -59,7 +135,34 @@ impl Renderer<Html> for HtmlRenderer {
         heading: &unimarkup_parser::elements::atomic::Heading,
         context: &Context,
     ) -> Result<Html, crate::log_id::RenderError> {
-        let inner = self.render_inlines(&heading.content, context)?;
+        let mut inner = self.render_numbered(heading, &heading.content, context)?;
+        if context.get_config().preamble.render.heading_anchors {
+            inner.append(Html::with_body(HtmlBody::from(vec![
+                HtmlElement {
+                    tag: HtmlTag::PlainContent,
+                    attributes: HtmlAttributes::default(),
+                    content: Some(" ".to_string().into()),
+                },
+                HtmlElement {
+                    tag: HtmlTag::A,
+                    attributes: HtmlAttributes::from(vec![
+                        HtmlAttribute {
+                            name: "class".to_string(),
+                            value: Some("heading-anchor".to_string()),
+                        },
+                        HtmlAttribute {
+                            name: "href".to_string(),
+                            value: Some(format!("#{}", heading.id)),
+                        },
+                        HtmlAttribute {
+                            name: "aria-hidden".to_string(),
+                            value: Some("true".to_string()),
+                        },
+                    ]),
+                    content: Some("#".to_string().into()),
+                },
+            ])))?;
+        }
         let tag = HtmlTag::from(heading.level);
 
         let attributes = HtmlAttributes::from(vec![HtmlAttribute {
@@ -91,7 +194,8 @@ impl Renderer<Html> for HtmlRenderer {
                             .as_ref()
                             .unwrap_or(&highlight::PLAIN_SYNTAX.to_string()),
                     )
-                    .unwrap_or(verbatim.content.clone()),
+                    .unwrap_or_else(|| escape_text(&verbatim.content))
+                    .into(),
                 ),
             }),
         );
@@ -104,7 +208,7 @@ impl Renderer<Html> for HtmlRenderer {
         bullet_list: &BulletList,
         context: &Context,
     ) -> Result<Html, crate::log_id::RenderError> {
-        let mut entries = Html::new(context);
+        let mut entries = Html::default();
 
         for entry in &bullet_list.entries {
             entries.append(self.render_bullet_list_entry(entry, context)?)?;
@@ -147,12 +251,71 @@ impl Renderer<Html> for HtmlRenderer {
                 name: "style".to_string(),
                 value: Some("white-space: pre-wrap;".to_string()),
             }]),
-            content: Some(String::from(TokenKind::Blankline)),
+            content: Some(String::from(TokenKind::Blankline).into()),
         }));
 
         Ok(html)
     }
 
+    fn render_bibliography_block(
+        &mut self,
+        bibliography: &Bibliography,
+        context: &Context,
+    ) -> Result<Html, RenderError> {
+        let Some(rendered) = context.placed_bibliography(bibliography) else {
+            return Ok(Html::default());
+        };
+
+        let mut elements: Vec<HtmlElement> = vec![];
+        if let Some(title) = context.bibliography_title(bibliography) {
+            elements.push(HtmlElement {
+                tag: HtmlTag::from(context.bibliography_title_level(bibliography)),
+                attributes: HtmlAttributes::default(),
+                content: Some(escape_text(&title).into()),
+            });
+        }
+        elements.push(HtmlElement {
+            tag: HtmlTag::PlainContent,
+            attributes: HtmlAttributes::default(),
+            content: Some(rendered.into()),
+        });
+
+        Ok(Html::with_body(HtmlBody::from(elements)))
+    }
+
+    fn render_toc_block(
+        &mut self,
+        toc: &TableOfContents,
+        context: &Context,
+    ) -> Result<Html, RenderError> {
+        let outline = context.doc.outline(&toc.options);
+        if outline.is_empty() {
+            return Ok(Html::default());
+        }
+
+        let mut inner = Html::default();
+        if let Some(title) = context.toc_title(toc) {
+            inner.append(Html::with_body(HtmlBody::from(HtmlElement {
+                tag: HtmlTag::from(context.toc_title_level(toc)),
+                attributes: HtmlAttributes::default(),
+                content: Some(escape_text(&title).into()),
+            })))?;
+        }
+        inner.append(self.render_outline(&outline, context)?)?;
+
+        let mut html = Html::nested(
+            HtmlTag::Nav,
+            HtmlAttributes::from(vec![HtmlAttribute {
+                name: "class".to_string(),
+                value: Some("toc".to_string()),
+            }]),
+            inner,
+        );
+        html.head.toc_used = true;
+
+        Ok(html)
+    }
+
     fn render_textbox(
         &mut self,
         textbox: &TextBox,
@@ -173,10 +336,24 @@ impl Renderer<Html> for HtmlRenderer {
         context: &Context,
     ) -> Result<Html, crate::log_id::RenderError> {
         let inner = self.render_nested_inline(hyperlink.inner(), context)?;
-        let mut attributes = vec![HtmlAttribute {
-            name: "href".to_string(),
-            value: Some(hyperlink.link().to_string()),
-        }];
+        let mut attributes = Vec::new();
+
+        if context.get_config().preamble.render.allow_unsafe || is_safe_url(hyperlink.link()) {
+            attributes.push(HtmlAttribute {
+                name: "href".to_string(),
+                value: Some(hyperlink.link().to_string()),
+            });
+        } else {
+            // Note: The link text is kept, but the unsafe target is never written into the output.
+            log!(
+                GeneralWarning::UnsafeUrl,
+                format!(
+                    "Removed unsafe link target '{}'. Set `allow_unsafe` to keep it.",
+                    hyperlink.link()
+                ),
+                add: element_kind_info("Hyperlink")
+            );
+        }
 
         if let Some(link_text) = hyperlink.link_text() {
             attributes.push(HtmlAttribute {
@@ -201,7 +378,7 @@ impl Renderer<Html> for HtmlRenderer {
         let html = Html::with_body(HtmlBody::from(HtmlElement {
             tag: HtmlTag::PlainContent,
             attributes: HtmlAttributes::default(),
-            content: Some(citation.clone()),
+            content: Some(citation.clone().into()),
         }));
 
         Ok(html)
@@ -212,63 +389,39 @@ impl Renderer<Html> for HtmlRenderer {
         distinct_reference: &DistinctReference,
         context: &Context,
     ) -> Result<Html, RenderError> {
-        let mut selected_item: Option<Value> = None;
-        for csl_item in context.csl_data.clone().items {
-            if csl_item.id.to_string() == distinct_reference.id() {
-                selected_item = Some(serde_json::to_value(csl_item).unwrap());
-                break;
-            }
-        }
-        let content;
-        if let Some(item_value) = selected_item {
-            let mut result_value = item_value.clone();
-            if distinct_reference.fields().len() == 1 && distinct_reference.fields()[0] == "authors"
-            {
-                let citeproc_wrapper = if cfg!(test) {
-                    CiteprocWrapper::new_with_path("./src/html/citeproc/js/citeproc_adapter.js")
-                } else {
-                    CiteprocWrapper::new()
-                };
-                content = match citeproc_wrapper {
-                    Ok(mut citeproc) => citeproc
-                        .get_author_only(context.doc, distinct_reference.id().to_string())
-                        .unwrap_or("########### CITATION ERROR ###########".to_string()),
-                    Err(_) => "########### CITATION ERROR ###########".to_string(),
-                }
-            } else {
-                for field in distinct_reference.fields().clone() {
-                    result_value = match field.parse::<usize>() {
-                        Ok(n) => result_value[n].clone(),
-                        Err(_) => result_value[field].clone(),
-                    };
-                }
-                content = if let Some(s) = result_value.as_str() {
-                    s.to_string()
-                } else {
-                    let content_as_string = result_value.to_string();
-                    if content_as_string.ends_with(".0") {
-                        match content_as_string[..content_as_string.len() - 2].parse::<usize>() {
-                            Ok(n) => n.to_string(),
-                            Err(_) => content_as_string,
-                        }
-                    } else if content_as_string == "null" {
-                        "########### CITATION ERROR ###########".to_string()
-                    } else {
-                        content_as_string
-                    }
-                }
-            }
-        } else {
-            content = "########### CITATION ERROR ###########".to_string()
-        }
+        let content = context
+            .distinct_reference(distinct_reference)
+            .map(|content| escape_text(&content))
+            .unwrap_or("########### CITATION ERROR ###########".to_string());
         let html = Html::with_body(HtmlBody::from(HtmlElement {
             tag: HtmlTag::PlainContent,
             attributes: HtmlAttributes::default(),
-            content: Some(content),
+            content: Some(content.into()),
         }));
         Ok(html)
     }
 
+    fn render_internal_reference(
+        &mut self,
+        internal_reference: &InternalReference,
+        context: &Context,
+    ) -> Result<Html, RenderError> {
+        // Dead references are reported when creating the context, so the id is shown instead.
+        let content = context
+            .internal_reference_label(internal_reference)
+            .unwrap_or(internal_reference.target().to_string());
+        let attributes = vec![HtmlAttribute {
+            name: "href".to_string(),
+            value: Some(format!("#{}", internal_reference.target())),
+        }];
+
+        Ok(Html::with_body(HtmlBody::from(HtmlElement {
+            tag: HtmlTag::A,
+            attributes: HtmlAttributes(attributes),
+            content: Some(escape_text(&content).into()),
+        })))
+    }
+
     fn render_bibliography(
         &mut self,
         context: &Context,
@@ -276,22 +429,15 @@ impl Renderer<Html> for HtmlRenderer {
         match &context.bibliography {
             Some(bibliography) => {
                 let mut elements: Vec<HtmlElement> = vec![];
-                let bibliography_string = if context.get_lang().id.language
-                    == unimarkup_commons::config::icu_locid::subtags::language!("de")
-                {
-                    "Literaturverzeichnis"
-                } else {
-                    "Bibliography"
-                };
                 elements.push(HtmlElement {
                     tag: HtmlTag::H1,
                     attributes: HtmlAttributes::default(),
-                    content: Some(bibliography_string.to_string()),
+                    content: Some(escape_text(context.localized_bibliography_title()).into()),
                 });
                 elements.push(HtmlElement {
                     tag: HtmlTag::PlainContent,
                     attributes: HtmlAttributes::default(),
-                    content: Some(bibliography.clone()),
+                    content: Some(bibliography.clone().into()),
                 });
                 let body = HtmlBody::from(elements);
                 let html = Html::with_body(body);
@@ -306,14 +452,17 @@ impl Renderer<Html> for HtmlRenderer {
             Some(footnotes) => {
                 let elements: Vec<HtmlElement> = vec![
                     HtmlElement {
-                        tag: HtmlTag::PlainContent,
-                        attributes: HtmlAttributes::default(),
-                        content: Some("<hr style=\"width: 25%; margin-left: 0\">".to_string()),
+                        tag: HtmlTag::Hr,
+                        attributes: HtmlAttributes::from(vec![HtmlAttribute {
+                            name: "style".to_string(),
+                            value: Some("width: 25%; margin-left: 0".to_string()),
+                        }]),
+                        content: None,
                     },
                     HtmlElement {
                         tag: HtmlTag::PlainContent,
                         attributes: HtmlAttributes::default(),
-                        content: Some(footnotes.clone()),
+                        content: Some(footnotes.clone().into()),
                     },
                 ];
                 let body = HtmlBody::from(elements);
@@ -445,7 +594,7 @@ impl Renderer<Html> for HtmlRenderer {
         let html = Html::with_body(HtmlBody::from(HtmlElement {
             tag: HtmlTag::Code,
             attributes: HtmlAttributes::default(),
-            content: Some(verbatim.inner().as_unimarkup()),
+            content: Some(escape_text(&verbatim.inner().as_unimarkup()).into()),
         }));
 
         Ok(html)
@@ -472,7 +621,7 @@ impl Renderer<Html> for HtmlRenderer {
             elements: vec![HtmlElement {
                 tag: HtmlTag::PlainContent,
                 attributes: HtmlAttributes::default(),
-                content: Some(math),
+                content: Some(math.into()),
             }]
             .into(),
         }))
@@ -486,7 +635,7 @@ impl Renderer<Html> for HtmlRenderer {
         let html = Html::with_body(HtmlBody::from(HtmlElement {
             tag: HtmlTag::PlainContent,
             attributes: HtmlAttributes::default(),
-            content: Some(plain.content().clone()),
+            content: Some(escape_text(plain.content()).into()),
         }));
 
         Ok(html)
@@ -500,7 +649,7 @@ impl Renderer<Html> for HtmlRenderer {
         let html = Html::with_body(HtmlBody::from(HtmlElement {
             tag: HtmlTag::PlainContent,
             attributes: HtmlAttributes::default(),
-            content: Some(SymbolKind::Whitespace.as_str().to_string()),
+            content: Some(SymbolKind::Whitespace.as_str().to_string().into()),
         }));
 
         Ok(html)
@@ -545,7 +694,7 @@ impl Renderer<Html> for HtmlRenderer {
                 name: "style".to_string(),
                 value: Some("white-space: pre-wrap;".to_string()),
             }]),
-            content: Some(SymbolKind::Whitespace.as_str().to_string()),
+            content: Some(SymbolKind::Whitespace.as_str().to_string().into()),
         }));
 
         Ok(html)
@@ -559,7 +708,7 @@ impl Renderer<Html> for HtmlRenderer {
         let html = Html::with_body(HtmlBody::from(HtmlElement {
             tag: HtmlTag::PlainContent,
             attributes: HtmlAttributes::default(),
-            content: Some(escaped_plain.content().clone()),
+            content: Some(escape_text(escaped_plain.content()).into()),
         }));
 
         Ok(html)
@@ -570,12 +719,15 @@ impl Renderer<Html> for HtmlRenderer {
 mod tests {
     use crate::html::citeproc::get_csl_data;
     use crate::html::render::HtmlRenderer;
+    use crate::html::tag::HtmlTag;
+    use crate::html::{style::Stylesheet, HtmlHead};
     use crate::render::{Context, Renderer};
     use std::collections::{HashMap, HashSet};
     use std::path::PathBuf;
     use std::str::FromStr;
     use unimarkup_commons::config::icu_locid::locale;
-    use unimarkup_commons::config::preamble::{Citedata, I18n, Preamble};
+    use unimarkup_commons::config::output::HtmlFormat;
+    use unimarkup_commons::config::preamble::{Citedata, HtmlTheme, I18n, Preamble};
     use unimarkup_commons::config::Config;
     use unimarkup_inline::element::substitution::DistinctReference;
     use unimarkup_parser::document::Document;
@@ -591,6 +743,9 @@ mod tests {
             footnotes: None,
             bibliography: None,
             csl_data: get_csl_data(&citation_paths),
+            citeproc: Default::default(),
+            reference_targets: doc.reference_targets(),
+            html_template: None,
         }
     }
 
@@ -618,12 +773,18 @@ mod tests {
                         ),
                         citation_locales,
                         csl_locales: vec![],
+                        warn_unused_references: false,
                     },
                     render: Default::default(),
                     i18n: I18n {
                         lang: Some(locale!("de-DE")),
                         output_langs: Default::default(),
                     },
+                    numbering: Default::default(),
+                    toc: Default::default(),
+                    slugs: Default::default(),
+                    styling: Default::default(),
+                    latex: Default::default(),
                 },
                 output: Default::default(),
                 merging: Default::default(),
@@ -650,12 +811,47 @@ mod tests {
 
         assert!(actual.is_ok());
         let actual_unwrapped = actual.unwrap();
-        let content_option = actual_unwrapped.body.elements.0[0].content.clone();
+        let content_option = actual_unwrapped.body.elements.0[0]
+            .content
+            .as_ref()
+            .map(ToString::to_string);
         assert!(content_option.is_some());
         let content_unwrapped = content_option.unwrap();
         assert_eq!(content_unwrapped, "A1 Cook");
     }
 
+    #[test]
+    fn render_distinct_reference_authors_escaped_once() {
+        let references =
+            PathBuf::from_str("./src/html/citeproc/test_files/authors_with_ampersand.csl").unwrap();
+        let mut config = Config::default();
+        config.preamble.cite.references = HashSet::from([references.clone()]);
+        config.preamble.cite.style = Some(PathBuf::from("apa"));
+        let doc = unimarkup_parser::parse_unimarkup("", config);
+        let context = Context {
+            csl_data: get_csl_data(&HashSet::from([references])),
+            ..get_test_context(&doc)
+        };
+        let distinct_reference = DistinctReference::new(
+            "id-1".to_string(),
+            vec!["authors".to_string()],
+            Default::default(),
+            Default::default(),
+        );
+
+        let actual = HtmlRenderer::default()
+            .render_distinct_reference(&distinct_reference, &context)
+            .unwrap();
+
+        assert_eq!(
+            actual.body.elements.0[0]
+                .content
+                .as_ref()
+                .map(ToString::to_string),
+            Some("Smith &amp; Sons".to_string())
+        );
+    }
+
     #[test]
     fn test_render_distinct_reference_with_one_field() {
         let doc = Document {
@@ -682,7 +878,10 @@ mod tests {
 
         assert!(actual.is_ok());
         let actual_unwrapped = actual.unwrap();
-        let content_option = actual_unwrapped.body.elements.0[0].content.clone();
+        let content_option = actual_unwrapped.body.elements.0[0]
+            .content
+            .as_ref()
+            .map(ToString::to_string);
         assert!(content_option.is_some());
         let content_unwrapped = content_option.unwrap();
         assert_eq!(content_unwrapped, "Some Notes on Gertrude Stein and Deixis");
@@ -714,7 +913,10 @@ mod tests {
 
         assert!(actual.is_ok());
         let actual_unwrapped = actual.unwrap();
-        let content_option = actual_unwrapped.body.elements.0[0].content.clone();
+        let content_option = actual_unwrapped.body.elements.0[0]
+            .content
+            .as_ref()
+            .map(ToString::to_string);
         assert!(content_option.is_some());
         let content_unwrapped = content_option.unwrap();
         assert_eq!(content_unwrapped, "Cook");
@@ -746,7 +948,10 @@ mod tests {
 
         assert!(actual.is_ok());
         let actual_unwrapped = actual.unwrap();
-        let content_option = actual_unwrapped.body.elements.0[0].content.clone();
+        let content_option = actual_unwrapped.body.elements.0[0]
+            .content
+            .as_ref()
+            .map(ToString::to_string);
         assert!(content_option.is_some());
         let content_unwrapped = content_option.unwrap();
         assert_eq!(content_unwrapped, "########### CITATION ERROR ###########");
@@ -782,7 +987,10 @@ mod tests {
 
         assert!(actual.is_ok());
         let actual_unwrapped = actual.unwrap();
-        let content_option = actual_unwrapped.body.elements.0[0].content.clone();
+        let content_option = actual_unwrapped.body.elements.0[0]
+            .content
+            .as_ref()
+            .map(ToString::to_string);
         assert!(content_option.is_some());
         let content_unwrapped = content_option.unwrap();
         assert_eq!(content_unwrapped, "########### CITATION ERROR ###########");
@@ -819,9 +1027,418 @@ mod tests {
 
         assert!(actual.is_ok());
         let actual_unwrapped = actual.unwrap();
-        let content_option = actual_unwrapped.body.elements.0[0].content.clone();
+        let content_option = actual_unwrapped.body.elements.0[0]
+            .content
+            .as_ref()
+            .map(ToString::to_string);
         assert!(content_option.is_some());
         let content_unwrapped = content_option.unwrap();
         assert_eq!(content_unwrapped, "1997");
     }
+
+    #[test]
+    fn render_placed_bibliographies() {
+        let mut config = Config::default();
+        config.preamble.cite.references =
+            HashSet::from([
+                PathBuf::from_str("./src/html/citeproc/test_files/citation_items.csl").unwrap(),
+            ]);
+        let input = "# Intro\n\nSee [&&id-1].\n\n{$um.bibliography}{\"section\": true, \"title\": \"Intro sources\"}\n\n# Method\n\nSee [&&id-2].\n\n{$um.bibliography}\n";
+        let doc = unimarkup_parser::parse_unimarkup(input, config);
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        let (intro, method) = html.split_once("Method").unwrap();
+        assert!(intro.contains("Intro sources"));
+        assert!(intro.contains("<div id=\"id-1\">"));
+        assert!(!intro.contains("<div id=\"id-2\">"));
+        assert!(method.contains("Bibliography"));
+        assert!(method.contains("<div id=\"id-1\">"));
+        assert!(method.contains("<div id=\"id-2\">"));
+        assert_eq!(html.matches("Bibliography").count(), 1);
+    }
+
+    #[test]
+    fn render_distinct_reference_formatted_fields() {
+        let doc = Document {
+            blocks: vec![],
+            config: Default::default(),
+            citations: vec![],
+            macros: vec![],
+            variables: vec![],
+            metadata: vec![],
+            resources: vec![],
+        };
+        let context = get_test_context(&doc);
+        let mut under_test = HtmlRenderer::default();
+
+        for (fields, expected) in [
+            (vec!["issued"], "1997"),
+            (vec!["issued", "year"], "1997"),
+            (vec!["author", "family"], "Cook"),
+        ] {
+            let distinct_reference = DistinctReference::new(
+                "id-1".to_string(),
+                fields.iter().map(|field| field.to_string()).collect(),
+                Default::default(),
+                Default::default(),
+            );
+            let actual = under_test
+                .render_distinct_reference(&distinct_reference, &context)
+                .unwrap();
+
+            assert_eq!(
+                actual.body.elements.0[0]
+                    .content
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .as_deref(),
+                Some(expected),
+                "Formatted fields '{:?}'.",
+                fields
+            );
+        }
+    }
+
+    #[test]
+    fn render_internal_references() {
+        let input =
+            "# Intro\n\n# Method\n\n## Related work\n\nSee [#related-work] and [#missing].\n";
+
+        for (lang, label) in [
+            (locale!("en"), "Section 2.1"),
+            (locale!("de"), "Abschnitt 2.1"),
+        ] {
+            let mut config = Config::default();
+            config.preamble.i18n.lang = Some(lang);
+            let doc = unimarkup_parser::parse_unimarkup(input, config);
+
+            let html = crate::render::render(
+                &doc,
+                unimarkup_commons::config::output::OutputFormatKind::Html,
+                HtmlRenderer::default(),
+            )
+            .unwrap()
+            .to_string();
+
+            assert!(html.contains(&format!("<a href='#related-work'>{}</a>", label)));
+            assert!(html.contains("<a href='#missing'>missing</a>"));
+        }
+    }
+
+    #[test]
+    fn render_numbered_headings() {
+        let input = "# Intro\n\n## Scope\n\n### Details\n\nSee [#details].\n";
+        let mut config = Config::default();
+        config.preamble.numbering.numbering_depth = Some(2);
+        let doc = unimarkup_parser::parse_unimarkup(input, config);
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(html.contains("<h2 id='scope'><span class='heading-number'>1.1</span> Scope</h2>"));
+        assert!(html.contains("<h3 id='details'>Details</h3>"));
+        // Headings deeper than the numbering depth are referenced by their id
+        assert!(html.contains("<a href='#details'>details</a>"));
+    }
+
+    #[test]
+    fn render_table_of_contents() {
+        let input = "{$um.toc}{\"exclude\": [\"appendix\"]}\n\n# Intro\n\n## Scope\n\n# Appendix\n\n## Data\n";
+        let doc = unimarkup_parser::parse_unimarkup(input, Config::default());
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(html.contains(
+            "<nav class='toc'><h1>Contents</h1><ul><li><a href='#intro'>Intro</a><ul><li><a href='#scope'>Scope</a></li></ul></li></ul></nav>"
+        ));
+    }
+
+    #[test]
+    fn render_table_of_contents_from_preamble() {
+        let input = "# Intro\n\n## Scope\n\n# Method\n";
+        let mut config = Config::default();
+        config.preamble.toc.toc = true;
+        config.preamble.toc.toc_depth = Some(1);
+        config.preamble.numbering.numbering_depth = Some(1);
+        let doc = unimarkup_parser::parse_unimarkup(input, config);
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(html.contains(
+            "<body><nav class='toc'><h1>Contents</h1><ul><li><a href='#intro'><span class='heading-number'>1</span> Intro</a></li><li><a href='#method'><span class='heading-number'>2</span> Method</a></li></ul></nav>"
+        ));
+    }
+
+    #[test]
+    fn render_heading_anchors() {
+        let input = "# Intro\n\n# Intro\n";
+        let mut config = Config::default();
+        config.preamble.render.heading_anchors = true;
+        let doc = unimarkup_parser::parse_unimarkup(input, config);
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(html.contains(
+            "<h1 id='intro'>Intro <a class='heading-anchor' href='#intro' aria-hidden='true'>#</a></h1>"
+        ));
+        assert!(html.contains(
+            "<h1 id='intro-1'>Intro <a class='heading-anchor' href='#intro-1' aria-hidden='true'>#</a></h1>"
+        ));
+    }
+
+    #[test]
+    fn render_fragment() {
+        let input = "# Intro\n\n```rust\nlet x = 1;\n```\n";
+        let doc = unimarkup_parser::parse_unimarkup(input, Config::default());
+
+        let fragment = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .fragment();
+
+        assert!(fragment.content.starts_with("<h1 id='intro'>Intro</h1>"));
+        assert!(!fragment.content.contains("<body>"));
+        assert!(!fragment.content.contains("<head>"));
+        assert_eq!(fragment.assets.styles.len(), 1);
+        assert!(fragment.assets.scripts.is_empty());
+        assert_eq!(fragment.to_string(), fragment.content);
+    }
+
+    #[test]
+    fn render_metadata_in_head() {
+        let mut config = Config::default();
+        config.preamble.metadata.title = Some("Report".to_string());
+        config.preamble.metadata.authors = HashSet::from(["Bob".to_string(), "Ann".to_string()]);
+        config.preamble.metadata.description = Some("Yearly report".to_string());
+        let doc = unimarkup_parser::parse_unimarkup("# Intro\n", config);
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(html.contains(
+            "<head><title>Report</title><meta name='author' content='Ann'/><meta name='author' content='Bob'/><meta name='description' content='Yearly report'/>"
+        ));
+    }
+
+    #[test]
+    fn render_with_template() {
+        let template_path = std::env::temp_dir().join("unimarkup_render_with_template.html");
+        std::fs::write(
+            &template_path,
+            "<html lang='{{lang}}'><head>{{head}}</head><body><header>{{title}}</header>{{toc}}<main>{{body}}</main></body></html>",
+        )
+        .unwrap();
+        let mut config = Config::default();
+        config.preamble.metadata.title = Some("Report".to_string());
+        config.preamble.toc.toc = true;
+        config.preamble.render.html_template = Some(template_path);
+        let doc = unimarkup_parser::parse_unimarkup("# Intro\n", config);
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert_eq!(
+            html,
+            "<html lang='en'><head><title>Report</title></head><body><header>Report</header><nav class='toc'><h1>Contents</h1><ul><li><a href='#intro'>Intro</a></li></ul></nav><main><h1 id='intro'>Intro</h1></main></body></html>"
+        );
+    }
+
+    #[test]
+    fn render_theme_and_stylesheets() {
+        let input = "```rust\nlet x = 1;\n```\n";
+        let mut config = Config::default();
+        config.preamble.styling.theme = Some(HtmlTheme::Article);
+        config.preamble.styling.stylesheets = vec![PathBuf::from("corporate.css")];
+        config.preamble.styling.link_stylesheets = true;
+        config.preamble.styling.highlight_theme = Some("InspiredGitHub".to_string());
+        let doc = unimarkup_parser::parse_unimarkup(input, config);
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(html.contains("max-width: 42rem"));
+        assert!(html.contains("<link rel='stylesheet' href='corporate.css'/></head>"));
+        assert!(html.contains(".highlighted_code"));
+        assert!(!html.contains("Solarized"));
+    }
+
+    #[test]
+    fn render_escaped_and_sanitised() {
+        let input = "[click](javascript:alert) and <script>\n";
+        let doc = unimarkup_parser::parse_unimarkup(input, Config::default());
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(html.contains("<a>click</a>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+
+        let mut config = Config::default();
+        config.preamble.render.allow_unsafe = true;
+        let doc = unimarkup_parser::parse_unimarkup(input, config);
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(html.contains("<a href='javascript:alert'>click</a>"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn transform_element_tree() {
+        let input = "# Intro\n\nSome **bold** text.\n\n## Method\n";
+        let doc = unimarkup_parser::parse_unimarkup(input, Config::default());
+
+        let mut html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap();
+
+        assert_eq!(html.body.elements.ids(), vec!["intro", "method"]);
+
+        html.body.elements.visit_mut(&mut |element| {
+            if element.tag == HtmlTag::Strong {
+                element.tag = HtmlTag::Em;
+            }
+        });
+
+        assert!(html.to_string().contains("<p>Some <em>bold</em> text.</p>"));
+    }
+
+    #[test]
+    fn render_pretty_html() {
+        let input = "# Intro\n\nSome **bold** text.\n";
+        let mut config = Config::default();
+        config.preamble.render.heading_anchors = true;
+        config.output.html_format = HtmlFormat::Pretty;
+        let doc = unimarkup_parser::parse_unimarkup(input, config);
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert_eq!(
+            html,
+            "<!DOCTYPE HTML>\n<html lang=\"en\">\n  <head>\n  </head>\n  <body>\n    <h1 id='intro'>Intro <a aria-hidden='true' class='heading-anchor' href='#intro'>#</a></h1>\n    <p>Some <strong>bold</strong> text.</p>\n  </body>\n</html>\n"
+        );
+    }
+
+    #[test]
+    fn render_minified_html() {
+        let input = "```rust\nlet x = 1;\n```\n";
+        let mut config = Config::default();
+        config.output.html_format = HtmlFormat::Minified;
+        let doc = unimarkup_parser::parse_unimarkup(input, config);
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(html.contains("<style>@media (prefers-color-scheme:light){.highlighted_code{"));
+        assert!(!html.contains("/*"));
+    }
+
+    #[test]
+    fn render_bullet_list_keeps_single_head() {
+        let input = "- first\n- second\n";
+        let mut config = Config::default();
+        config.preamble.metadata.authors = HashSet::from(["Ann".to_string()]);
+        let doc = unimarkup_parser::parse_unimarkup(input, config);
+
+        let html = crate::render::render(
+            &doc,
+            unimarkup_commons::config::output::OutputFormatKind::Html,
+            HtmlRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert_eq!(html.matches("<meta name='author'").count(), 1);
+    }
+
+    #[test]
+    fn merge_head_without_duplicates() {
+        let head = || HtmlHead {
+            authors: vec!["Ann".to_string()],
+            styles: vec![Stylesheet::Inline("p{color:red}".to_string())],
+            ..Default::default()
+        };
+        let mut merged = head();
+        let mut other = head();
+        other.authors.push("Bob".to_string());
+
+        merged.merge(other);
+
+        assert_eq!(merged.authors, vec!["Ann".to_string(), "Bob".to_string()]);
+        assert_eq!(merged.styles, head().styles);
+    }
 }
@@ -0,0 +1,87 @@
+//! Contains the [`Stylesheet`] enum used to add themes and configured stylesheets to the [`HtmlHead`](super::HtmlHead).
+
+use std::path::{Path, PathBuf};
+
+use logid::log;
+use unimarkup_commons::config::preamble::HtmlTheme;
+
+use crate::log_id::GeneralWarning;
+
+/// Represents one stylesheet of the HTML output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stylesheet {
+    /// The CSS is written into a `<style>` element.
+    Inline(String),
+    /// The CSS file is linked using its path.
+    Linked(PathBuf),
+}
+
+impl Stylesheet {
+    /// Creates a [`Stylesheet`] for the given CSS file.
+    ///
+    /// Returns `None` if the file could not be read for inlining.
+    pub fn new(path: &Path, inline: bool) -> Option<Self> {
+        if !inline {
+            return Some(Stylesheet::Linked(path.to_path_buf()));
+        }
+
+        match std::fs::read_to_string(path) {
+            Ok(css) => Some(Stylesheet::Inline(css)),
+            Err(err) => {
+                log!(
+                    GeneralWarning::FileRead,
+                    format!("Could not read stylesheet {:?}: {}", path, err)
+                );
+                None
+            }
+        }
+    }
+
+    /// Returns the bundled stylesheet of the given theme.
+    pub fn theme(theme: HtmlTheme) -> Self {
+        let css = match theme {
+            HtmlTheme::Article => include_str!("../../styles/themes/article.css"),
+            HtmlTheme::Report => include_str!("../../styles/themes/report.css"),
+            HtmlTheme::Slides => include_str!("../../styles/themes/slides.css"),
+        };
+
+        Stylesheet::Inline(css.to_string())
+    }
+
+    /// Returns the URL of a linked stylesheet, or `None` for inlined CSS.
+    ///
+    /// If `absolute_links` is `true`, the stylesheet is referenced with an absolute `file://` URL.
+    /// This is needed if the HTML file is loaded from another location (e.g. to render PDFs).
+    pub fn link(&self, absolute_links: bool) -> Option<String> {
+        match self {
+            Stylesheet::Inline(_) => None,
+            Stylesheet::Linked(path) if absolute_links => {
+                let abs_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+                Some(format!("file://{}", abs_path.to_string_lossy()))
+            }
+            Stylesheet::Linked(path) => Some(path.to_string_lossy().replace('\\', "/")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::Stylesheet;
+
+    #[test]
+    fn linked_stylesheet() {
+        let stylesheet = Stylesheet::new(Path::new("styles\\corporate.css"), false).unwrap();
+
+        assert_eq!(
+            stylesheet.link(false),
+            Some("styles/corporate.css".to_string())
+        );
+    }
+
+    #[test]
+    fn unreadable_stylesheet_is_skipped() {
+        assert_eq!(Stylesheet::new(Path::new("does/not/exist.css"), true), None);
+    }
+}
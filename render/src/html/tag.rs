@@ -30,10 +30,81 @@ pub enum HtmlTag {
     Ul,
     Li,
     A,
+    Nav,
     Script,
+    Title,
+    Meta,
+    Link,
+    Div,
+    Section,
+    Article,
+    Header,
+    Footer,
+    Main,
+    Aside,
+    Ol,
+    Table,
+    Caption,
+    Thead,
+    Tbody,
+    Tr,
+    Th,
+    Td,
+    Figure,
+    Figcaption,
+    Img,
+    Hr,
+    Blockquote,
+    Del,
+    Style,
 }
 
 impl HtmlTag {
+    /// Returns `true` if elements with this tag are written on their own line in pretty-printed HTML.
+    pub fn is_block(&self) -> bool {
+        matches!(
+            self,
+            HtmlTag::Html
+                | HtmlTag::Head
+                | HtmlTag::Body
+                | HtmlTag::P
+                | HtmlTag::H1
+                | HtmlTag::H2
+                | HtmlTag::H3
+                | HtmlTag::H4
+                | HtmlTag::H5
+                | HtmlTag::H6
+                | HtmlTag::Pre
+                | HtmlTag::Ul
+                | HtmlTag::Li
+                | HtmlTag::Nav
+                | HtmlTag::Script
+                | HtmlTag::Title
+                | HtmlTag::Meta
+                | HtmlTag::Link
+                | HtmlTag::Div
+                | HtmlTag::Section
+                | HtmlTag::Article
+                | HtmlTag::Header
+                | HtmlTag::Footer
+                | HtmlTag::Main
+                | HtmlTag::Aside
+                | HtmlTag::Ol
+                | HtmlTag::Table
+                | HtmlTag::Caption
+                | HtmlTag::Thead
+                | HtmlTag::Tbody
+                | HtmlTag::Tr
+                | HtmlTag::Th
+                | HtmlTag::Td
+                | HtmlTag::Figure
+                | HtmlTag::Figcaption
+                | HtmlTag::Hr
+                | HtmlTag::Blockquote
+                | HtmlTag::Style
+        )
+    }
+
     pub fn as_str(&self) -> &'static str {
         match *self {
             HtmlTag::PlainContent => "",
@@ -60,7 +131,33 @@ impl HtmlTag {
             HtmlTag::Ul => "ul",
             HtmlTag::Li => "li",
             HtmlTag::A => "a",
+            HtmlTag::Nav => "nav",
             HtmlTag::Script => "script",
+            HtmlTag::Title => "title",
+            HtmlTag::Meta => "meta",
+            HtmlTag::Link => "link",
+            HtmlTag::Div => "div",
+            HtmlTag::Section => "section",
+            HtmlTag::Article => "article",
+            HtmlTag::Header => "header",
+            HtmlTag::Footer => "footer",
+            HtmlTag::Main => "main",
+            HtmlTag::Aside => "aside",
+            HtmlTag::Ol => "ol",
+            HtmlTag::Table => "table",
+            HtmlTag::Caption => "caption",
+            HtmlTag::Thead => "thead",
+            HtmlTag::Tbody => "tbody",
+            HtmlTag::Tr => "tr",
+            HtmlTag::Th => "th",
+            HtmlTag::Td => "td",
+            HtmlTag::Figure => "figure",
+            HtmlTag::Figcaption => "figcaption",
+            HtmlTag::Img => "img",
+            HtmlTag::Hr => "hr",
+            HtmlTag::Blockquote => "blockquote",
+            HtmlTag::Del => "del",
+            HtmlTag::Style => "style",
         }
     }
 }
@@ -0,0 +1,174 @@
+//! Contains the [`HtmlTemplate`] that may replace the default document shell of the [`Html`] output.
+//!
+//! A template is a HTML file with the following placeholders:
+//!
+//! - `{{title}}`: the title set in the preamble metadata
+//! - `{{authors}}`: the authors set in the preamble metadata, separated by `, `
+//! - `{{description}}`: the description set in the preamble metadata
+//! - `{{lang}}`: the language of the rendered document
+//! - `{{head}}`: the content of the default `<head>` element, e.g. meta tags, styles and scripts
+//! - `{{body}}`: the rendered content
+//! - `{{toc}}`: the table of contents of the document.
+//!   If set, no table of contents is generated at the start of the content.
+//! - `{{bibliography}}`: the bibliography of all cited entries.
+//!   If set, the bibliography is not added at the end of the content.
+//!
+//! Placeholders that are not set are replaced with an empty string.
+//! Text in double braces that is no placeholder is kept as is.
+
+use std::path::Path;
+
+use logid::log;
+use unimarkup_commons::lexer::position::Position;
+use unimarkup_parser::elements::atomic::{TableOfContents, TocOptions};
+
+use crate::{
+    log_id::GeneralWarning,
+    render::{Context, Renderer},
+};
+
+use super::{escape::escape_attribute, render::HtmlRenderer, Html};
+
+const TOC_PLACEHOLDER: &str = "{{toc}}";
+const BIBLIOGRAPHY_PLACEHOLDER: &str = "{{bibliography}}";
+
+/// Template for the document shell of the [`Html`] output.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HtmlTemplate {
+    content: String,
+    toc: Option<String>,
+    bibliography: Option<String>,
+}
+
+impl HtmlTemplate {
+    /// Creates a template from the given template content.
+    pub fn new(content: String) -> Self {
+        HtmlTemplate {
+            content,
+            ..Default::default()
+        }
+    }
+
+    /// Loads the template from the given file.
+    /// Returns `None` if the file could not be read.
+    pub fn load(path: &Path) -> Option<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Some(HtmlTemplate::new(content)),
+            Err(err) => {
+                log!(
+                    GeneralWarning::FileRead,
+                    format!("Could not read HTML template {:?}: {}", path, err)
+                );
+                None
+            }
+        }
+    }
+
+    /// Returns `true` if the template places the table of contents.
+    pub fn places_toc(&self) -> bool {
+        self.content.contains(TOC_PLACEHOLDER)
+    }
+
+    /// Returns `true` if the template places the bibliography.
+    pub fn places_bibliography(&self) -> bool {
+        self.content.contains(BIBLIOGRAPHY_PLACEHOLDER)
+    }
+
+    /// Renders the table of contents and bibliography for the placeholders set in this template.
+    pub(crate) fn with_sections(mut self, context: &Context) -> Self {
+        let mut renderer = HtmlRenderer::default();
+
+        if self.places_toc() {
+            let toc = TableOfContents {
+                options: TocOptions::default(),
+                start: Position::default(),
+                end: Position::default(),
+            };
+            self.toc = renderer
+                .render_toc_block(&toc, context)
+                .ok()
+                .map(|html| html.body.elements.to_string());
+        }
+
+        if self.places_bibliography() {
+            self.bibliography = renderer
+                .render_bibliography(context)
+                .ok()
+                .map(|html| html.body.elements.to_string());
+        }
+
+        self
+    }
+
+    /// Returns the template content with all placeholders replaced by the values of the given [`Html`].
+    pub(crate) fn fill(&self, html: &Html) -> String {
+        let mut filled = String::with_capacity(self.content.len());
+        let mut rest = self.content.as_str();
+
+        // Note: Placeholders are replaced in one pass, so inserted content is never treated as placeholder.
+        while let Some(open) = rest.find("{{") {
+            filled.push_str(&rest[..open]);
+            rest = &rest[open..];
+
+            let value = rest.find("}}").and_then(|close| {
+                let value = match rest[2..close].trim() {
+                    // Note: Metadata may be placed in attribute values, e.g. `content='{{description}}'`.
+                    "title" => escape_attribute(html.head.title.as_deref().unwrap_or_default()),
+                    "authors" => escape_attribute(&html.head.authors.join(", ")),
+                    "description" => {
+                        escape_attribute(html.head.description.as_deref().unwrap_or_default())
+                    }
+                    "lang" => escape_attribute(&html.lang),
+                    "head" => html.head_content(),
+                    "body" => html.body_content(),
+                    "toc" => self.toc.clone().unwrap_or_default(),
+                    "bibliography" => self.bibliography.clone().unwrap_or_default(),
+                    _ => return None,
+                };
+                Some((value, close + 2))
+            });
+
+            match value {
+                Some((value, len)) => {
+                    filled.push_str(&value);
+                    rest = &rest[len..];
+                }
+                None => {
+                    filled.push_str("{{");
+                    rest = &rest[2..];
+                }
+            }
+        }
+        filled.push_str(rest);
+
+        filled
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HtmlTemplate;
+    use crate::html::{Html, HtmlHead};
+
+    #[test]
+    fn fill_placeholders() {
+        let template = HtmlTemplate::new(
+            "<html lang='{{lang}}'><title>{{ title }}</title><p>{{authors}}</p>{{body}}{{unknown}}</html>"
+                .to_string(),
+        );
+        let html = Html {
+            head: HtmlHead {
+                title: Some("Report".to_string()),
+                authors: vec!["Ann".to_string(), "Bob".to_string()],
+                ..Default::default()
+            },
+            lang: "en".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            template.fill(&html),
+            "<html lang='en'><title>Report</title><p>Ann, Bob</p>{{unknown}}</html>"
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Contains escaping of text, URLs and labels for the LaTeX output.
+
+/// Escapes the given text, so it is typeset as is.
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '<' => escaped.push_str("\\textless{}"),
+            '>' => escaped.push_str("\\textgreater{}"),
+            '|' => escaped.push_str("\\textbar{}"),
+            '{' | '}' | '$' | '&' | '#' | '%' | '_' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Escapes the given URL for the first argument of `\href`.
+///
+/// **Note:** `hyperref` reads the URL verbatim, so only characters that end or comment out the argument are escaped.
+pub fn escape_url(url: &str) -> String {
+    let mut escaped = String::with_capacity(url.len());
+
+    for c in url.chars() {
+        if matches!(c, '#' | '%' | '{' | '}' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(
+            escape_text("50% of $x_1 & {y} ~ \\z"),
+            "50\\% of \\$x\\_1 \\& \\{y\\} \\textasciitilde{} \\textbackslash{}z"
+        );
+    }
+
+    #[test]
+    fn escape_url_comments() {
+        assert_eq!(
+            escape_url("https://unimarkup.org/a%20b#intro"),
+            "https://unimarkup.org/a\\%20b\\#intro"
+        );
+    }
+}
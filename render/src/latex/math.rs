@@ -0,0 +1,407 @@
+//! Contains the conversion of AsciiMath, the syntax of Unimarkup math, into LaTeX math.
+//!
+//! Symbols are matched greedily like in AsciiMath, e.g. `sinx` is converted to `\sin x`.
+//! Brackets around the operands of fractions, scripts and functions like `sqrt` are removed,
+//! e.g. `(a+b)/2` is converted to `\frac{a+b}{2}`.
+
+use super::escape::escape_text;
+
+/// AsciiMath symbols with their LaTeX counterparts.
+const SYMBOLS: &[(&str, &str)] = &[
+    // operators
+    ("+-", "\\pm"),
+    ("-+", "\\mp"),
+    ("***", "\\star"),
+    ("**", "\\ast"),
+    ("*", "\\cdot"),
+    ("xx", "\\times"),
+    ("-:", "\\div"),
+    ("o+", "\\oplus"),
+    ("ox", "\\otimes"),
+    ("sum", "\\sum"),
+    ("prod", "\\prod"),
+    ("^^", "\\wedge"),
+    ("vv", "\\vee"),
+    ("nn", "\\cap"),
+    ("uu", "\\cup"),
+    // relations
+    ("!=", "\\neq"),
+    ("<=>", "\\Leftrightarrow"),
+    ("<=", "\\leq"),
+    (">=", "\\geq"),
+    ("-<", "\\prec"),
+    (">-", "\\succ"),
+    ("!in", "\\notin"),
+    ("in", "\\in"),
+    ("sube", "\\subseteq"),
+    ("supe", "\\supseteq"),
+    ("sub", "\\subset"),
+    ("sup", "\\supset"),
+    ("-=", "\\equiv"),
+    ("~=", "\\cong"),
+    ("~~", "\\approx"),
+    ("prop", "\\propto"),
+    // logic and arrows
+    ("=>", "\\Rightarrow"),
+    ("|->", "\\mapsto"),
+    ("->", "\\to"),
+    ("<-", "\\leftarrow"),
+    ("AA", "\\forall"),
+    ("EE", "\\exists"),
+    ("not", "\\neg"),
+    ("and", "\\text{ and }"),
+    ("or", "\\text{ or }"),
+    // miscellaneous
+    ("oo", "\\infty"),
+    ("del", "\\partial"),
+    ("grad", "\\nabla"),
+    ("oint", "\\oint"),
+    ("int", "\\int"),
+    ("O/", "\\emptyset"),
+    ("...", "\\ldots"),
+    ("cdots", "\\cdots"),
+    ("RR", "\\mathbb{R}"),
+    ("NN", "\\mathbb{N}"),
+    ("ZZ", "\\mathbb{Z}"),
+    ("QQ", "\\mathbb{Q}"),
+    ("CC", "\\mathbb{C}"),
+    ("~", "\\sim"),
+    ("\\", "\\backslash"),
+    ("#", "\\#"),
+    ("%", "\\%"),
+    ("&", "\\&"),
+    ("$", "\\$"),
+    // functions
+    ("lim", "\\lim"),
+    ("sin", "\\sin"),
+    ("cos", "\\cos"),
+    ("tan", "\\tan"),
+    ("log", "\\log"),
+    ("ln", "\\ln"),
+    ("exp", "\\exp"),
+    ("min", "\\min"),
+    ("max", "\\max"),
+    ("det", "\\det"),
+    // greek letters
+    ("alpha", "\\alpha"),
+    ("beta", "\\beta"),
+    ("gamma", "\\gamma"),
+    ("Gamma", "\\Gamma"),
+    ("delta", "\\delta"),
+    ("Delta", "\\Delta"),
+    ("epsilon", "\\epsilon"),
+    ("zeta", "\\zeta"),
+    ("eta", "\\eta"),
+    ("theta", "\\theta"),
+    ("Theta", "\\Theta"),
+    ("iota", "\\iota"),
+    ("kappa", "\\kappa"),
+    ("lambda", "\\lambda"),
+    ("Lambda", "\\Lambda"),
+    ("mu", "\\mu"),
+    ("nu", "\\nu"),
+    ("xi", "\\xi"),
+    ("Xi", "\\Xi"),
+    ("pi", "\\pi"),
+    ("Pi", "\\Pi"),
+    ("rho", "\\rho"),
+    ("sigma", "\\sigma"),
+    ("Sigma", "\\Sigma"),
+    ("tau", "\\tau"),
+    ("upsilon", "\\upsilon"),
+    ("phi", "\\phi"),
+    ("Phi", "\\Phi"),
+    ("chi", "\\chi"),
+    ("psi", "\\psi"),
+    ("Psi", "\\Psi"),
+    ("omega", "\\omega"),
+    ("Omega", "\\Omega"),
+];
+
+/// AsciiMath functions with one operand and their LaTeX commands.
+const UNARY: &[(&str, &str)] = &[
+    ("sqrt", "\\sqrt"),
+    ("hat", "\\hat"),
+    ("bar", "\\overline"),
+    ("vec", "\\vec"),
+    ("ddot", "\\ddot"),
+    ("dot", "\\dot"),
+    ("tilde", "\\tilde"),
+    ("ul", "\\underline"),
+    ("bb", "\\mathbf"),
+    ("cc", "\\mathcal"),
+    ("text", "\\text"),
+    ("abs", "|"),
+    ("floor", "\\lfloor"),
+    ("ceil", "\\lceil"),
+];
+
+/// AsciiMath functions with two operands.
+const BINARY: &[&str] = &["frac", "root"];
+
+/// Opening brackets with their LaTeX counterparts. `{:` is an invisible bracket.
+const OPEN: &[(&str, &str)] = &[
+    ("(:", "\\langle"),
+    ("{:", ""),
+    ("(", "("),
+    ("[", "["),
+    ("{", "\\{"),
+];
+
+/// Closing brackets with their LaTeX counterparts. `:}` is an invisible bracket.
+const CLOSE: &[(&str, &str)] = &[
+    (":)", "\\rangle"),
+    (":}", ""),
+    (")", ")"),
+    ("]", "]"),
+    ("}", "\\}"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// Symbol, number or variable that is converted as is.
+    Atom(String),
+    Unary(&'static str),
+    Binary(&'static str),
+    Open(&'static str),
+    Close(&'static str),
+    Fraction,
+    Subscript,
+    Superscript,
+}
+
+/// Converts the given AsciiMath into LaTeX math that may be placed between `$` signs.
+pub fn asciimath_to_latex(asciimath: &str) -> String {
+    let mut parser = Parser {
+        tokens: tokenize(asciimath),
+        pos: 0,
+    };
+
+    let mut latex = String::new();
+    while parser.pos < parser.tokens.len() {
+        let part = parser.expression();
+        push_part(&mut latex, &part);
+
+        // Closing brackets without opening bracket are kept.
+        if let Some(Token::Close(close)) = parser.next() {
+            push_part(&mut latex, close);
+        }
+    }
+
+    latex
+}
+
+fn tokenize(asciimath: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = asciimath.trim();
+
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = rest.trim_start();
+            continue;
+        }
+
+        let (token, len) = if c == '"' {
+            // Quoted text is typeset as text.
+            let end = rest[1..].find('"').map_or(rest.len(), |end| end + 2);
+            let text = rest[1..end].trim_end_matches('"');
+            (Token::Atom(format!("\\text{{{}}}", escape_text(text))), end)
+        } else if c.is_ascii_digit() {
+            let number: String = rest
+                .chars()
+                .take_while(|c| c.is_ascii_digit() || *c == '.')
+                .collect();
+            let len = number.len();
+            (Token::Atom(number), len)
+        } else if let Some(matched) = longest_match(rest) {
+            matched
+        } else {
+            (Token::Atom(c.to_string()), c.len_utf8())
+        };
+
+        tokens.push(token);
+        rest = &rest[len..];
+    }
+
+    tokens
+}
+
+/// Returns the longest symbol, function, bracket or operator at the start of the given input.
+fn longest_match(input: &str) -> Option<(Token, usize)> {
+    let candidates = SYMBOLS
+        .iter()
+        .map(|(symbol, latex)| (*symbol, Token::Atom(latex.to_string())))
+        .chain(UNARY.iter().map(|(name, _)| (*name, Token::Unary(name))))
+        .chain(BINARY.iter().map(|name| (*name, Token::Binary(name))))
+        .chain(
+            OPEN.iter()
+                .map(|(bracket, latex)| (*bracket, Token::Open(latex))),
+        )
+        .chain(
+            CLOSE
+                .iter()
+                .map(|(bracket, latex)| (*bracket, Token::Close(latex))),
+        )
+        .chain([
+            ("/", Token::Fraction),
+            ("_", Token::Subscript),
+            ("^", Token::Superscript),
+        ]);
+
+    candidates
+        .filter(|(symbol, _)| input.starts_with(symbol))
+        .max_by_key(|(symbol, _)| symbol.len())
+        .map(|(symbol, token)| (token, symbol.len()))
+}
+
+/// Appends the given part, separated by a space if a command would otherwise run into a letter.
+fn push_part(latex: &mut String, part: &str) {
+    let ends_with_command = latex
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+        .ends_with('\\')
+        && latex.ends_with(|c: char| c.is_ascii_alphabetic());
+    if ends_with_command && part.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        latex.push(' ');
+    }
+    latex.push_str(part);
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Converts tokens up to the end or the next closing bracket, which is not consumed.
+    fn expression(&mut self) -> String {
+        let mut latex = String::new();
+
+        while let Some(token) = self.peek() {
+            if matches!(token, Token::Close(_)) {
+                break;
+            }
+
+            let (full, operand) = self.intermediate();
+            if self.peek() == Some(&Token::Fraction) {
+                self.pos += 1;
+                let (_, denominator) = self.intermediate();
+                push_part(
+                    &mut latex,
+                    &format!("\\frac{{{}}}{{{}}}", operand, denominator),
+                );
+            } else {
+                push_part(&mut latex, &full);
+            }
+        }
+
+        latex
+    }
+
+    /// Converts a simple expression with optional sub- and superscript.
+    /// Returns the converted expression, and the expression to use as operand.
+    fn intermediate(&mut self) -> (String, String) {
+        let (mut full, operand) = self.simple();
+        let mut scripted = false;
+
+        for (script, marker) in [(Token::Subscript, "_"), (Token::Superscript, "^")] {
+            if self.peek() == Some(&script) {
+                self.pos += 1;
+                let (_, script_operand) = self.simple();
+                full.push_str(&format!("{}{{{}}}", marker, script_operand));
+                scripted = true;
+            }
+        }
+
+        if scripted {
+            (full.clone(), full)
+        } else {
+            (full, operand)
+        }
+    }
+
+    /// Converts a symbol, a bracketed expression or a function with its operands.
+    /// Returns the converted expression, and the expression to use as operand without brackets.
+    fn simple(&mut self) -> (String, String) {
+        match self.next() {
+            Some(Token::Atom(atom)) => (atom.clone(), atom),
+            Some(Token::Open(open)) => {
+                let inner = self.expression();
+                let close = match self.peek() {
+                    Some(Token::Close(close)) => {
+                        let close = *close;
+                        self.pos += 1;
+                        close
+                    }
+                    _ => "",
+                };
+                (format!("{}{}{}", open, inner, close), inner)
+            }
+            Some(Token::Unary(name)) => {
+                let (_, operand) = self.simple();
+                let latex = match name {
+                    "abs" => format!("\\left|{}\\right|", operand),
+                    "floor" => format!("\\lfloor {}\\rfloor", operand),
+                    "ceil" => format!("\\lceil {}\\rceil", operand),
+                    _ => {
+                        let command = UNARY
+                            .iter()
+                            .find(|(unary, _)| *unary == name)
+                            .map_or("", |(_, command)| *command);
+                        format!("{}{{{}}}", command, operand)
+                    }
+                };
+                (latex.clone(), latex)
+            }
+            Some(Token::Binary(name)) => {
+                let (_, first) = self.simple();
+                let (_, second) = self.simple();
+                let latex = match name {
+                    "root" => format!("\\sqrt[{}]{{{}}}", first, second),
+                    _ => format!("\\frac{{{}}}{{{}}}", first, second),
+                };
+                (latex.clone(), latex)
+            }
+            // Operators without operand are escaped.
+            Some(Token::Fraction) => ("/".to_string(), "/".to_string()),
+            Some(Token::Subscript) => ("\\_".to_string(), "\\_".to_string()),
+            Some(Token::Superscript) => ("\\hat{}".to_string(), "\\hat{}".to_string()),
+            Some(Token::Close(close)) => (close.to_string(), close.to_string()),
+            None => (String::new(), String::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::asciimath_to_latex;
+
+    #[test]
+    fn convert_sum_and_fraction() {
+        assert_eq!(
+            asciimath_to_latex("sum_(i=1)^n i^2 = (n(n+1)(2n+1))/6"),
+            "\\sum_{i=1}^{n}i^{2}=\\frac{n(n+1)(2n+1)}{6}"
+        );
+    }
+
+    #[test]
+    fn convert_symbols_and_functions() {
+        assert_eq!(
+            asciimath_to_latex("sqrt(x^2+y^2) <= alpha xx oo"),
+            "\\sqrt{x^{2}+y^{2}}\\leq\\alpha\\times\\infty"
+        );
+        assert_eq!(
+            asciimath_to_latex("sinx \"if\" x in RR"),
+            "\\sin x\\text{if}x\\in\\mathbb{R}"
+        );
+    }
+}
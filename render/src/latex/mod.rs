@@ -0,0 +1,165 @@
+//! Defines the [`Latex`] struct that is returned when rendering Unimarkup to LaTeX.
+//!
+//! The output is a standalone document that compiles with `pdflatex` and `bibtex`.
+//! The references of the document are embedded as BibTeX database in a `filecontents*` environment,
+//! so no separate `.bib` file is needed to compile the output.
+//!
+//! The default preamble loads the packages the output needs:
+//! `fontenc`, `inputenc`, `babel` for supported languages, `amsmath`, `amssymb`, `ulem`, `xcolor`,
+//! `listings` or `minted` depending on the preamble option `latex_listings`, and `hyperref`.
+//! Commands of the preamble option `latex_preamble` are added after these packages.
+//!
+//! The bibliography is typeset by BibTeX with the `plain` style.
+//! The CSL style of the preamble and the filters of `{$um.bibliography}` elements are therefore ignored,
+//! and a [`LatexFallback`](crate::log_id::GeneralWarning::LatexFallback) warning is logged if they are set.
+
+use std::path::Path;
+
+use logid::log;
+use unimarkup_commons::config::preamble::LatexListings;
+
+use crate::{
+    csl_json::bibtex,
+    log_id::GeneralWarning,
+    render::{Context, OutputFormat},
+};
+
+use self::escape::escape_text;
+
+pub mod escape;
+pub mod math;
+pub mod render;
+
+/// Document class that is used if no class is set in the preamble.
+const DEFAULT_CLASS: &str = "article";
+
+/// Rendered LaTeX document.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Latex {
+    /// Document class, e.g. `article`.
+    pub document_class: String,
+    /// Commands between `\documentclass` and `\begin{document}`.
+    pub preamble: String,
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    /// Rendered content between `\begin{document}` and `\end{document}`.
+    pub body: String,
+    /// BibTeX database of the references, if the document contains citations.
+    /// It is embedded in the output, so it is only needed to use the database separately.
+    pub bibliography: Option<String>,
+}
+
+impl Latex {
+    /// Creates LaTeX with the given content as body.
+    pub fn with_body(body: impl Into<String>) -> Self {
+        Latex {
+            body: body.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl OutputFormat for Latex {
+    fn new(context: &Context) -> Self {
+        let config = &context.get_config().preamble;
+        let mut authors: Vec<_> = config.metadata.authors.iter().cloned().collect();
+        // Note: Authors are stored in a `HashSet`, so they are sorted to get a stable output.
+        authors.sort();
+
+        Latex {
+            document_class: config
+                .latex
+                .latex_class
+                .clone()
+                .unwrap_or(DEFAULT_CLASS.to_string()),
+            preamble: preamble(context),
+            title: config.metadata.title.clone(),
+            authors,
+            body: String::new(),
+            bibliography: (!context.doc.citations.is_empty())
+                .then(|| bibtex::export(&context.csl_data.items)),
+        }
+    }
+
+    fn append(&mut self, other: Self) -> Result<(), crate::log_id::RenderError> {
+        self.body.push_str(&other.body);
+        Ok(())
+    }
+}
+
+/// Returns the default preamble followed by the commands of the configured preamble file.
+fn preamble(context: &Context) -> String {
+    let config = &context.get_config().preamble.latex;
+    let mut preamble = String::from("\\usepackage[T1]{fontenc}\n\\usepackage[utf8]{inputenc}\n");
+
+    let babel = match context.get_lang().id.language.as_str() {
+        "en" => Some("english"),
+        "de" => Some("ngerman"),
+        "fr" => Some("french"),
+        "es" => Some("spanish"),
+        _ => None,
+    };
+    if let Some(language) = babel {
+        preamble.push_str(&format!("\\usepackage[{}]{{babel}}\n", language));
+    }
+
+    preamble.push_str("\\usepackage{amsmath}\n\\usepackage{amssymb}\n");
+    preamble.push_str("\\usepackage[normalem]{ulem}\n\\usepackage{xcolor}\n");
+    match config.latex_listings.unwrap_or_default() {
+        LatexListings::Listings => preamble.push_str(
+            "\\usepackage{listings}\n\\lstset{basicstyle=\\ttfamily\\small, breaklines=true, columns=fullflexible}\n",
+        ),
+        LatexListings::Minted => preamble.push_str("\\usepackage{minted}\n"),
+    }
+    // Note: `hyperref` must be loaded after other packages, because it redefines their commands.
+    preamble.push_str("\\usepackage{hyperref}\n");
+
+    if let Some(commands) = config.latex_preamble.as_deref().and_then(load_preamble) {
+        preamble.push_str(commands.trim_end());
+        preamble.push('\n');
+    }
+
+    preamble
+}
+
+/// Loads the commands of the given preamble file.
+/// Returns `None` if the file could not be read.
+fn load_preamble(path: &Path) -> Option<String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(err) => {
+            log!(
+                GeneralWarning::FileRead,
+                format!("Could not read LaTeX preamble {:?}: {}", path, err)
+            );
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for Latex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(bibliography) = &self.bibliography {
+            writeln!(f, "\\begin{{filecontents*}}[overwrite]{{\\jobname.bib}}")?;
+            write!(f, "{}", bibliography)?;
+            writeln!(f, "\\end{{filecontents*}}")?;
+        }
+
+        writeln!(f, "\\documentclass{{{}}}", self.document_class)?;
+        write!(f, "{}", self.preamble)?;
+
+        if let Some(title) = &self.title {
+            writeln!(f, "\\title{{{}}}", escape_text(title))?;
+            let authors: Vec<_> = self.authors.iter().map(|a| escape_text(a)).collect();
+            writeln!(f, "\\author{{{}}}", authors.join(" \\and "))?;
+            writeln!(f, "\\date{{}}")?;
+        }
+
+        writeln!(f, "\n\\begin{{document}}\n")?;
+        if self.title.is_some() {
+            writeln!(f, "\\maketitle\n")?;
+        }
+        writeln!(f, "{}", self.body.trim())?;
+        writeln!(f, "\n\\end{{document}}")
+    }
+}
@@ -0,0 +1,654 @@
+use crate::log_id::{GeneralWarning, RenderError};
+use logid::log;
+use unimarkup_commons::config::preamble::{element_kind_info, LatexListings};
+use unimarkup_commons::lexer::span::Span;
+use unimarkup_inline::element::substitution::DistinctReference;
+use unimarkup_inline::element::{
+    base::{EscapedNewline, EscapedPlain, EscapedWhitespace, Newline, Plain},
+    formatting::{
+        Bold, Highlight, Italic, Math, Overline, Quote, Strikethrough, Subscript, Superscript,
+        Underline, Verbatim,
+    },
+    textbox::{citation::Citation, hyperlink::Hyperlink, reference::InternalReference, TextBox},
+    Inline, InlineElement,
+};
+use unimarkup_parser::{
+    document::OutlineEntry,
+    elements::{
+        atomic::{Bibliography, Heading, HeadingLevel, Paragraph, TableOfContents},
+        enclosed::VerbatimBlock,
+        indents::{BulletList, BulletListEntry},
+    },
+};
+
+use crate::html::escape::is_safe_url;
+use crate::render::{Context, OutputFormat, Renderer};
+
+use super::{
+    escape::{escape_text, escape_url},
+    math::asciimath_to_latex,
+    Latex,
+};
+
+/// Sectioning commands of the heading levels in document classes without chapters.
+const SECTIONS: [&str; 6] = [
+    "section",
+    "subsection",
+    "subsubsection",
+    "paragraph",
+    "subparagraph",
+    "subparagraph",
+];
+
+/// Sectioning commands of the heading levels in document classes with chapters.
+const CHAPTER_SECTIONS: [&str; 6] = [
+    "chapter",
+    "section",
+    "subsection",
+    "subsubsection",
+    "paragraph",
+    "subparagraph",
+];
+
+/// Document classes whose top-level sectioning command is `\chapter`.
+const CHAPTER_CLASSES: [&str; 5] = ["report", "book", "memoir", "scrreprt", "scrbook"];
+
+/// Languages of verbatim blocks with their names in the `listings` package.
+const LISTINGS_LANGUAGES: &[(&str, &str)] = &[
+    ("c", "C"),
+    ("cpp", "C++"),
+    ("c++", "C++"),
+    ("java", "Java"),
+    ("python", "Python"),
+    ("bash", "bash"),
+    ("sh", "sh"),
+    ("shell", "sh"),
+    ("html", "HTML"),
+    ("xml", "XML"),
+    ("sql", "SQL"),
+    ("ruby", "Ruby"),
+    ("perl", "Perl"),
+    ("php", "PHP"),
+    ("haskell", "Haskell"),
+    ("matlab", "Matlab"),
+    ("r", "R"),
+    ("latex", "[LaTeX]TeX"),
+    ("tex", "TeX"),
+];
+
+#[derive(Debug, Default)]
+pub struct LatexRenderer {
+    /// `true` once the bibliography is placed, because BibTeX allows only one bibliography per document.
+    bibliography_rendered: bool,
+}
+
+impl LatexRenderer {
+    /// Renders the given content of the heading, prefixed with the heading number if it has one.
+    fn render_numbered(
+        &mut self,
+        heading: &Heading,
+        content: &[Inline],
+        context: &Context,
+    ) -> Result<String, RenderError> {
+        let mut latex = String::new();
+        if let Some(number) = context.heading_number(heading) {
+            latex.push_str(&escape_text(number));
+            latex.push(' ');
+        }
+        latex.push_str(self.render_inlines(content, context)?.body.trim());
+
+        Ok(latex)
+    }
+
+    /// Renders the given outline entries as nested lists linking to their headings.
+    fn render_outline(
+        &mut self,
+        outline: &[OutlineEntry],
+        context: &Context,
+    ) -> Result<String, RenderError> {
+        let mut list = String::from("\\begin{itemize}\n");
+        for entry in outline {
+            // Citations are only rendered at the heading, to not cite entries in the table of contents.
+            let content: Vec<Inline> = entry
+                .heading
+                .content
+                .iter()
+                .filter(|inline| !matches!(inline, Inline::Citation(_)))
+                .cloned()
+                .collect();
+            let text = self.render_numbered(entry.heading, &content, context)?;
+            list.push_str(&format!(
+                "\\item \\hyperref[{}]{{{}}}\n",
+                entry.heading.id, text
+            ));
+
+            if !entry.children.is_empty() {
+                list.push_str(&self.render_outline(&entry.children, context)?);
+            }
+        }
+        list.push_str("\\end{itemize}\n");
+
+        Ok(list)
+    }
+
+    /// Renders the BibTeX bibliography with the given title, if it was not rendered before.
+    fn render_bibtex_bibliography(&mut self, title: Option<&str>, context: &Context) -> Latex {
+        if self.bibliography_rendered {
+            return Latex::default();
+        }
+        self.bibliography_rendered = true;
+
+        if let Some(style) = &context.get_config().preamble.cite.style {
+            log_ignored_option(&format!("citation style {:?}", style));
+        }
+
+        // Note: Classes name the bibliography either `\refname` or `\bibname`, so both are set.
+        let title = escape_text(title.unwrap_or_default());
+        Latex::with_body(format!(
+            "\\def\\refname{{{0}}}\n\\def\\bibname{{{0}}}\n\\bibliographystyle{{plain}}\n\\bibliography{{\\jobname}}\n\n",
+            title
+        ))
+    }
+}
+
+/// Logs that the given bibliography option is ignored, because BibTeX typesets the bibliography.
+fn log_ignored_option(option: &str) {
+    log!(
+        GeneralWarning::LatexFallback,
+        format!(
+            "The {} is ignored, because the LaTeX bibliography is typeset by BibTeX.",
+            option
+        ),
+        add: element_kind_info("Bibliography")
+    );
+}
+
+/// Returns the starred sectioning command for the given heading level.
+///
+/// **Note:** Starred commands are used, so headings keep the numbers of the numbering options
+/// that are also used by internal references.
+fn section(level: HeadingLevel, context: &Context) -> String {
+    let class = context.get_config().preamble.latex.latex_class.as_deref();
+    let sections = match class {
+        Some(class) if CHAPTER_CLASSES.contains(&class) => CHAPTER_SECTIONS,
+        _ => SECTIONS,
+    };
+
+    format!(
+        "\\{}*",
+        sections[usize::from(u8::from(level)).clamp(1, 6) - 1]
+    )
+}
+
+impl Renderer<Latex> for LatexRenderer {
+    fn get_target(&mut self) -> Result<Latex, RenderError> {
+        Ok(Latex::default())
+    }
+
+    fn render_paragraph(
+        &mut self,
+        paragraph: &Paragraph,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let inner = self.render_inlines(&paragraph.content, context)?;
+
+        Ok(Latex::with_body(format!("{}\n\n", inner.body.trim())))
+    }
+
+    fn render_heading(
+        &mut self,
+        heading: &Heading,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let content = self.render_numbered(heading, &heading.content, context)?;
+
+        // Note: `\phantomsection` sets the anchor for `\hyperref`, because starred commands set none.
+        Ok(Latex::with_body(format!(
+            "{}{{{}}}\n\\phantomsection\\label{{{}}}\n\n",
+            section(heading.level, context),
+            content,
+            heading.id
+        )))
+    }
+
+    fn render_verbatim_block(
+        &mut self,
+        verbatim: &VerbatimBlock,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let content = verbatim.content.trim_end_matches('\n');
+        let lang = verbatim.data_lang.as_deref().map(str::to_lowercase);
+
+        let latex = match context
+            .get_config()
+            .preamble
+            .latex
+            .latex_listings
+            .unwrap_or_default()
+        {
+            LatexListings::Listings => {
+                let language = lang.and_then(|lang| {
+                    LISTINGS_LANGUAGES
+                        .iter()
+                        .find(|(name, _)| *name == lang)
+                        .map(|(_, language)| format!("[language={}]", language))
+                });
+                format!(
+                    "\\begin{{lstlisting}}{}\n{}\n\\end{{lstlisting}}\n\n",
+                    language.unwrap_or_default(),
+                    content
+                )
+            }
+            LatexListings::Minted => format!(
+                "\\begin{{minted}}{{{}}}\n{}\n\\end{{minted}}\n\n",
+                lang.unwrap_or("text".to_string()),
+                content
+            ),
+        };
+
+        Ok(Latex::with_body(latex))
+    }
+
+    fn render_bullet_list(
+        &mut self,
+        bullet_list: &BulletList,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let mut entries = Latex::with_body("\\begin{itemize}\n");
+
+        for entry in &bullet_list.entries {
+            entries.append(self.render_bullet_list_entry(entry, context)?)?;
+        }
+        entries.append(Latex::with_body("\\end{itemize}\n\n"))?;
+
+        Ok(entries)
+    }
+
+    fn render_bullet_list_entry(
+        &mut self,
+        bullet_list_entry: &BulletListEntry,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let heading = self.render_inlines(&bullet_list_entry.heading, context)?;
+        let mut entry = Latex::with_body(format!("\\item {}\n", heading.body.trim()));
+
+        if !bullet_list_entry.body.is_empty() {
+            let body = self.render_blocks(&bullet_list_entry.body, context)?;
+            entry.append(Latex::with_body(format!("\n{}\n", body.body.trim())))?;
+        }
+
+        Ok(entry)
+    }
+
+    fn render_blankline(
+        &mut self,
+        _blankline: &Span,
+        _context: &Context,
+    ) -> Result<Latex, RenderError> {
+        // Blocks are already separated by blank lines.
+        Ok(Latex::default())
+    }
+
+    fn render_bibliography_block(
+        &mut self,
+        bibliography: &Bibliography,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let options = &bibliography.options;
+        if options.section || options.after.is_some() || !options.types.is_empty() {
+            log_ignored_option("filter of the bibliography element");
+        }
+        if context.placed_bibliography(bibliography).is_none() {
+            return Ok(Latex::default());
+        }
+
+        let title = context.bibliography_title(bibliography);
+        Ok(self.render_bibtex_bibliography(title.as_deref(), context))
+    }
+
+    fn render_toc_block(
+        &mut self,
+        toc: &TableOfContents,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let outline = context.doc.outline(&toc.options);
+        if outline.is_empty() {
+            return Ok(Latex::default());
+        }
+
+        let mut latex = Latex::default();
+        if let Some(title) = context.toc_title(toc) {
+            latex.append(Latex::with_body(format!(
+                "{}{{{}}}\n\n",
+                section(context.toc_title_level(toc), context),
+                escape_text(&title)
+            )))?;
+        }
+        latex.append(Latex::with_body(self.render_outline(&outline, context)?))?;
+        latex.append(Latex::with_body("\n"))?;
+
+        Ok(latex)
+    }
+
+    fn render_textbox(
+        &mut self,
+        textbox: &TextBox,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        self.render_nested_inline(textbox.inner(), context)
+    }
+
+    fn render_hyperlink(
+        &mut self,
+        hyperlink: &Hyperlink,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let inner = self.render_nested_inline(hyperlink.inner(), context)?;
+
+        if !context.get_config().preamble.render.allow_unsafe && !is_safe_url(hyperlink.link()) {
+            // Note: The link text is kept, but the unsafe target is never written into the output.
+            log!(
+                GeneralWarning::UnsafeUrl,
+                format!(
+                    "Removed unsafe link target '{}'. Set `allow_unsafe` to keep it.",
+                    hyperlink.link()
+                ),
+                add: element_kind_info("Hyperlink")
+            );
+            return Ok(inner);
+        }
+
+        let url = escape_url(hyperlink.link());
+        let latex = if inner.body.trim().is_empty() {
+            format!("\\url{{{}}}", url)
+        } else {
+            format!("\\href{{{}}}{{{}}}", url, inner.body.trim())
+        };
+
+        Ok(Latex::with_body(latex))
+    }
+
+    fn render_citation(
+        &mut self,
+        citation: &Citation,
+        _context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let ids: Vec<&str> = citation.entries().iter().map(|entry| entry.id()).collect();
+
+        // Note: `\cite` has only one note after the cites, so locators and suffixes are kept for single cites.
+        let note = match citation.entries().as_slice() {
+            [entry] => {
+                let locator = entry.locator().map(|locator| match locator.label() {
+                    "page" => format!("p.~{}", escape_text(locator.value())),
+                    label => format!("{}~{}", label, escape_text(locator.value())),
+                });
+                let parts: Vec<String> = locator
+                    .into_iter()
+                    .chain(entry.suffix().map(escape_text))
+                    .collect();
+                (!parts.is_empty()).then(|| format!("[{}]", parts.join(", ")))
+            }
+            _ => None,
+        };
+
+        Ok(Latex::with_body(format!(
+            "\\cite{}{{{}}}",
+            note.unwrap_or_default(),
+            ids.join(",")
+        )))
+    }
+
+    fn render_distinct_reference(
+        &mut self,
+        distinct_reference: &DistinctReference,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let content = context
+            .distinct_reference(distinct_reference)
+            .map(|content| escape_text(&content))
+            .unwrap_or("########### CITATION ERROR ###########".to_string());
+
+        Ok(Latex::with_body(content))
+    }
+
+    fn render_internal_reference(
+        &mut self,
+        internal_reference: &InternalReference,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        // Dead references are reported when creating the context, so the id is shown instead.
+        let content = context
+            .internal_reference_label(internal_reference)
+            .unwrap_or(internal_reference.target().to_string());
+
+        Ok(Latex::with_body(format!(
+            "\\hyperref[{}]{{{}}}",
+            internal_reference.target(),
+            escape_text(&content)
+        )))
+    }
+
+    fn render_bibliography(&mut self, context: &Context) -> Result<Latex, RenderError> {
+        if context.doc.citations.is_empty() {
+            return Ok(Latex::default());
+        }
+
+        Ok(self.render_bibtex_bibliography(Some(context.localized_bibliography_title()), context))
+    }
+
+    fn render_footnotes(&mut self, _context: &Context) -> Result<Latex, RenderError> {
+        // Citations are rendered with `\cite`, so the citation processor creates no footnotes.
+        Ok(Latex::default())
+    }
+
+    fn render_bold(&mut self, bold: &Bold, context: &Context) -> Result<Latex, RenderError> {
+        let inner = self.render_nested_inline(bold.inner(), context)?;
+
+        Ok(Latex::with_body(format!("\\textbf{{{}}}", inner.body)))
+    }
+
+    fn render_italic(&mut self, italic: &Italic, context: &Context) -> Result<Latex, RenderError> {
+        let inner = self.render_nested_inline(italic.inner(), context)?;
+
+        Ok(Latex::with_body(format!("\\emph{{{}}}", inner.body)))
+    }
+
+    fn render_underline(
+        &mut self,
+        underline: &Underline,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let inner = self.render_nested_inline(underline.inner(), context)?;
+
+        Ok(Latex::with_body(format!("\\uline{{{}}}", inner.body)))
+    }
+
+    fn render_subscript(
+        &mut self,
+        subscript: &Subscript,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let inner = self.render_nested_inline(subscript.inner(), context)?;
+
+        Ok(Latex::with_body(format!(
+            "\\textsubscript{{{}}}",
+            inner.body
+        )))
+    }
+
+    fn render_superscript(
+        &mut self,
+        superscript: &Superscript,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let inner = self.render_nested_inline(superscript.inner(), context)?;
+
+        Ok(Latex::with_body(format!(
+            "\\textsuperscript{{{}}}",
+            inner.body
+        )))
+    }
+
+    fn render_overline(
+        &mut self,
+        overline: &Overline,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let inner = self.render_nested_inline(overline.inner(), context)?;
+
+        Ok(Latex::with_body(format!(
+            "\\ensuremath{{\\overline{{\\text{{{}}}}}}}",
+            inner.body
+        )))
+    }
+
+    fn render_strikethrough(
+        &mut self,
+        strikethrough: &Strikethrough,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let inner = self.render_nested_inline(strikethrough.inner(), context)?;
+
+        Ok(Latex::with_body(format!("\\sout{{{}}}", inner.body)))
+    }
+
+    fn render_highlight(
+        &mut self,
+        highlight: &Highlight,
+        context: &Context,
+    ) -> Result<Latex, RenderError> {
+        let inner = self.render_nested_inline(highlight.inner(), context)?;
+
+        Ok(Latex::with_body(format!(
+            "\\colorbox{{yellow}}{{{}}}",
+            inner.body
+        )))
+    }
+
+    fn render_quote(&mut self, quote: &Quote, context: &Context) -> Result<Latex, RenderError> {
+        let inner = self.render_nested_inline(quote.inner(), context)?;
+
+        Ok(Latex::with_body(format!("``{}''", inner.body)))
+    }
+
+    fn render_inline_verbatim(
+        &mut self,
+        verbatim: &Verbatim,
+        _context: &Context,
+    ) -> Result<Latex, RenderError> {
+        // Note: `\verb` is not allowed in arguments of other commands, so the content is escaped instead.
+        Ok(Latex::with_body(format!(
+            "\\texttt{{{}}}",
+            escape_text(&verbatim.inner().as_unimarkup())
+        )))
+    }
+
+    fn render_inline_math(
+        &mut self,
+        math: &Math,
+        _context: &Context,
+    ) -> Result<Latex, RenderError> {
+        // TODO: resolve logic inlines before parsing math.
+        let content_str: String = math
+            .inner()
+            .iter()
+            .filter_map(|i| match i {
+                Inline::Plain(p) => Some(p.content().clone()),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Latex::with_body(format!(
+            "${}$",
+            asciimath_to_latex(&content_str)
+        )))
+    }
+
+    fn render_plain(&mut self, plain: &Plain, _context: &Context) -> Result<Latex, RenderError> {
+        Ok(Latex::with_body(escape_text(plain.content())))
+    }
+
+    fn render_newline(
+        &mut self,
+        _newline: &Newline,
+        _context: &Context,
+    ) -> Result<Latex, RenderError> {
+        Ok(Latex::with_body("\n"))
+    }
+
+    fn render_implicit_newline(
+        &mut self,
+        _implicit_newline: &Newline,
+        _context: &Context,
+    ) -> Result<Latex, RenderError> {
+        Ok(Latex::with_body("\\newline\n"))
+    }
+
+    fn render_escaped_newline(
+        &mut self,
+        _escaped_newline: &EscapedNewline,
+        _context: &Context,
+    ) -> Result<Latex, RenderError> {
+        Ok(Latex::with_body("\\newline\n"))
+    }
+
+    fn render_escaped_whitespace(
+        &mut self,
+        _escaped_whitespace: &EscapedWhitespace,
+        _context: &Context,
+    ) -> Result<Latex, RenderError> {
+        Ok(Latex::with_body("~"))
+    }
+
+    fn render_escaped_plain(
+        &mut self,
+        escaped_plain: &EscapedPlain,
+        _context: &Context,
+    ) -> Result<Latex, RenderError> {
+        Ok(Latex::with_body(escape_text(escaped_plain.content())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use unimarkup_commons::config::{output::OutputFormatKind, Config};
+
+    use super::*;
+
+    fn render(input: &str, config: Config) -> Latex {
+        let doc = unimarkup_parser::parse_unimarkup(input, config);
+
+        crate::render::render(&doc, OutputFormatKind::Latex, LatexRenderer::default()).unwrap()
+    }
+
+    #[test]
+    fn render_blocks() {
+        let latex = render(
+            "# Intro\n\nSome **bold** and 50% *italic* text.\n\n- first\n- second\n",
+            Config::default(),
+        );
+
+        assert_eq!(
+            latex.body,
+            "\\section*{Intro}\n\\phantomsection\\label{intro}\n\nSome \\textbf{bold} and 50\\% \\emph{italic} text.\n\n\\begin{itemize}\n\\item first\n\\item second\n\\end{itemize}\n\n"
+        );
+    }
+
+    #[test]
+    fn render_standalone_document() {
+        let mut config = Config::default();
+        config.preamble.metadata.title = Some("Paper".to_string());
+        config.preamble.latex.latex_class = Some("report".to_string());
+        config.preamble.latex.latex_listings = Some(LatexListings::Minted);
+
+        let latex = render("# Intro\n\n```rust\nlet x = 1;\n```\n", config).to_string();
+
+        assert!(latex.starts_with("\\documentclass{report}\n"));
+        assert!(latex.contains("\\usepackage{minted}\n"));
+        assert!(latex.contains("\\title{Paper}\n"));
+        assert!(latex.contains("\\begin{document}\n\n\\maketitle\n\n\\chapter*{Intro}"));
+        assert!(latex.contains("\\begin{minted}{rust}\nlet x = 1;\n\\end{minted}"));
+        assert!(latex.ends_with("\\end{document}\n"));
+        assert!(!latex.contains("filecontents"));
+    }
+}
@@ -5,7 +5,10 @@
 
 mod csl_json;
 pub mod html;
+pub mod latex;
 pub mod log_id;
+pub mod markdown;
 pub mod pdf;
+mod references;
 pub mod render;
 pub mod umi;
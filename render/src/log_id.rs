@@ -26,27 +26,51 @@ pub enum GeneralWarning {
 
     /// Log-id denoting a failed json serialization
     JSONSerialization,
+
+    /// Log-id denoting a BibTeX or RIS entry or field that could not be converted to CSL-JSON
+    ReferenceImport,
+
+    /// Log-id denoting a reference that is never cited in the document
+    UnusedReference,
+
+    /// Log-id denoting an internal reference to an element that does not exist
+    DeadInternalReference,
+
+    /// Log-id denoting a link target with a scheme that may execute code, e.g. `javascript:`
+    UnsafeUrl,
+
+    /// Log-id denoting an element without Markdown equivalent that is rendered with a fallback
+    MarkdownFallback,
+
+    /// Log-id denoting an option that is not supported and ignored by the LaTeX output
+    LatexFallback,
 }
 
 #[derive(Debug, Clone, ErrLogId, Error)]
 pub enum CiteError {
-    #[error("Importing the JavaScript module failed.")]
-    ModuleImportError,
-
     #[error("Initialization of the CSL processor failed.")]
     ProcessorInitializationError,
 
     #[error("Processing the citations failed.")]
     CitationError,
 
-    #[error("The check if footnotes exist, failed.")]
-    CheckForFootnotesError,
-
     #[error("Getting the footnotes failed.")]
     GetFootnotesError,
 
     #[error("Getting the bibliography failed.")]
     GetBibliographyError,
+
+    #[error("A cited id is not defined in the references.")]
+    UnknownCitationId,
+
+    #[error("A cited reference has no issued date, so it cannot be rendered.")]
+    UndatedReference,
+
+    #[error("The citation style is neither a bundled style nor a readable file.")]
+    UnknownStyle,
+
+    #[error("The citation style is not a valid CSL style.")]
+    InvalidStyle,
 }
 
 #[derive(Debug, Clone, ErrLogId, Error, PartialEq, Eq)]
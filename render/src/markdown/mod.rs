@@ -0,0 +1,64 @@
+//! Defines the [`Markdown`] struct that is returned when rendering Unimarkup to Markdown.
+//!
+//! The output follows [CommonMark](https://commonmark.org/) with the extensions of
+//! [GitHub Flavored Markdown](https://github.github.com/gfm/), e.g. strikethrough and math.
+//!
+//! Elements without Markdown equivalent are rendered with the following fallbacks:
+//!
+//! - underline, subscript and superscript use the HTML tags `<ins>`, `<sub>` and `<sup>`
+//! - overline and highlight are rendered as their plain content
+//! - escaped whitespace is rendered as `&nbsp;`
+//! - citations, footnotes and the bibliography are rendered as plain text
+//!
+//! Overline and highlight log a [`MarkdownFallback`](crate::log_id::GeneralWarning::MarkdownFallback) warning.
+
+use crate::render::OutputFormat;
+
+pub mod render;
+
+/// Rendered Markdown content.
+///
+/// **Note:** Blocks end with a blank line, so rendered blocks may simply be appended.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Markdown {
+    pub content: String,
+}
+
+impl Markdown {
+    /// Creates Markdown for inline content.
+    pub fn inline(content: impl Into<String>) -> Self {
+        Markdown {
+            content: content.into(),
+        }
+    }
+
+    /// Creates Markdown for block content.
+    /// Trailing whitespace is replaced by one blank line that separates this block from the next one.
+    pub fn block(content: &str) -> Self {
+        Markdown {
+            content: format!("{}\n\n", content.trim_end()),
+        }
+    }
+}
+
+impl OutputFormat for Markdown {
+    fn new(_context: &crate::render::Context) -> Self {
+        Markdown::default()
+    }
+
+    fn append(&mut self, other: Self) -> Result<(), crate::log_id::RenderError> {
+        self.content.push_str(&other.content);
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Markdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let content = self.content.trim_end();
+        if content.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "{}", content)
+    }
+}
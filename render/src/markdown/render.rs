@@ -0,0 +1,717 @@
+use crate::log_id::{GeneralWarning, RenderError};
+use logid::log;
+use unimarkup_commons::config::preamble::element_kind_info;
+use unimarkup_commons::lexer::span::Span;
+use unimarkup_inline::element::substitution::DistinctReference;
+use unimarkup_inline::element::{
+    base::{EscapedNewline, EscapedPlain, EscapedWhitespace, Newline, Plain},
+    formatting::{
+        Bold, Highlight, Italic, Math, Overline, Quote, Strikethrough, Subscript, Superscript,
+        Underline, Verbatim,
+    },
+    textbox::{citation::Citation, hyperlink::Hyperlink, reference::InternalReference, TextBox},
+    Inline, InlineElement,
+};
+use unimarkup_parser::{
+    document::OutlineEntry,
+    elements::{
+        atomic::{Bibliography, Heading, Paragraph, TableOfContents},
+        enclosed::VerbatimBlock,
+        indents::{BulletList, BulletListEntry},
+    },
+};
+
+use crate::html::escape::is_safe_url;
+use crate::render::{Context, OutputFormat, Renderer};
+
+use super::Markdown;
+
+/// Indentation of content nested in list entries.
+const LIST_INDENT: &str = "  ";
+
+/// Characters that are escaped in Markdown text, because they may start inline elements.
+const SPECIAL_CHARS: [char; 13] = [
+    '\\', '`', '*', '_', '[', ']', '<', '>', '#', '|', '~', '&', '$',
+];
+
+#[derive(Debug, Default)]
+pub struct MarkdownRenderer {
+    citation_index: usize,
+}
+
+impl MarkdownRenderer {
+    /// Renders the given content of the heading in one line, prefixed with the heading number if it has one.
+    fn render_numbered(
+        &mut self,
+        heading: &Heading,
+        content: &[Inline],
+        context: &Context,
+    ) -> Result<String, RenderError> {
+        let mut line = String::new();
+        if let Some(number) = context.heading_number(heading) {
+            line.push_str(&escape_text(number));
+            line.push(' ');
+        }
+        line.push_str(&self.render_inlines(content, context)?.content);
+
+        Ok(single_line(&line))
+    }
+
+    /// Renders the given outline entries as nested lists linking to their headings.
+    fn render_outline(
+        &mut self,
+        outline: &[OutlineEntry],
+        context: &Context,
+    ) -> Result<String, RenderError> {
+        let mut list = String::new();
+        for entry in outline {
+            // Citations are only rendered at the heading, because rendered citations are taken in document flow.
+            let content: Vec<Inline> = entry
+                .heading
+                .content
+                .iter()
+                .filter(|inline| !matches!(inline, Inline::Citation(_)))
+                .cloned()
+                .collect();
+            let text = self.render_numbered(entry.heading, &content, context)?;
+            list.push_str(&format!("- [{}](#{})\n", text, entry.heading.id));
+
+            if !entry.children.is_empty() {
+                list.push_str(&indent(&self.render_outline(&entry.children, context)?));
+            }
+        }
+
+        Ok(list)
+    }
+
+    /// Logs that the given element is rendered with a fallback.
+    fn log_fallback(&self, kind: &str, fallback: &str) {
+        log!(
+            GeneralWarning::MarkdownFallback,
+            format!(
+                "'{}' has no Markdown equivalent and is rendered {}.",
+                kind, fallback
+            ),
+            add: element_kind_info(kind)
+        );
+    }
+}
+
+/// Escapes characters in the given text that would otherwise be interpreted as Markdown syntax.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if SPECIAL_CHARS.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Escapes markers at the start of lines that would turn the paragraph into a list, heading or thematic break.
+fn escape_line_starts(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let offset = line.len() - trimmed.len();
+            let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+
+            if trimmed.starts_with(['-', '+', '=']) {
+                format!("{}\\{}", &line[..offset], trimmed)
+            } else if digits > 0 && trimmed[digits..].starts_with(['.', ')']) {
+                format!(
+                    "{}{}\\{}",
+                    &line[..offset],
+                    &trimmed[..digits],
+                    &trimmed[digits..]
+                )
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the length of the longest run of the given character in the text.
+fn longest_run(text: &str, marker: char) -> usize {
+    text.split(|c| c != marker)
+        .map(str::len)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Replaces line breaks with whitespace, e.g. for headings and link texts.
+fn single_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && *line != "\\")
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Indents all non-empty lines of the given text to nest it in a list entry.
+fn indent(text: &str) -> String {
+    text.split_inclusive('\n')
+        .map(|line| {
+            if line.trim().is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", LIST_INDENT, line)
+            }
+        })
+        .collect()
+}
+
+/// Converts HTML created by the citation processor to escaped Markdown text.
+/// Entries in `<div>` or `<p>` elements are separated by blank lines,
+/// and `<style>` or `<script>` elements are removed.
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            rest = &rest[open..];
+            break;
+        };
+
+        let closing = rest[open + 1..].starts_with('/');
+        let tag = rest[open + 1..open + close]
+            .trim_start_matches('/')
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if matches!(tag.as_str(), "div" | "p" | "br" | "li") {
+            text.push('\n');
+        }
+        rest = &rest[open + close + 1..];
+
+        // Note: Styles and scripts are no text, so they are dropped together with their content.
+        if !closing && matches!(tag.as_str(), "style" | "script") {
+            let end_tag = format!("</{}", tag);
+            rest = match rest.to_ascii_lowercase().find(&end_tag) {
+                Some(end) => {
+                    let after_end = &rest[end..];
+                    &after_end[after_end.find('>').map_or(after_end.len(), |i| i + 1)..]
+                }
+                None => "",
+            };
+        }
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&amp;", "&");
+
+    text.lines()
+        .map(|line| escape_line_starts(&escape_text(line.trim())))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+impl Renderer<Markdown> for MarkdownRenderer {
+    fn get_target(&mut self) -> Result<Markdown, RenderError> {
+        Ok(Markdown::default())
+    }
+
+    fn render_paragraph(
+        &mut self,
+        paragraph: &Paragraph,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let inner = self.render_inlines(&paragraph.content, context)?;
+
+        Ok(Markdown::block(&escape_line_starts(inner.content.trim())))
+    }
+
+    fn render_heading(
+        &mut self,
+        heading: &Heading,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let content = self.render_numbered(heading, &heading.content, context)?;
+        let level = u8::from(heading.level) as usize;
+
+        Ok(Markdown::block(&format!(
+            "{} {}",
+            "#".repeat(level),
+            content
+        )))
+    }
+
+    fn render_verbatim_block(
+        &mut self,
+        verbatim: &VerbatimBlock,
+        _context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        // The fence must be longer than any backtick run in the content to not close the block early.
+        let fence = "`".repeat(longest_run(&verbatim.content, '`').max(2) + 1);
+        let lang = verbatim.data_lang.as_deref().unwrap_or_default();
+
+        Ok(Markdown::block(&format!(
+            "{}{}\n{}\n{}",
+            fence,
+            lang,
+            verbatim.content.trim_end_matches('\n'),
+            fence
+        )))
+    }
+
+    fn render_bullet_list(
+        &mut self,
+        bullet_list: &BulletList,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let mut entries = Markdown::default();
+
+        for entry in &bullet_list.entries {
+            entries.append(self.render_bullet_list_entry(entry, context)?)?;
+        }
+
+        Ok(Markdown::block(&entries.content))
+    }
+
+    fn render_bullet_list_entry(
+        &mut self,
+        bullet_list_entry: &BulletListEntry,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let heading = self.render_inlines(&bullet_list_entry.heading, context)?;
+        let mut entry = format!("- {}\n", indent(heading.content.trim()).trim_start());
+
+        if !bullet_list_entry.body.is_empty() {
+            let body = self.render_blocks(&bullet_list_entry.body, context)?;
+            entry.push('\n');
+            entry.push_str(&indent(&body.content));
+        }
+
+        Ok(Markdown::inline(entry))
+    }
+
+    fn render_blankline(
+        &mut self,
+        _blankline: &Span,
+        _context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        // Blocks are already separated by blank lines.
+        Ok(Markdown::default())
+    }
+
+    fn render_bibliography_block(
+        &mut self,
+        bibliography: &Bibliography,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let Some(rendered) = context.placed_bibliography(bibliography) else {
+            return Ok(Markdown::default());
+        };
+
+        let mut markdown = Markdown::default();
+        if let Some(title) = context.bibliography_title(bibliography) {
+            let level = u8::from(context.bibliography_title_level(bibliography)) as usize;
+            markdown.append(Markdown::block(&format!(
+                "{} {}",
+                "#".repeat(level),
+                escape_text(&title)
+            )))?;
+        }
+        markdown.append(Markdown::block(&html_to_text(&rendered)))?;
+
+        Ok(markdown)
+    }
+
+    fn render_toc_block(
+        &mut self,
+        toc: &TableOfContents,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let outline = context.doc.outline(&toc.options);
+        if outline.is_empty() {
+            return Ok(Markdown::default());
+        }
+
+        let mut markdown = Markdown::default();
+        if let Some(title) = context.toc_title(toc) {
+            let level = u8::from(context.toc_title_level(toc)) as usize;
+            markdown.append(Markdown::block(&format!(
+                "{} {}",
+                "#".repeat(level),
+                escape_text(&title)
+            )))?;
+        }
+        markdown.append(Markdown::block(&self.render_outline(&outline, context)?))?;
+
+        Ok(markdown)
+    }
+
+    fn render_textbox(
+        &mut self,
+        textbox: &TextBox,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        self.render_nested_inline(textbox.inner(), context)
+    }
+
+    fn render_hyperlink(
+        &mut self,
+        hyperlink: &Hyperlink,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let inner = self.render_nested_inline(hyperlink.inner(), context)?;
+        let text = if inner.content.trim().is_empty() {
+            escape_text(hyperlink.link())
+        } else {
+            single_line(&inner.content)
+        };
+
+        if !context.get_config().preamble.render.allow_unsafe && !is_safe_url(hyperlink.link()) {
+            // Note: The link text is kept, but the unsafe target is never written into the output.
+            log!(
+                GeneralWarning::UnsafeUrl,
+                format!(
+                    "Removed unsafe link target '{}'. Set `allow_unsafe` to keep it.",
+                    hyperlink.link()
+                ),
+                add: element_kind_info("Hyperlink")
+            );
+            return Ok(Markdown::inline(text));
+        }
+
+        let link = hyperlink.link();
+        let destination = if link.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+            format!("<{}>", link.replace('<', "%3C").replace('>', "%3E"))
+        } else {
+            link.to_string()
+        };
+        let title = hyperlink
+            .link_text()
+            .map(|title| format!(" \"{}\"", title.replace('\\', "\\\\").replace('"', "\\\"")))
+            .unwrap_or_default();
+
+        Ok(Markdown::inline(format!(
+            "[{}]({}{})",
+            text, destination, title
+        )))
+    }
+
+    fn render_citation(
+        &mut self,
+        _citation: &Citation,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let citation = context
+            .rendered_citation(self.citation_index)
+            .expect("Rendered citation must exist for parsed citation.");
+        self.citation_index += 1;
+
+        Ok(Markdown::inline(single_line(&html_to_text(citation))))
+    }
+
+    fn render_distinct_reference(
+        &mut self,
+        distinct_reference: &DistinctReference,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let content = context
+            .distinct_reference(distinct_reference)
+            .map(|content| escape_text(&content))
+            .unwrap_or("########### CITATION ERROR ###########".to_string());
+
+        Ok(Markdown::inline(content))
+    }
+
+    fn render_internal_reference(
+        &mut self,
+        internal_reference: &InternalReference,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        // Dead references are reported when creating the context, so the id is shown instead.
+        let content = context
+            .internal_reference_label(internal_reference)
+            .unwrap_or(internal_reference.target().to_string());
+
+        Ok(Markdown::inline(format!(
+            "[{}](#{})",
+            escape_text(&content),
+            internal_reference.target()
+        )))
+    }
+
+    fn render_bibliography(&mut self, context: &Context) -> Result<Markdown, RenderError> {
+        match &context.bibliography {
+            Some(bibliography) => {
+                let mut markdown = Markdown::block(&format!(
+                    "# {}",
+                    escape_text(context.localized_bibliography_title())
+                ));
+                markdown.append(Markdown::block(&html_to_text(bibliography)))?;
+                Ok(markdown)
+            }
+            None => Ok(Markdown::default()),
+        }
+    }
+
+    fn render_footnotes(&mut self, context: &Context) -> Result<Markdown, RenderError> {
+        match &context.footnotes {
+            Some(footnotes) => {
+                let mut markdown = Markdown::block("---");
+                markdown.append(Markdown::block(&html_to_text(footnotes)))?;
+                Ok(markdown)
+            }
+            None => Ok(Markdown::default()),
+        }
+    }
+
+    fn render_bold(&mut self, bold: &Bold, context: &Context) -> Result<Markdown, RenderError> {
+        let inner = self.render_nested_inline(bold.inner(), context)?;
+
+        Ok(Markdown::inline(format!("**{}**", inner.content)))
+    }
+
+    fn render_italic(
+        &mut self,
+        italic: &Italic,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let inner = self.render_nested_inline(italic.inner(), context)?;
+
+        Ok(Markdown::inline(format!("*{}*", inner.content)))
+    }
+
+    fn render_underline(
+        &mut self,
+        underline: &Underline,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let inner = self.render_nested_inline(underline.inner(), context)?;
+
+        Ok(Markdown::inline(format!("<ins>{}</ins>", inner.content)))
+    }
+
+    fn render_subscript(
+        &mut self,
+        subscript: &Subscript,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let inner = self.render_nested_inline(subscript.inner(), context)?;
+
+        Ok(Markdown::inline(format!("<sub>{}</sub>", inner.content)))
+    }
+
+    fn render_superscript(
+        &mut self,
+        superscript: &Superscript,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let inner = self.render_nested_inline(superscript.inner(), context)?;
+
+        Ok(Markdown::inline(format!("<sup>{}</sup>", inner.content)))
+    }
+
+    fn render_overline(
+        &mut self,
+        overline: &Overline,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        self.log_fallback("Overline", "as plain content");
+
+        self.render_nested_inline(overline.inner(), context)
+    }
+
+    fn render_strikethrough(
+        &mut self,
+        strikethrough: &Strikethrough,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let inner = self.render_nested_inline(strikethrough.inner(), context)?;
+
+        Ok(Markdown::inline(format!("~~{}~~", inner.content)))
+    }
+
+    fn render_highlight(
+        &mut self,
+        highlight: &Highlight,
+        context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        self.log_fallback("Highlight", "as plain content");
+
+        self.render_nested_inline(highlight.inner(), context)
+    }
+
+    fn render_quote(&mut self, quote: &Quote, context: &Context) -> Result<Markdown, RenderError> {
+        let inner = self.render_nested_inline(quote.inner(), context)?;
+
+        Ok(Markdown::inline(format!("\"{}\"", inner.content)))
+    }
+
+    fn render_inline_verbatim(
+        &mut self,
+        verbatim: &Verbatim,
+        _context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        let content = verbatim.inner().as_unimarkup();
+        // The code span must be delimited by more backticks than any backtick run in the content.
+        let fence = "`".repeat(longest_run(&content, '`') + 1);
+        let padding = if content.starts_with('`') || content.ends_with('`') {
+            " "
+        } else {
+            ""
+        };
+
+        Ok(Markdown::inline(format!(
+            "{fence}{padding}{content}{padding}{fence}"
+        )))
+    }
+
+    fn render_inline_math(
+        &mut self,
+        math: &Math,
+        _context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        // TODO: resolve logic inlines before parsing math.
+        let content_str: String = math
+            .inner()
+            .iter()
+            .filter_map(|i| match i {
+                Inline::Plain(p) => Some(p.content().clone()),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Markdown::inline(format!("${}$", content_str.trim())))
+    }
+
+    fn render_plain(&mut self, plain: &Plain, _context: &Context) -> Result<Markdown, RenderError> {
+        Ok(Markdown::inline(escape_text(plain.content())))
+    }
+
+    fn render_newline(
+        &mut self,
+        _newline: &Newline,
+        _context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        Ok(Markdown::inline("\n"))
+    }
+
+    fn render_implicit_newline(
+        &mut self,
+        _implicit_newline: &Newline,
+        _context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        Ok(Markdown::inline("\n"))
+    }
+
+    fn render_escaped_newline(
+        &mut self,
+        _escaped_newline: &EscapedNewline,
+        _context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        // A backslash at the end of a line is a hard line break.
+        Ok(Markdown::inline("\\\n"))
+    }
+
+    fn render_escaped_whitespace(
+        &mut self,
+        _escaped_whitespace: &EscapedWhitespace,
+        _context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        Ok(Markdown::inline("&nbsp;"))
+    }
+
+    fn render_escaped_plain(
+        &mut self,
+        escaped_plain: &EscapedPlain,
+        _context: &Context,
+    ) -> Result<Markdown, RenderError> {
+        Ok(Markdown::inline(escape_text(escaped_plain.content())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use unimarkup_commons::config::{output::OutputFormatKind, Config};
+
+    use super::*;
+
+    fn render(input: &str) -> String {
+        let doc = unimarkup_parser::parse_unimarkup(input, Config::default());
+
+        crate::render::render(
+            &doc,
+            OutputFormatKind::Markdown,
+            MarkdownRenderer::default(),
+        )
+        .unwrap()
+        .to_string()
+    }
+
+    #[test]
+    fn render_blocks() {
+        let markdown = render("# Intro\n\nSome **bold** and *italic* text.\n\n- first\n- second\n");
+
+        assert_eq!(
+            markdown,
+            "# Intro\n\nSome **bold** and *italic* text.\n\n- first\n- second\n"
+        );
+    }
+
+    #[test]
+    fn render_citations_and_bibliography() {
+        let mut config = Config::default();
+        config.preamble.cite.style = Some("apa".into());
+        config.preamble.cite.references =
+            ["./src/html/citeproc/test_files/citation_items.csl".into()].into();
+        let doc = unimarkup_parser::parse_unimarkup("See [&&id-1]\n", config);
+
+        let markdown = crate::render::render(
+            &doc,
+            OutputFormatKind::Markdown,
+            MarkdownRenderer::default(),
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(markdown.starts_with("See (Cook, 1997)\n"), "{}", markdown);
+        assert!(
+            markdown.contains("# Bibliography\n\nCook, A. (1997). Some Notes on Gertrude Stein"),
+            "{}",
+            markdown
+        );
+        assert!(!markdown.contains("csl-entry"), "{}", markdown);
+    }
+
+    #[test]
+    fn render_verbatim_with_backticks() {
+        let markdown = render("````rust\nlet s = \"```\";\n````\n");
+
+        assert_eq!(markdown, "````rust\nlet s = \"```\";\n````\n");
+    }
+
+    #[test]
+    fn render_highlight_fallback_and_escapes() {
+        let markdown = render("||high|| and <tag>\n");
+
+        assert_eq!(markdown, "high and \\<tag\\>\n");
+    }
+
+    #[test]
+    fn escape_list_markers_at_line_start() {
+        assert_eq!(
+            escape_line_starts("1. item\n- item\ntext"),
+            "1\\. item\n\\- item\ntext"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+//! Validation of internal references against the labelled elements of a document.
+
+use logid::{
+    log,
+    logging::event_entry::AddonKind,
+    lsp_types::{Diagnostic, DiagnosticSeverity, Range},
+};
+use unimarkup_commons::config::preamble::element_kind_info;
+use unimarkup_inline::element::{textbox::reference::InternalReference, InlineElement};
+use unimarkup_parser::document::{Document, ReferenceTarget};
+
+use crate::html::citeproc::validate::lsp_position;
+use crate::log_id::GeneralWarning;
+
+/// Returns all internal references of the given document whose target does not exist,
+/// in order of appearance.
+pub fn dead_references<'a>(
+    doc: &'a Document,
+    targets: &[ReferenceTarget],
+) -> Vec<&'a InternalReference> {
+    doc.internal_references()
+        .into_iter()
+        .filter(|reference| !targets.iter().any(|target| target.id == reference.target()))
+        .collect()
+}
+
+/// Logs all internal references of the given document whose target does not exist.
+pub fn log_dead_references(doc: &Document, targets: &[ReferenceTarget]) {
+    for reference in dead_references(doc, targets) {
+        let msg = format!(
+            "Internal reference target '{}' does not exist.",
+            reference.target()
+        );
+        let start = reference.start();
+
+        log!(
+            GeneralWarning::DeadInternalReference,
+            format!("{} (line {}, column {})", msg, start.line, start.col_utf8),
+            add: AddonKind::Diagnostic(Diagnostic {
+                range: Range::new(lsp_position(start), lsp_position(reference.end())),
+                severity: Some(DiagnosticSeverity::WARNING),
+                source: Some("unimarkup".to_string()),
+                message: msg,
+                ..Default::default()
+            }),
+            add: element_kind_info("InternalReference")
+        );
+    }
+}
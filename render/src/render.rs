@@ -1,14 +1,21 @@
 //! Contains the [`Render`] trait definition.
 
 use crate::csl_json::csl_types::CslData;
-use crate::html::citeproc::{get_csl_data, CiteprocWrapper};
+use crate::html::citeproc::{
+    get_csl_data, validate::log_citation_issues, CiteprocWrapper, CITATION_ERROR,
+};
+use crate::html::template::HtmlTemplate;
+use crate::references::log_dead_references;
 use logid::log;
 use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashSet;
 use unimarkup_commons::config::output::OutputFormatKind;
+use unimarkup_commons::config::preamble::element_kind_info;
 use unimarkup_commons::config::Config;
 use unimarkup_commons::{
     config::icu_locid::{locale, Locale},
-    lexer::span::Span,
+    lexer::{position::Position, span::Span},
 };
 use unimarkup_inline::element::substitution::DistinctReference;
 use unimarkup_inline::element::{
@@ -17,20 +24,20 @@ use unimarkup_inline::element::{
         Bold, Highlight, Italic, Math, Overline, Quote, Strikethrough, Subscript, Superscript,
         Underline, Verbatim,
     },
-    textbox::{citation::Citation, hyperlink::Hyperlink, TextBox},
+    textbox::{citation::Citation, hyperlink::Hyperlink, reference::InternalReference, TextBox},
     Inline,
 };
 use unimarkup_parser::{
-    document::Document,
+    document::{Document, ReferenceTarget, TargetKind},
     elements::{
-        atomic::{Heading, Paragraph},
+        atomic::{Bibliography, Heading, HeadingLevel, Paragraph, TableOfContents, TocOptions},
         blocks::Block,
         enclosed,
         indents::{BulletList, BulletListEntry},
     },
 };
 
-use crate::log_id::{GeneralWarning, RenderError};
+use crate::log_id::{CiteError, RenderError};
 
 pub struct Context<'a> {
     pub doc: &'a Document,
@@ -38,6 +45,9 @@ pub struct Context<'a> {
     pub footnotes: Option<String>,
     pub bibliography: Option<String>,
     pub csl_data: CslData,
+    pub(crate) citeproc: RefCell<CiteprocWrapper>,
+    pub(crate) reference_targets: Vec<ReferenceTarget>,
+    pub(crate) html_template: Option<HtmlTemplate>,
 }
 
 impl<'a> Context<'a> {
@@ -58,6 +68,17 @@ impl<'a> Context<'a> {
 
     fn new(doc: &'a Document, format: OutputFormatKind) -> Self {
         let csl_data = get_csl_data(&doc.config.preamble.cite.references);
+        let reference_targets = doc.reference_targets();
+        let html_template = match format {
+            OutputFormatKind::Html | OutputFormatKind::Pdf => doc
+                .config
+                .preamble
+                .render
+                .html_template
+                .as_deref()
+                .and_then(HtmlTemplate::load),
+            OutputFormatKind::Umi | OutputFormatKind::Markdown | OutputFormatKind::Latex => None,
+        };
         if doc.citations.is_empty() {
             return Context {
                 doc,
@@ -65,52 +86,23 @@ impl<'a> Context<'a> {
                 footnotes: None,
                 bibliography: None,
                 csl_data,
+                citeproc: RefCell::new(CiteprocWrapper::new()),
+                reference_targets,
+                html_template,
             };
         }
-        let rendered_citations: Vec<String>;
-        let footnotes: Option<String>;
-        let bibliography: Option<String>;
-
-        match CiteprocWrapper::new() {
-            Ok(mut citeproc) => {
-                let for_pagedjs = matches!(format, OutputFormatKind::Html);
-                let citation_ids = doc
-                    .citations
-                    .clone()
-                    .into_iter()
-                    .map(|c| match serde_json::to_value::<Vec<String>>(c) {
-                        Ok(citation_ids) => citation_ids,
-                        Err(e) => {
-                            log!(
-                                GeneralWarning::JSONSerialization,
-                                format!("JSON serialization failed with error: '{:?}'", e)
-                            );
-                            serde_json::to_value::<Vec<String>>(vec![]).unwrap()
-                        }
-                    })
-                    .collect::<Value>();
-                rendered_citations =
-                    match citeproc.get_citation_strings(doc, &[citation_ids], for_pagedjs) {
-                        Ok(rendered_citations) => rendered_citations,
-                        Err(e) => {
-                            log!(e);
-                            vec![
-                                "########### CITATION ERROR ###########".to_string();
-                                doc.citations.len()
-                            ]
-                        }
-                    };
-                footnotes = citeproc.get_footnotes().ok();
-                bibliography = citeproc.get_bibliography().ok();
-            }
-            Err(e) => {
-                log!(e);
-                rendered_citations =
-                    vec!["########### CITATION ERROR ###########".to_string(); doc.citations.len()];
-                footnotes = None;
-                bibliography = None;
-            }
-        }
+        let mut citeproc = CiteprocWrapper::new();
+        let for_pagedjs = matches!(format, OutputFormatKind::Html);
+        let rendered_citations =
+            match citeproc.get_citation_strings(doc, &doc.citations, for_pagedjs) {
+                Ok(rendered_citations) => rendered_citations,
+                Err(e) => {
+                    log!(e);
+                    vec![CITATION_ERROR.to_string(); doc.citations.len()]
+                }
+            };
+        let footnotes = citeproc.get_footnotes().ok();
+        let bibliography = citeproc.get_bibliography().ok();
 
         Context {
             doc,
@@ -118,6 +110,220 @@ impl<'a> Context<'a> {
             footnotes,
             bibliography,
             csl_data: csl_data.clone(),
+            citeproc: RefCell::new(citeproc),
+            reference_targets,
+            html_template,
+        }
+    }
+
+    /// Returns `true` if the configured HTML template places the table of contents.
+    pub fn template_places_toc(&self) -> bool {
+        self.html_template
+            .as_ref()
+            .is_some_and(HtmlTemplate::places_toc)
+    }
+
+    /// Returns `true` if the configured HTML template places the bibliography.
+    pub fn template_places_bibliography(&self) -> bool {
+        self.html_template
+            .as_ref()
+            .is_some_and(HtmlTemplate::places_bibliography)
+    }
+
+    /// Returns the rendered bibliography of the entries the given [`Bibliography`] element lists,
+    /// or `None` if no cited entry matches its filters.
+    pub fn placed_bibliography(&self, bibliography: &Bibliography) -> Option<String> {
+        if self.doc.citations.is_empty() {
+            return None;
+        }
+        let types = &bibliography.options.types;
+        let ids: HashSet<String> = self
+            .doc
+            .bibliography_entries(bibliography)
+            .map(|entry| entry.id().to_string())
+            .filter(|id| {
+                types.is_empty()
+                    || self.csl_data.items.iter().any(|item| {
+                        item.id.to_string() == *id
+                            && serde_json::to_value(item.type_)
+                                .is_ok_and(|item_type| types.iter().any(|t| item_type == *t))
+                    })
+            })
+            .collect();
+
+        match self.citeproc.borrow().get_filtered_bibliography(Some(&ids)) {
+            Ok(rendered) if !rendered.is_empty() => Some(rendered),
+            Ok(_) => None,
+            Err(e) => {
+                log!(e);
+                None
+            }
+        }
+    }
+
+    /// Returns the rendered authors of the reference with the given id.
+    /// The processor of this rendering is reused, and authors are only rendered once per reference.
+    pub fn author_only(&self, id: &str) -> Result<String, CiteError> {
+        self.citeproc
+            .borrow_mut()
+            .get_author_only(self.doc, id.to_string())
+    }
+
+    /// Returns the content of the given [`DistinctReference`], formatted for the main language of this rendering.
+    /// Fields without a format are taken as they are from the CSL-JSON item of the reference.
+    /// Returns `None` if the reference or the selected field does not exist.
+    pub fn distinct_reference(&self, distinct_reference: &DistinctReference) -> Option<String> {
+        let item = self
+            .csl_data
+            .items
+            .iter()
+            .find(|item| item.id.to_string() == distinct_reference.id())?;
+        let fields = distinct_reference.fields();
+        if fields.len() == 1 && fields[0] == "authors" {
+            return self.author_only(distinct_reference.id()).ok();
+        }
+
+        let item = serde_json::to_value(item).ok()?;
+        match self
+            .citeproc
+            .borrow_mut()
+            .get_formatted_field(self.doc, &item, fields)
+        {
+            Ok(Some(formatted)) => return Some(formatted),
+            Ok(None) => {}
+            Err(e) => {
+                log!(e);
+            }
+        }
+
+        let mut value = item;
+        for field in fields {
+            value = match field.parse::<usize>() {
+                Ok(n) => value.get(n).cloned(),
+                Err(_) => value.get(field).cloned(),
+            }
+            .unwrap_or_default();
+        }
+        match value {
+            Value::Null => None,
+            Value::String(s) => Some(s),
+            Value::Number(number) => Some(match number.as_f64() {
+                Some(float) if float.fract() == 0.0 => (float as i64).to_string(),
+                _ => number.to_string(),
+            }),
+            value => Some(value.to_string()),
+        }
+    }
+
+    /// Returns the title shown above the given [`Bibliography`] element,
+    /// or `None` if the title is set to be empty.
+    /// Falls back to a title in the main language if no title is set.
+    pub fn bibliography_title(&self, bibliography: &Bibliography) -> Option<String> {
+        match &bibliography.options.title {
+            Some(title) if title.is_empty() => None,
+            Some(title) => Some(title.clone()),
+            None => Some(self.localized_bibliography_title().to_string()),
+        }
+    }
+
+    /// Returns the level of the title heading of the given [`Bibliography`] element.
+    /// The title is one level below the last heading before the element, or level 1 if there is none.
+    pub fn bibliography_title_level(&self, bibliography: &Bibliography) -> HeadingLevel {
+        self.title_level_at(bibliography.start)
+    }
+
+    /// Returns the bibliography title for the main language of this rendering.
+    pub fn localized_bibliography_title(&self) -> &'static str {
+        match self.get_lang().id.language.as_str() {
+            "de" => "Literaturverzeichnis",
+            "fr" => "Bibliographie",
+            "es" => "Bibliografía",
+            _ => "Bibliography",
+        }
+    }
+
+    /// Returns the title shown above the given [`TableOfContents`] element,
+    /// or `None` if the title is set to be empty.
+    /// Falls back to a title in the main language if no title is set.
+    pub fn toc_title(&self, toc: &TableOfContents) -> Option<String> {
+        match &toc.options.title {
+            Some(title) if title.is_empty() => None,
+            Some(title) => Some(title.clone()),
+            None => Some(self.localized_toc_title().to_string()),
+        }
+    }
+
+    /// Returns the level of the title heading of the given [`TableOfContents`] element.
+    /// The title is one level below the last heading before the element, or level 1 if there is none.
+    pub fn toc_title_level(&self, toc: &TableOfContents) -> HeadingLevel {
+        self.title_level_at(toc.start)
+    }
+
+    /// Returns the table of contents title for the main language of this rendering.
+    pub fn localized_toc_title(&self) -> &'static str {
+        match self.get_lang().id.language.as_str() {
+            "de" => "Inhaltsverzeichnis",
+            "fr" => "Table des matières",
+            "es" => "Índice",
+            _ => "Contents",
+        }
+    }
+
+    /// Level one below the last top-level heading before the given position, or level 1 if there is none.
+    fn title_level_at(&self, start: Position) -> HeadingLevel {
+        let level = self
+            .doc
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Heading(heading) if heading.start < start => {
+                    Some(u8::from(heading.level) + 1)
+                }
+                _ => None,
+            })
+            .next_back()
+            .unwrap_or(1)
+            .min(6);
+
+        HeadingLevel::try_from(level.to_string().as_str()).unwrap_or(HeadingLevel::Level1)
+    }
+
+    /// Returns the labelled element the given [`InternalReference`] points to, if it exists.
+    /// The first element wins if several elements share an id.
+    pub fn reference_target(&self, reference: &InternalReference) -> Option<&ReferenceTarget> {
+        self.reference_targets
+            .iter()
+            .find(|target| target.id == reference.target())
+    }
+
+    /// Returns the label of the element the given [`InternalReference`] points to in the main language
+    /// of this rendering, e.g. `Section 2.1`, or `None` if the element does not exist or is not numbered.
+    pub fn internal_reference_label(&self, reference: &InternalReference) -> Option<String> {
+        let target = self.reference_target(reference)?;
+        Some(format!(
+            "{} {}",
+            self.localized_target_kind(target.kind),
+            target.number.as_ref()?
+        ))
+    }
+
+    /// Returns the number shown for the given [`Heading`], e.g. `2.1`.
+    /// Returns `None` if no numbering depth is set in the preamble, or the heading is deeper than the depth.
+    pub fn heading_number(&self, heading: &Heading) -> Option<&str> {
+        self.doc.config.preamble.numbering.numbering_depth?;
+        self.reference_targets
+            .iter()
+            .find(|target| target.kind == TargetKind::Section && target.start == heading.start)?
+            .number
+            .as_deref()
+    }
+
+    /// Returns the name of the given [`TargetKind`] for the main language of this rendering.
+    pub fn localized_target_kind(&self, kind: TargetKind) -> &'static str {
+        match (self.get_lang().id.language.as_str(), kind) {
+            ("de", TargetKind::Section) => "Abschnitt",
+            ("es", TargetKind::Section) => "Sección",
+            (_, TargetKind::Section) => "Section",
         }
     }
 
@@ -126,6 +332,14 @@ impl<'a> Context<'a> {
     }
 }
 
+/// Logs cited ids that are not defined in the references, unused references and dead internal references.
+///
+/// **Note:** This is not done while rendering, because a document is rendered once per output format.
+pub fn log_document_issues(doc: &Document) {
+    log_citation_issues(doc, &get_csl_data(&doc.config.preamble.cite.references));
+    log_dead_references(doc, &doc.reference_targets());
+}
+
 pub fn render<T: OutputFormat>(
     doc: &Document,
     format: OutputFormatKind,
@@ -134,11 +348,26 @@ pub fn render<T: OutputFormat>(
     let context = Context::new(doc, format);
     let mut t = T::new(&context);
 
+    if doc.config.preamble.toc.toc && !doc.places_toc() && !context.template_places_toc() {
+        let toc = TableOfContents {
+            options: TocOptions::default(),
+            start: Position::default(),
+            end: Position::default(),
+        };
+        match renderer.render_toc_block(&toc, &context) {
+            Ok(rendered) => t.append(rendered)?,
+            Err(RenderError::Unimplemented) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
     t.append(renderer.render_blocks(&doc.blocks, &context)?)?;
 
     // TODO: replace once logic is implemented
     t.append(renderer.render_footnotes(&context)?)?;
-    t.append(renderer.render_bibliography(&context)?)?;
+    if !doc.places_bibliography() && !context.template_places_bibliography() {
+        t.append(renderer.render_bibliography(&context)?)?;
+    }
 
     Ok(t)
 }
@@ -210,6 +439,24 @@ pub trait Renderer<T: OutputFormat> {
         Err(RenderError::Unimplemented)
     }
 
+    /// Render a Unimarkup [`Bibliography`] element to the output format `T`.
+    fn render_bibliography_block(
+        &mut self,
+        _bibliography: &Bibliography,
+        _context: &Context,
+    ) -> Result<T, RenderError> {
+        Err(RenderError::Unimplemented)
+    }
+
+    /// Render a Unimarkup [`TableOfContents`] element to the output format `T`.
+    fn render_toc_block(
+        &mut self,
+        _toc: &TableOfContents,
+        _context: &Context,
+    ) -> Result<T, RenderError> {
+        Err(RenderError::Unimplemented)
+    }
+
     //--------------------------------- INLINES ---------------------------------
 
     /// Render a [`TextBox`] to the output format `T`.
@@ -242,6 +489,15 @@ pub trait Renderer<T: OutputFormat> {
         Err(RenderError::Unimplemented)
     }
 
+    /// Render an [`InternalReference`] to the output format `T`.
+    fn render_internal_reference(
+        &mut self,
+        _internal_reference: &InternalReference,
+        _context: &Context,
+    ) -> Result<T, RenderError> {
+        Err(RenderError::Unimplemented)
+    }
+
     fn render_bibliography(&mut self, _context: &Context) -> Result<T, RenderError> {
         Err(RenderError::Unimplemented)
     }
@@ -394,6 +650,7 @@ pub trait Renderer<T: OutputFormat> {
                             "Rendering of block '{}' is not implemented",
                             block.variant_str()
                         ),
+                        add: element_kind_info(block.variant_str())
                     );
                     continue;
                 }
@@ -414,6 +671,10 @@ pub trait Renderer<T: OutputFormat> {
             Block::VerbatimBlock(verbatim) => self.render_verbatim_block(verbatim, context),
             Block::BulletList(bullet_list) => self.render_bullet_list(bullet_list, context),
             Block::Blankline(blankline) => self.render_blankline(blankline, context),
+            Block::Bibliography(bibliography) => {
+                self.render_bibliography_block(bibliography, context)
+            }
+            Block::TableOfContents(toc) => self.render_toc_block(toc, context),
             Block::BulletListEntry(_) => {
                 debug_assert!(
                     false,
@@ -437,6 +698,7 @@ pub trait Renderer<T: OutputFormat> {
                             "Rendering of inline '{}' is not implemented",
                             inline.variant_str()
                         ),
+                        add: element_kind_info(inline.variant_str())
                     );
                     continue;
                 }
@@ -482,6 +744,9 @@ pub trait Renderer<T: OutputFormat> {
             Inline::TextBox(textbox) => self.render_textbox(textbox, context),
             Inline::Hyperlink(hyperlink) => self.render_hyperlink(hyperlink, context),
             Inline::Citation(citation) => self.render_citation(citation, context),
+            Inline::InternalReference(internal_reference) => {
+                self.render_internal_reference(internal_reference, context)
+            }
 
             Inline::DistinctReference(distinct_reference) => {
                 self.render_distinct_reference(distinct_reference, context)
@@ -22,7 +22,9 @@ use unimarkup_inline::{
 use unimarkup_parser::{
     document::Document,
     elements::{
-        atomic::{Heading, Paragraph},
+        atomic::{
+            Bibliography, BibliographyOptions, Heading, Paragraph, TableOfContents, TocOptions,
+        },
         blocks::Block,
         enclosed::VerbatimBlock,
         indents::{BulletList, BulletListEntry},
@@ -340,14 +342,52 @@ impl Umi {
                 Ok(Block::BulletListEntry(bullet_list_entry))
             }
             "Bibliography" => {
-                let paragraph = Paragraph {
-                    content: vec![Inline::Plain(Plain::new(
-                        "{$um.bibliography}".to_string(),
-                        Position::default(),
-                        Position::default(),
-                    ))],
+                let options = current_line
+                    .content
+                    .trim()
+                    .strip_prefix("{$um.bibliography}")
+                    .map(|options| {
+                        if options.trim().is_empty() {
+                            Ok(BibliographyOptions::default())
+                        } else {
+                            serde_json::from_str(options)
+                        }
+                    })
+                    .and_then(Result::ok)
+                    .ok_or(UmiParserError::InvalidPropertyValue((
+                        "content".into(),
+                        current_line.position,
+                    )))?;
+                let bibliography = Bibliography {
+                    options,
+                    start: Position::new(1, 1), // Fallback in case content has been changed manually in .umi
+                    end: Position::new(1, 1), // Fallback in case content has been changed manually in .umi
                 };
-                Ok(Block::Paragraph(paragraph))
+                Ok(Block::Bibliography(bibliography))
+            }
+            "TableOfContents" => {
+                let options = current_line
+                    .content
+                    .trim()
+                    .strip_prefix("{$um.toc}")
+                    .map(|options| {
+                        if options.trim().is_empty() {
+                            Ok(TocOptions::default())
+                        } else {
+                            serde_json::from_str(options)
+                        }
+                    })
+                    .and_then(Result::ok)
+                    .ok_or(UmiParserError::InvalidPropertyValue((
+                        "content".into(),
+                        current_line.position,
+                    )))?;
+                let toc = TableOfContents {
+                    options,
+                    start: Position::new(1, 1), // Fallback in case content has been changed manually in .umi
+                    end: Position::new(1, 1), // Fallback in case content has been changed manually in .umi
+                };
+                Ok(Block::TableOfContents(toc))
             }
             "Footnotes" => {
                 let paragraph = Paragraph {
@@ -1,5 +1,9 @@
 use unimarkup_inline::element::InlineElement;
-use unimarkup_parser::elements::blocks::Block;
+use unimarkup_parser::elements::{
+    atomic::{Bibliography, TableOfContents},
+    blocks::Block,
+    BlockElement,
+};
 
 use crate::log_id::RenderError;
 use crate::render::{Context, OutputFormat, Renderer};
@@ -234,6 +238,52 @@ impl Renderer<Umi> for UmiRenderer {
         Ok(Umi::default())
     }
 
+    fn render_bibliography_block(
+        &mut self,
+        bibliography: &Bibliography,
+        context: &Context,
+    ) -> Result<Umi, crate::log_id::RenderError> {
+        let entry = UmiRow::new(
+            self.pos,
+            String::new(),
+            String::from(Block::Bibliography(bibliography.to_owned()).variant_str()),
+            String::new(),
+            self.depth,
+            bibliography.as_unimarkup(),
+            String::new(),
+        );
+        self.pos += 1;
+
+        self.proceed(Umi::with_um(
+            vec![entry],
+            context.get_config().clone(),
+            context.get_lang().to_string(),
+        ))
+    }
+
+    fn render_toc_block(
+        &mut self,
+        toc: &TableOfContents,
+        context: &Context,
+    ) -> Result<Umi, crate::log_id::RenderError> {
+        let entry = UmiRow::new(
+            self.pos,
+            String::new(),
+            String::from(Block::TableOfContents(toc.to_owned()).variant_str()),
+            String::new(),
+            self.depth,
+            toc.as_unimarkup(),
+            String::new(),
+        );
+        self.pos += 1;
+
+        self.proceed(Umi::with_um(
+            vec![entry],
+            context.get_config().clone(),
+            context.get_lang().to_string(),
+        ))
+    }
+
     fn render_bibliography(
         &mut self,
         context: &Context,